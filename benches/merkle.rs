@@ -0,0 +1,30 @@
+//! Compares `Digest::merkle_root_parallel` with `Digest::merkle_root`.
+//!
+//! Run with `cargo bench --bench merkle --features rayon`.
+
+use std::time::{ Duration, Instant };
+
+use bc_components::Digest;
+
+const LEAVES: usize = 1_000_000;
+
+fn time(f: impl FnOnce() -> Digest) -> (Digest, Duration) {
+    let start = Instant::now();
+    let root = f();
+    (root, start.elapsed())
+}
+
+fn main() {
+    let leaves: Vec<Digest> = (0..LEAVES).map(|i| Digest::from_image(i.to_le_bytes())).collect();
+
+    let (serial_root, serial) = time(|| Digest::merkle_root(&leaves));
+    let (parallel_root, parallel) = time(|| Digest::merkle_root_parallel(&leaves));
+    assert_eq!(parallel_root, serial_root);
+    println!(
+        "{} leaves: merkle_root {:?}, merkle_root_parallel {:?}, speedup {:.2}x",
+        LEAVES,
+        serial,
+        parallel,
+        serial.as_secs_f64() / parallel.as_secs_f64()
+    );
+}
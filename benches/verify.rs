@@ -0,0 +1,38 @@
+//! Compares `Verifier::verify_all` with a loop over `Verifier::verify`.
+//!
+//! Run with `cargo bench --bench verify`.
+
+use std::time::{ Duration, Instant };
+
+use bc_components::{ ECPrivateKey, Signature, Signer, SigningPrivateKey, Verifier };
+
+const SIGNATURES: usize = 1000;
+const ROUNDS: u32 = 10;
+
+fn time(mut f: impl FnMut() -> bool) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        assert!(f());
+    }
+    start.elapsed() / ROUNDS
+}
+
+fn main() {
+    let private_key = SigningPrivateKey::new_schnorr(ECPrivateKey::new());
+    let public_key = private_key.public_key();
+    let messages: Vec<Vec<u8>> = (0..SIGNATURES).map(|i| format!("message {}", i).into_bytes()).collect();
+    let entries: Vec<(Signature, &[u8])> = messages
+        .iter()
+        .map(|m| (private_key.sign(m).unwrap(), m.as_slice()))
+        .collect();
+
+    let serial = time(|| entries.iter().all(|(signature, message)| public_key.verify(signature, message)));
+    let verify_all = time(|| public_key.verify_all(&entries));
+    println!(
+        "{} Schnorr signatures: serial loop {:?}, verify_all {:?}, ratio {:.2}",
+        SIGNATURES,
+        serial,
+        verify_all,
+        serial.as_secs_f64() / verify_all.as_secs_f64()
+    );
+}
@@ -1,93 +1,232 @@
-use std::rc::Rc;
-use bc_crypto::x25519_new_agreement_private_key_using;
+use alloc::{ rc::Rc, string::String, vec, vec::Vec };
+use bc_crypto::{ hash::hkdf_hmac_sha256, x25519_new_agreement_private_key_using };
 use bc_ur::prelude::*;
-use crate::{ tags, AgreementPublicKey, SymmetricKey };
-use bc_rand::{ SecureRandomNumberGenerator, RandomNumberGenerator };
+use crate::{ tags, AgreementPublicKey, ComponentError, EncryptedMessage, KeyAgreement, SymmetricKey };
+use bc_rand::RandomNumberGenerator;
 use anyhow::{ bail, Error, Result };
+use subtle::ConstantTimeEq;
+use zeroize::{ Zeroize, ZeroizeOnDrop };
 
-/// A Curve25519 private key used for X25519 key agreement.
+/// A private key used for key agreement, either X25519 or X448.
 ///
 /// <https://datatracker.ietf.org/doc/html/rfc7748>
-#[derive(Clone, PartialEq, Eq, Hash)]
-pub struct AgreementPrivateKey([u8; Self::KEY_SIZE]);
+///
+/// Agreement is only possible with an `AgreementPublicKey` on the same curve.
+/// The key material is zeroized when the key is dropped.
+#[derive(Clone, PartialEq, Eq, Hash, Zeroize, ZeroizeOnDrop)]
+pub struct AgreementPrivateKey(Curve);
+
+#[derive(Clone, PartialEq, Eq, Hash, Zeroize)]
+enum Curve {
+    X25519([u8; AgreementPrivateKey::KEY_SIZE]),
+    X448([u8; AgreementPrivateKey::X448_KEY_SIZE]),
+}
 
 impl AgreementPrivateKey {
     pub const KEY_SIZE: usize = 32;
+    pub const X448_KEY_SIZE: usize = 56;
 
-    /// Generate a new random `AgreementPrivateKey`.
+    /// Generate a new random X25519 `AgreementPrivateKey`.
+    #[cfg(feature = "std")]
     pub fn new() -> Self {
-        let mut rng = SecureRandomNumberGenerator;
+        let mut rng = bc_rand::SecureRandomNumberGenerator;
         Self::new_using(&mut rng)
     }
 
-    /// Generate a new random `AgreementPrivateKey` using the given random number generator.
+    /// Generate a new random X25519 `AgreementPrivateKey` using the given random number generator.
     pub fn new_using(rng: &mut impl RandomNumberGenerator) -> Self {
-        Self(x25519_new_agreement_private_key_using(rng))
+        Self::from_data(x25519_new_agreement_private_key_using(rng))
     }
 
-    /// Restore an `AgreementPrivateKey` from a fixed-size array of bytes.
+    /// Generate a new random X448 `AgreementPrivateKey`.
+    #[cfg(feature = "std")]
+    pub fn new_x448() -> Self {
+        let mut rng = bc_rand::SecureRandomNumberGenerator;
+        Self::new_x448_using(&mut rng)
+    }
+
+    /// Generate a new random X448 `AgreementPrivateKey` using the given random number generator.
+    pub fn new_x448_using(rng: &mut impl RandomNumberGenerator) -> Self {
+        let mut data = [0u8; Self::X448_KEY_SIZE];
+        rng.fill_random_data(&mut data);
+        Self::from_x448_data(data)
+    }
+
+    /// Restore an X25519 `AgreementPrivateKey` from a fixed-size array of bytes.
     pub const fn from_data(data: [u8; Self::KEY_SIZE]) -> Self {
-        Self(data)
+        Self(Curve::X25519(data))
+    }
+
+    /// Restore an X448 `AgreementPrivateKey` from a fixed-size array of bytes.
+    pub const fn from_x448_data(data: [u8; Self::X448_KEY_SIZE]) -> Self {
+        Self(Curve::X448(data))
     }
 
-    /// Restore an `AgreementPrivateKey` from a reference to an array of bytes.
+    /// Restore an `AgreementPrivateKey` from a reference to an array of bytes,
+    /// which is an X25519 key if it is `KEY_SIZE` bytes long and an X448 key
+    /// if it is `X448_KEY_SIZE` bytes long.
     pub fn from_data_ref(data: impl AsRef<[u8]>) -> Result<Self> {
         let data = data.as_ref();
-        if data.len() != Self::KEY_SIZE {
-            bail!("Invalid agreement private key size");
+        if let Ok(data) = data.try_into() {
+            Ok(Self::from_data(data))
+        } else if let Ok(data) = data.try_into() {
+            Ok(Self::from_x448_data(data))
+        } else {
+            bail!(ComponentError::InvalidLength { component: "agreement private key" });
         }
-        let mut arr = [0u8; Self::KEY_SIZE];
-        arr.copy_from_slice(data);
-        Ok(Self::from_data(arr))
     }
 
-    /// Get a reference to the fixed-size array of bytes.
+    /// Get a reference to the fixed-size array of bytes of an X25519 key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this is an X448 key. Use `to_x25519` to check the curve
+    /// first, or `as_bytes` for the bytes of a key on either curve.
     pub fn data(&self) -> &[u8; Self::KEY_SIZE] {
         self.into()
     }
 
+    /// Get a reference to the fixed-size array of bytes if this is an X25519
+    /// key.
+    pub fn to_x25519(&self) -> Option<&[u8; Self::KEY_SIZE]> {
+        match &self.0 {
+            Curve::X25519(data) => Some(data),
+            Curve::X448(_) => None,
+        }
+    }
+
+    /// Get a reference to the fixed-size array of bytes if this is an X448
+    /// key.
+    pub fn to_x448(&self) -> Option<&[u8; Self::X448_KEY_SIZE]> {
+        match &self.0 {
+            Curve::X25519(_) => None,
+            Curve::X448(data) => Some(data),
+        }
+    }
+
+    /// Get a reference to the key bytes, `KEY_SIZE` bytes long for an X25519
+    /// key and `X448_KEY_SIZE` bytes long for an X448 key.
+    pub fn as_bytes(&self) -> &[u8] {
+        match &self.0 {
+            Curve::X25519(data) => data,
+            Curve::X448(data) => data,
+        }
+    }
+
     /// Restore an `AgreementPrivateKey` from a hex string.
     ///
     /// # Panics
     ///
-    /// Panics if the hex string is invalid or the length is not `AgreementPrivateKey::KEY_SIZE * 2`.
+    /// Panics if the hex string is invalid or does not encode a key of a
+    /// valid size.
     pub fn from_hex(hex: impl AsRef<str>) -> Self {
         Self::from_data_ref(hex::decode(hex.as_ref()).unwrap()).unwrap()
     }
 
     /// Get the hex string representation of the `AgreementPrivateKey`.
     pub fn hex(&self) -> String {
-        hex::encode(self.data())
+        hex::encode(self.as_bytes())
+    }
+
+    /// Returns `true` if this is an X448 key.
+    pub fn is_x448(&self) -> bool {
+        matches!(self.0, Curve::X448(_))
+    }
+
+    /// Compare two keys in constant time.
+    ///
+    /// Unlike `==`, this does not short-circuit on the first differing byte.
+    /// Keys on different curves are never equal.
+    pub fn ct_eq(&self, other: &AgreementPrivateKey) -> bool {
+        match (&self.0, &other.0) {
+            (Curve::X25519(a), Curve::X25519(b)) => a.ct_eq(b).into(),
+            (Curve::X448(a), Curve::X448(b)) => a.ct_eq(b).into(),
+            _ => false,
+        }
     }
 
     /// Get the `AgreementPublicKey` corresponding to this `AgreementPrivateKey`.
     pub fn public_key(&self) -> AgreementPublicKey {
-        AgreementPublicKey::from_data(
-            bc_crypto::x25519_agreement_public_key_from_private_key(self.into())
-        )
+        match &self.0 {
+            Curve::X25519(data) => AgreementPublicKey::from_data(
+                bc_crypto::x25519_agreement_public_key_from_private_key(data)
+            ),
+            Curve::X448(data) => {
+                let secret = x448::Secret::from(*data);
+                AgreementPublicKey::from_valid_x448_data(*x448::PublicKey::from(&secret).as_bytes())
+            }
+        }
     }
 
-    /// Derive an `AgreementPrivateKey` from the given key material.
+    /// Derive an X25519 `AgreementPrivateKey` from the given key material.
     pub fn derive_from_key_material(key_material: impl AsRef<[u8]>) -> Self {
         Self::from_data(bc_crypto::x25519_derive_agreement_private_key(key_material))
     }
 
     /// Derive a shared symmetric key from this `AgreementPrivateKey` and the given `AgreementPublicKey`.
-    pub fn shared_key_with(&self, public_key: &AgreementPublicKey) -> SymmetricKey {
-        SymmetricKey::from_data(bc_crypto::x25519_shared_key(self.into(), public_key.into()))
+    ///
+    /// Both parties compute the same key. The raw shared secret is passed
+    /// through HKDF-SHA256 with the ASCII salt `"agreement"`, empty info, and
+    /// a 32-byte output length.
+    ///
+    /// Returns an error if the keys are on different curves.
+    pub fn shared_key_with(&self, public_key: &AgreementPublicKey) -> Result<SymmetricKey> {
+        match (&self.0, public_key.to_x25519(), public_key.to_x448()) {
+            (Curve::X25519(private_key), Some(public_key), _) => {
+                Ok(SymmetricKey::from_data(bc_crypto::x25519_shared_key(private_key, public_key)))
+            }
+            (Curve::X448(private_key), _, Some(public_key)) => {
+                // `AgreementPublicKey` rejects low-order X448 points, the only
+                // ones for which there is no shared secret.
+                let shared_secret = x448::x448(*private_key, *public_key)
+                    .expect("X448 public key is not a low-order point");
+                let key = hkdf_hmac_sha256(shared_secret, b"agreement", SymmetricKey::SYMMETRIC_KEY_SIZE);
+                SymmetricKey::from_data_ref(key)
+            }
+            _ => bail!("Agreement keys are on different curves"),
+        }
+    }
+
+    /// Decrypt a message produced by `AgreementPublicKey::seal`, using the
+    /// ephemeral public key that accompanied it.
+    ///
+    /// Fails if this is not the private key the message was sealed to.
+    pub fn open(
+        &self,
+        message: &EncryptedMessage,
+        ephemeral_public_key: &AgreementPublicKey
+    ) -> Result<Vec<u8>> {
+        Ok(self.shared_key_with(ephemeral_public_key)?.decrypt(message)?)
+    }
+}
+
+impl KeyAgreement for AgreementPrivateKey {
+    type Public = AgreementPublicKey;
+
+    fn agree(&self, peer: &AgreementPublicKey) -> Result<SymmetricKey> {
+        self.shared_key_with(peer)
     }
 }
 
+#[cfg(feature = "std")]
 impl Default for AgreementPrivateKey {
     fn default() -> Self {
         Self::new()
     }
 }
 
-// Convert from an `AgreementPrivateKey` to a `&'a [u8; AgreementPrivateKey::KEY_SIZE]`.
+/// # Panics
+///
+/// Panics if the key is an X448 key.
 impl<'a> From<&'a AgreementPrivateKey> for &'a [u8; AgreementPrivateKey::KEY_SIZE] {
     fn from(value: &'a AgreementPrivateKey) -> Self {
-        &value.0
+        value.to_x25519().expect("Not an X25519 agreement private key")
+    }
+}
+
+impl<'a> From<&'a AgreementPrivateKey> for &'a [u8] {
+    fn from(value: &'a AgreementPrivateKey) -> Self {
+        value.as_bytes()
     }
 }
 
@@ -115,9 +254,14 @@ impl From<AgreementPrivateKey> for CBOR {
     }
 }
 
+/// An X25519 key is encoded as a bare byte string, and an X448 key as the
+/// array `[1, bytes]`.
 impl CBORTaggedEncodable for AgreementPrivateKey {
     fn untagged_cbor(&self) -> CBOR {
-        CBOR::to_byte_string(self.data())
+        match &self.0 {
+            Curve::X25519(data) => CBOR::to_byte_string(data),
+            Curve::X448(data) => vec![(1).into(), CBOR::to_byte_string(data)].into(),
+        }
     }
 }
 
@@ -130,15 +274,39 @@ impl TryFrom<CBOR> for AgreementPrivateKey {
 }
 
 impl CBORTaggedDecodable for AgreementPrivateKey {
+    fn from_tagged_cbor(cbor: CBOR) -> Result<Self> {
+        crate::component_error::decode_tagged(cbor, "agreement private key")
+    }
+
     fn from_untagged_cbor(untagged_cbor: CBOR) -> Result<Self> {
-        let data = CBOR::try_into_byte_string(untagged_cbor)?;
-        Self::from_data_ref(data)
+        match untagged_cbor.into_case() {
+            CBORCase::ByteString(data) => {
+                if let Ok(data) = data.try_into() {
+                    return Ok(Self::from_data(data));
+                }
+            }
+            CBORCase::Array(elements) if elements.len() == 2 => {
+                let mut drain = elements.into_iter();
+                let ele_0 = drain.next().unwrap().into_case();
+                let ele_1 = drain.next().unwrap().into_case();
+                if let (CBORCase::Unsigned(1), CBORCase::ByteString(data)) = (ele_0, ele_1) {
+                    if let Ok(data) = data.try_into() {
+                        return Ok(Self::from_x448_data(data));
+                    }
+                }
+            }
+            _ => (),
+        }
+        bail!(ComponentError::InvalidFormat { component: "agreement private key" });
     }
 }
 
-impl std::fmt::Debug for AgreementPrivateKey {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "AgreementPrivateKey({})", self.hex())
+impl core::fmt::Debug for AgreementPrivateKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.0 {
+            Curve::X25519(_) => write!(f, "AgreementPrivateKey({})", self.hex()),
+            Curve::X448(_) => write!(f, "AgreementPrivateKey(X448, {})", self.hex()),
+        }
     }
 }
 
@@ -152,13 +320,191 @@ impl From<&AgreementPrivateKey> for AgreementPrivateKey {
 // Convert from a byte vector to a AgreementPrivateKey.
 impl From<AgreementPrivateKey> for Vec<u8> {
     fn from(key: AgreementPrivateKey) -> Self {
-        key.0.to_vec()
+        key.as_bytes().to_vec()
     }
 }
 
 // Convert from a reference to a byte vector to a AgreementPrivateKey.
 impl From<&AgreementPrivateKey> for Vec<u8> {
     fn from(key: &AgreementPrivateKey) -> Self {
-        key.0.to_vec()
+        key.as_bytes().to_vec()
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use hex_literal::hex;
+    use hkdf::Hkdf;
+    use sha2::Sha256;
+
+    use crate::{ AgreementPrivateKey, AgreementPublicKey, KeyAgreement, SealedMessage, SymmetricKey };
+    use dcbor::prelude::*;
+
+    #[test]
+    fn test_shared_key_is_symmetric() {
+        let alice = AgreementPrivateKey::new();
+        let bob = AgreementPrivateKey::new();
+        let alice_shared = alice.shared_key_with(&bob.public_key()).unwrap();
+        let bob_shared = bob.shared_key_with(&alice.public_key()).unwrap();
+        assert_eq!(alice_shared, bob_shared);
+        assert_ne!(alice_shared, AgreementPrivateKey::new().shared_key_with(&bob.public_key()).unwrap());
+    }
+
+    fn agree_both_ways<K: KeyAgreement>(
+        alice: &K,
+        alice_public: &K::Public,
+        bob: &K,
+        bob_public: &K::Public
+    ) -> (SymmetricKey, SymmetricKey) {
+        (alice.agree(bob_public).unwrap(), bob.agree(alice_public).unwrap())
+    }
+
+    #[test]
+    fn test_key_agreement_trait() {
+        let alice = AgreementPrivateKey::new();
+        let bob = AgreementPrivateKey::new();
+        let (alice_shared, bob_shared) =
+            agree_both_ways(&alice, &alice.public_key(), &bob, &bob.public_key());
+        assert_eq!(alice_shared, bob_shared);
+        assert_eq!(alice_shared, alice.shared_key_with(&bob.public_key()).unwrap());
+    }
+
+    #[test]
+    fn test_seal_open() {
+        let plaintext = b"Some mysteries aren't meant to be solved.";
+        let recipient = AgreementPrivateKey::new();
+
+        let (message, ephemeral_public_key) = recipient.public_key().seal(plaintext).unwrap();
+        assert_eq!(recipient.open(&message, &ephemeral_public_key).unwrap(), plaintext);
+
+        // Every seal uses a fresh ephemeral key.
+        let (message_2, ephemeral_public_key_2) = recipient.public_key().seal(plaintext).unwrap();
+        assert_ne!(ephemeral_public_key, ephemeral_public_key_2);
+        assert_ne!(message.ciphertext(), message_2.ciphertext());
+
+        // Transport as a single CBOR structure.
+        let sealed = SealedMessage::from((message, ephemeral_public_key));
+        let received = SealedMessage::from_tagged_cbor_data(sealed.to_cbor_data()).unwrap();
+        assert_eq!(
+            recipient.open(received.encrypted_message(), received.ephemeral_public_key()).unwrap(),
+            plaintext
+        );
+    }
+
+    #[test]
+    fn test_open_with_wrong_key_fails() {
+        let recipient = AgreementPrivateKey::new();
+        let (message, ephemeral_public_key) = recipient.public_key().seal(b"secret").unwrap();
+        let other = AgreementPrivateKey::new();
+        assert!(other.open(&message, &ephemeral_public_key).is_err());
+        // The ephemeral public key is not interchangeable with the recipient's.
+        assert!(recipient.open(&message, &recipient.public_key()).is_err());
+    }
+
+    #[test]
+    fn test_shared_key_derivation() {
+        // RFC 7748 Section 6.1
+        let alice = AgreementPrivateKey::from_data(hex!("77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a"));
+        let bob = AgreementPrivateKey::from_data(hex!("5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb"));
+        assert_eq!(alice.public_key().data(), &hex!("8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a"));
+        assert_eq!(bob.public_key().data(), &hex!("de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f"));
+        let shared_secret = hex!("4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742");
+
+        let mut expected = [0u8; SymmetricKey::SYMMETRIC_KEY_SIZE];
+        Hkdf::<Sha256>::new(Some(b"agreement"), &shared_secret).expand(&[], &mut expected).unwrap();
+        assert_eq!(alice.shared_key_with(&bob.public_key()).unwrap().data(), &expected);
+        assert_eq!(bob.shared_key_with(&alice.public_key()).unwrap().data(), &expected);
+    }
+
+    #[test]
+    fn test_x448_shared_key_derivation() {
+        // RFC 7748 Section 6.2
+        let alice = AgreementPrivateKey::from_x448_data(hex!("9a8f4925d1519f5775cf46b04b5800d4ee9ee8bae8bc5565d498c28dd9c9baf574a9419744897391006382a6f127ab1d9ac2d8c0a598726b"));
+        let bob = AgreementPrivateKey::from_x448_data(hex!("1c306a7ac2a0e2e0990b294470cba339e6453772b075811d8fad0d1d6927c120bb5ee8972b0d3e21374c9c921b09d1b0366f10b65173992d"));
+        assert_eq!(alice.public_key().as_bytes(), &hex!("9b08f7cc31b7e3e67d22d5aea121074a273bd2b83de09c63faa73d2c22c5d9bbc836647241d953d40c5b12da88120d53177f80e532c41fa0"));
+        assert_eq!(bob.public_key().as_bytes(), &hex!("3eb7a829b0cd20f5bcfc0b599b6feccf6da4627107bdb0d4f345b43027d8b972fc3e34fb4232a13ca706dcb57aec3dae07bdc1c67bf33609"));
+        let shared_secret = hex!("07fff4181ac6cc95ec1c16a94a0f74d12da232ce40a77552281d282bb60c0b56fd2464c335543936521c24403085d59a449a5037514a879d");
+
+        let mut expected = [0u8; SymmetricKey::SYMMETRIC_KEY_SIZE];
+        Hkdf::<Sha256>::new(Some(b"agreement"), &shared_secret).expand(&[], &mut expected).unwrap();
+        assert_eq!(alice.shared_key_with(&bob.public_key()).unwrap().data(), &expected);
+        assert_eq!(bob.shared_key_with(&alice.public_key()).unwrap().data(), &expected);
+    }
+
+    #[test]
+    fn test_x448_agreement() {
+        let alice = AgreementPrivateKey::new_x448();
+        let bob = AgreementPrivateKey::new_x448();
+        assert!(alice.is_x448() && alice.public_key().is_x448());
+        let (alice_shared, bob_shared) =
+            agree_both_ways(&alice, &alice.public_key(), &bob, &bob.public_key());
+        assert_eq!(alice_shared, bob_shared);
+
+        let (message, ephemeral_public_key) = bob.public_key().seal(b"secret").unwrap();
+        assert!(ephemeral_public_key.is_x448());
+        assert_eq!(bob.open(&message, &ephemeral_public_key).unwrap(), b"secret");
+    }
+
+    #[test]
+    fn test_cross_curve_agreement_fails() {
+        let x25519 = AgreementPrivateKey::new();
+        let x448 = AgreementPrivateKey::new_x448();
+        let err = x25519.shared_key_with(&x448.public_key()).unwrap_err();
+        assert_eq!(err.to_string(), "Agreement keys are on different curves");
+        assert!(x448.agree(&x25519.public_key()).is_err());
+        assert!(!x25519.ct_eq(&x448));
+
+        // The all-zero point has low order, and can't be restored.
+        assert!(AgreementPublicKey::from_x448_data([0u8; 56]).is_err());
+        assert!(AgreementPublicKey::from_data_ref([0u8; 56]).is_err());
+        let low_order = CBOR::to_tagged_value(
+            crate::tags::AGREEMENT_PUBLIC_KEY,
+            vec![CBOR::from(1), CBOR::to_byte_string([0u8; 56])]
+        );
+        assert!(AgreementPublicKey::from_tagged_cbor(low_order).is_err());
+    }
+
+    #[test]
+    fn test_x448_cbor() {
+        let private_key = AgreementPrivateKey::new_x448();
+        let cbor = private_key.untagged_cbor();
+        assert!(matches!(cbor.as_case(), CBORCase::Array(_)));
+        let decoded = AgreementPrivateKey::from_tagged_cbor_data(private_key.to_cbor_data()).unwrap();
+        assert_eq!(decoded, private_key);
+
+        let public_key = private_key.public_key();
+        let decoded = AgreementPublicKey::from_tagged_cbor_data(public_key.to_cbor_data()).unwrap();
+        assert_eq!(decoded, public_key);
+        assert_eq!(AgreementPublicKey::from_data_ref(public_key.as_bytes()).unwrap(), public_key);
+
+        // X25519 keys remain bare byte strings.
+        let x25519 = AgreementPrivateKey::new();
+        assert!(matches!(x25519.untagged_cbor().as_case(), CBORCase::ByteString(_)));
+        assert!(AgreementPrivateKey::from_untagged_cbor(
+            vec![CBOR::from(1), CBOR::to_byte_string(x25519.data())].into()
+        ).is_err());
+    }
+
+    #[test]
+    fn test_accessors() {
+        let x25519 = AgreementPrivateKey::new();
+        let bytes: &[u8; AgreementPrivateKey::KEY_SIZE] = (&x25519).into();
+        assert_eq!(x25519.data(), bytes);
+        assert_eq!(x25519.to_x25519(), Some(bytes));
+        assert_eq!(x25519.to_x448(), None);
+        assert_eq!(x25519.as_bytes(), bytes);
+        let public_key = x25519.public_key();
+        let bytes: &[u8; AgreementPublicKey::KEY_SIZE] = (&public_key).into();
+        assert_eq!(public_key.data(), bytes);
+        assert_eq!(public_key.to_x25519(), Some(bytes));
+        assert_eq!(public_key.to_x448(), None);
+
+        let x448 = AgreementPrivateKey::new_x448();
+        assert_eq!(x448.to_x25519(), None);
+        assert_eq!(x448.as_bytes(), x448.to_x448().unwrap());
+        let public_key = x448.public_key();
+        assert_eq!(public_key.to_x25519(), None);
+        assert_eq!(public_key.as_bytes().len(), AgreementPublicKey::X448_KEY_SIZE);
+        assert_eq!(public_key.as_bytes(), public_key.to_x448().unwrap());
     }
 }
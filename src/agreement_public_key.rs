@@ -1,50 +1,148 @@
-use std::rc::Rc;
+use alloc::{ rc::Rc, string::String, vec, vec::Vec };
 use bc_ur::prelude::*;
-use crate::tags;
+use bc_rand::RandomNumberGenerator;
+use crate::{ tags, AgreementPrivateKey, ComponentError, EncryptedMessage, Nonce };
 use anyhow::{ bail, Error, Result };
 
-/// A Curve25519 public key used for X25519 key agreement.
+/// A public key used for key agreement, either X25519 or X448.
 ///
 /// <https://datatracker.ietf.org/doc/html/rfc7748>
+///
+/// X448 keys are checked when they are restored, so every
+/// `AgreementPublicKey` can be used for agreement with a private key on the
+/// same curve.
 #[derive(Clone, PartialEq, Eq, Hash)]
-pub struct AgreementPublicKey([u8; Self::KEY_SIZE]);
+pub struct AgreementPublicKey(Curve);
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum Curve {
+    X25519([u8; AgreementPublicKey::KEY_SIZE]),
+    X448([u8; AgreementPublicKey::X448_KEY_SIZE]),
+}
 
 impl AgreementPublicKey {
     pub const KEY_SIZE: usize = 32;
+    pub const X448_KEY_SIZE: usize = 56;
 
-    /// Restore an `AgreementPublicKey` from a fixed-size array of bytes.
+    /// Restore an X25519 `AgreementPublicKey` from a fixed-size array of bytes.
     pub const fn from_data(data: [u8; Self::KEY_SIZE]) -> Self {
-        Self(data)
+        Self(Curve::X25519(data))
+    }
+
+    /// Restore an X448 `AgreementPublicKey` from a fixed-size array of bytes.
+    ///
+    /// Returns an error if the key is a low-order point, with which agreement
+    /// is not possible.
+    pub fn from_x448_data(data: [u8; Self::X448_KEY_SIZE]) -> Result<Self> {
+        if x448::PublicKey::from_bytes(&data).is_none() {
+            bail!(ComponentError::MalformedBytes { component: "X448 public key" });
+        }
+        Ok(Self(Curve::X448(data)))
+    }
+
+    /// Restore an X448 `AgreementPublicKey` already known not to be a
+    /// low-order point, such as one computed from a private key.
+    pub(crate) const fn from_valid_x448_data(data: [u8; Self::X448_KEY_SIZE]) -> Self {
+        Self(Curve::X448(data))
     }
 
-    /// Restore an `AgreementPublicKey` from a reference to an array of bytes.
+    /// Restore an `AgreementPublicKey` from a reference to an array of bytes,
+    /// which is an X25519 key if it is `KEY_SIZE` bytes long and an X448 key
+    /// if it is `X448_KEY_SIZE` bytes long.
     pub fn from_data_ref(data: impl AsRef<[u8]>) -> Result<Self> {
         let data = data.as_ref();
-        if data.len() != Self::KEY_SIZE {
-            bail!("Invalid agreement public key size");
+        if let Ok(data) = data.try_into() {
+            Ok(Self::from_data(data))
+        } else if let Ok(data) = data.try_into() {
+            Self::from_x448_data(data)
+        } else {
+            bail!(ComponentError::InvalidLength { component: "agreement public key" });
         }
-        let mut arr = [0u8; Self::KEY_SIZE];
-        arr.copy_from_slice(data);
-        Ok(Self::from_data(arr))
     }
 
-    /// Get a reference to the fixed-size array of bytes.
+    /// Get a reference to the fixed-size array of bytes of an X25519 key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this is an X448 key. Use `to_x25519` to check the curve
+    /// first, or `as_bytes` for the bytes of a key on either curve.
     pub fn data(&self) -> &[u8; Self::KEY_SIZE] {
         self.into()
     }
 
+    /// Get a reference to the fixed-size array of bytes if this is an X25519
+    /// key.
+    pub fn to_x25519(&self) -> Option<&[u8; Self::KEY_SIZE]> {
+        match &self.0 {
+            Curve::X25519(data) => Some(data),
+            Curve::X448(_) => None,
+        }
+    }
+
+    /// Get a reference to the fixed-size array of bytes if this is an X448
+    /// key.
+    pub fn to_x448(&self) -> Option<&[u8; Self::X448_KEY_SIZE]> {
+        match &self.0 {
+            Curve::X25519(_) => None,
+            Curve::X448(data) => Some(data),
+        }
+    }
+
+    /// Get a reference to the key bytes, `KEY_SIZE` bytes long for an X25519
+    /// key and `X448_KEY_SIZE` bytes long for an X448 key.
+    pub fn as_bytes(&self) -> &[u8] {
+        match &self.0 {
+            Curve::X25519(data) => data,
+            Curve::X448(data) => data,
+        }
+    }
+
     /// Restore an `AgreementPublicKey` from a hex string.
     ///
     /// # Panics
     ///
-    /// Panics if the hex string is invalid or the length is not `AgreementPublicKey::KEY_SIZE * 2`.
+    /// Panics if the hex string is invalid or does not encode a valid key.
     pub fn from_hex(hex: impl AsRef<str>) -> Self {
         Self::from_data_ref(hex::decode(hex.as_ref()).unwrap()).unwrap()
     }
 
     /// Get the hex string representation of the `AgreementPublicKey`.
     pub fn hex(&self) -> String {
-        hex::encode(self.data())
+        hex::encode(self.as_bytes())
+    }
+
+    /// Returns `true` if this is an X448 key.
+    pub fn is_x448(&self) -> bool {
+        matches!(self.0, Curve::X448(_))
+    }
+
+    /// Encrypt the plaintext so that only the holder of the corresponding
+    /// `AgreementPrivateKey` can decrypt it.
+    ///
+    /// A fresh ephemeral key pair on the same curve as this key is generated
+    /// for every call. The returned ephemeral public key must be sent along
+    /// with the message, and is needed by `AgreementPrivateKey::open`. The
+    /// pair can be carried as a single CBOR structure by converting it into a
+    /// `SealedMessage`.
+    #[cfg(feature = "std")]
+    pub fn seal(&self, plaintext: impl Into<Vec<u8>>) -> Result<(EncryptedMessage, AgreementPublicKey)> {
+        self.seal_using(plaintext, &mut bc_rand::SecureRandomNumberGenerator)
+    }
+
+    /// Encrypt the plaintext like `seal`, drawing the ephemeral key and the
+    /// nonce from the given random number generator.
+    pub fn seal_using(
+        &self,
+        plaintext: impl Into<Vec<u8>>,
+        rng: &mut impl RandomNumberGenerator
+    ) -> Result<(EncryptedMessage, AgreementPublicKey)> {
+        let ephemeral_private_key = match self.0 {
+            Curve::X25519(_) => AgreementPrivateKey::new_using(rng),
+            Curve::X448(_) => AgreementPrivateKey::new_x448_using(rng),
+        };
+        let shared_key = ephemeral_private_key.shared_key_with(self)?;
+        let message = shared_key.encrypt_using(plaintext, None::<Vec<u8>>, None::<Nonce>, rng);
+        Ok((message, ephemeral_private_key.public_key()))
     }
 }
 
@@ -54,9 +152,18 @@ impl From<Rc<AgreementPublicKey>> for AgreementPublicKey {
     }
 }
 
+/// # Panics
+///
+/// Panics if the key is an X448 key.
 impl<'a> From<&'a AgreementPublicKey> for &'a [u8; AgreementPublicKey::KEY_SIZE] {
     fn from(value: &'a AgreementPublicKey) -> Self {
-        &value.0
+        value.to_x25519().expect("Not an X25519 agreement public key")
+    }
+}
+
+impl<'a> From<&'a AgreementPublicKey> for &'a [u8] {
+    fn from(value: &'a AgreementPublicKey) -> Self {
+        value.as_bytes()
     }
 }
 
@@ -78,9 +185,14 @@ impl From<AgreementPublicKey> for CBOR {
     }
 }
 
+/// An X25519 key is encoded as a bare byte string, and an X448 key as the
+/// array `[1, bytes]`.
 impl CBORTaggedEncodable for AgreementPublicKey {
     fn untagged_cbor(&self) -> CBOR {
-        CBOR::to_byte_string(self.data())
+        match &self.0 {
+            Curve::X25519(data) => CBOR::to_byte_string(data),
+            Curve::X448(data) => vec![(1).into(), CBOR::to_byte_string(data)].into(),
+        }
     }
 }
 
@@ -93,15 +205,39 @@ impl TryFrom<CBOR> for AgreementPublicKey {
 }
 
 impl CBORTaggedDecodable for AgreementPublicKey {
+    fn from_tagged_cbor(cbor: CBOR) -> Result<Self> {
+        crate::component_error::decode_tagged(cbor, "agreement public key")
+    }
+
     fn from_untagged_cbor(untagged_cbor: CBOR) -> Result<Self> {
-        let data = CBOR::try_into_byte_string(untagged_cbor)?;
-        Self::from_data_ref(data)
+        match untagged_cbor.into_case() {
+            CBORCase::ByteString(data) => {
+                if let Ok(data) = data.try_into() {
+                    return Ok(Self::from_data(data));
+                }
+            }
+            CBORCase::Array(elements) if elements.len() == 2 => {
+                let mut drain = elements.into_iter();
+                let ele_0 = drain.next().unwrap().into_case();
+                let ele_1 = drain.next().unwrap().into_case();
+                if let (CBORCase::Unsigned(1), CBORCase::ByteString(data)) = (ele_0, ele_1) {
+                    if let Ok(data) = data.try_into() {
+                        return Self::from_x448_data(data);
+                    }
+                }
+            }
+            _ => (),
+        }
+        bail!(ComponentError::InvalidFormat { component: "agreement public key" });
     }
 }
 
-impl std::fmt::Debug for AgreementPublicKey {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "AgreementPublicKey({})", self.hex())
+impl core::fmt::Debug for AgreementPublicKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.0 {
+            Curve::X25519(_) => write!(f, "AgreementPublicKey({})", self.hex()),
+            Curve::X448(_) => write!(f, "AgreementPublicKey(X448, {})", self.hex()),
+        }
     }
 }
 
@@ -115,13 +251,13 @@ impl From<&AgreementPublicKey> for AgreementPublicKey {
 // Convert from a byte vector to a AgreementPublicKey.
 impl From<AgreementPublicKey> for Vec<u8> {
     fn from(key: AgreementPublicKey) -> Self {
-        key.0.to_vec()
+        key.as_bytes().to_vec()
     }
 }
 
 // Convert from a reference to a byte vector to a AgreementPublicKey.
 impl From<&AgreementPublicKey> for Vec<u8> {
     fn from(key: &AgreementPublicKey) -> Self {
-        key.0.to_vec()
+        key.as_bytes().to_vec()
     }
 }
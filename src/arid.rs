@@ -1,8 +1,11 @@
-use bc_rand::random_data;
-use bc_ur::prelude::*;
+use alloc::{ string::String, vec, vec::Vec };
+use core::str::FromStr;
+use bc_crypto::hash::sha256;
+use bc_rand::RandomNumberGenerator;
+use bc_ur::{ bytewords, prelude::* };
 
-use crate::tags;
-use anyhow::{ bail, Error, Result };
+use crate::{ bech32_encoding, tags, ComponentError };
+use anyhow::{ anyhow, bail, Error, Result };
 
 /// An "Apparently Random Identifier" (ARID)
 ///
@@ -14,9 +17,17 @@ impl ARID {
     pub const ARID_SIZE: usize = 32;
 
     /// Create a new random ARID.
+    #[cfg(feature = "std")]
     pub fn new() -> Self {
-        let data = random_data(Self::ARID_SIZE);
-        Self::from_data_ref(data).unwrap()
+        let mut rng = bc_rand::SecureRandomNumberGenerator;
+        Self::new_using(&mut rng)
+    }
+
+    /// Create a new random ARID using the given random number generator.
+    pub fn new_using(rng: &mut impl RandomNumberGenerator) -> Self {
+        let mut data = [0u8; Self::ARID_SIZE];
+        rng.fill_random_data(&mut data);
+        Self::from_data(data)
     }
 
     /// Restore a ARID from a fixed-size array of bytes.
@@ -28,7 +39,7 @@ impl ARID {
     pub fn from_data_ref(data: impl AsRef<[u8]>) -> Result<Self> {
         let data = data.as_ref();
         if data.len() != Self::ARID_SIZE {
-            bail!("Invalid ARID size");
+            bail!(ComponentError::InvalidLength { component: "ARID" });
         }
         let mut arr = [0u8; Self::ARID_SIZE];
         arr.copy_from_slice(data);
@@ -57,8 +68,49 @@ impl ARID {
     pub fn short_description(&self) -> String {
         hex::encode(&self.0[0..4])
     }
+
+    /// The data as bytewords in the given style, followed by a CRC-32 checksum.
+    pub fn bytewords(&self, style: bytewords::Style) -> String {
+        bytewords::encode(self.0, style)
+    }
+
+    /// Create a new ARID from bytewords in the given style.
+    ///
+    /// Returns an error if the checksum does not match or the data is not the
+    /// correct length.
+    pub fn from_bytewords(bytewords: &str, style: bytewords::Style) -> Result<Self> {
+        Self::from_data_ref(bytewords::decode(bytewords, style)?)
+    }
+
+    /// The data as a bech32m string with the given human-readable part, such
+    /// as `arid`.
+    ///
+    /// Returns an error if the human-readable part is invalid.
+    pub fn bech32(&self, hrp: &str) -> Result<String> {
+        bech32_encoding::encode(hrp, &self.0)
+    }
+
+    /// Create a new ARID from a bech32m string with the given human-readable
+    /// part.
+    ///
+    /// Returns an error if the checksum does not match, the human-readable
+    /// part differs, or the data is not the correct length.
+    pub fn from_bech32(bech32: &str, hrp: &str) -> Result<Self> {
+        Self::from_data_ref(bech32_encoding::decode(bech32, hrp)?)
+    }
+
+    /// Derive a child ARID from this one and a label.
+    ///
+    /// The child is `SHA-256(parent || label)`, where `parent` is the 32
+    /// bytes of this ARID and `label` is the UTF-8 bytes of the label. The
+    /// same parent and label always give the same child, and the parent
+    /// can't be recovered from the child.
+    pub fn derive(&self, label: &str) -> Self {
+        Self::from_data(sha256([self.0.as_slice(), label.as_bytes()].concat()))
+    }
 }
 
+#[cfg(feature = "std")]
 impl Default for ARID {
     fn default() -> Self {
         Self::new()
@@ -71,6 +123,26 @@ impl<'a> From<&'a ARID> for &'a [u8] {
     }
 }
 
+impl AsRef<[u8]> for ARID {
+    fn as_ref(&self) -> &[u8] {
+        self.data()
+    }
+}
+
+impl TryFrom<&[u8]> for ARID {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_data_ref(data)
+    }
+}
+
+impl From<[u8; Self::ARID_SIZE]> for ARID {
+    fn from(data: [u8; Self::ARID_SIZE]) -> Self {
+        Self::from_data(data)
+    }
+}
+
 impl AsRef<ARID> for ARID {
     fn as_ref(&self) -> &Self {
         self
@@ -104,26 +176,174 @@ impl TryFrom<CBOR> for ARID {
 }
 
 impl CBORTaggedDecodable for ARID {
+    fn from_tagged_cbor(cbor: CBOR) -> Result<Self> {
+        crate::component_error::decode_tagged(cbor, "ARID")
+    }
+
     fn from_untagged_cbor(untagged_cbor: CBOR) -> Result<Self> {
         let data = CBOR::try_into_byte_string(untagged_cbor)?;
         Self::from_data_ref(data)
     }
 }
 
-impl std::fmt::Debug for ARID {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Debug for ARID {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "ARID({})", self.hex())
     }
 }
 
-impl std::fmt::Display for ARID {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for ARID {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "ARID({})", self.hex())
     }
 }
 
+/// Parses either a bare 64-digit hexadecimal string or the `ARID(...)` form
+/// produced by `Display`.
+impl FromStr for ARID {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let hex = s
+            .strip_prefix("ARID(")
+            .and_then(|s| s.strip_suffix(')'))
+            .unwrap_or(s);
+        let data = hex::decode(hex).map_err(|e| anyhow!("Invalid ARID hex: {}", e))?;
+        Self::from_data_ref(data)
+    }
+}
+
 impl PartialOrd for ARID {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.0.cmp(&other.0))
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ARID {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{ collections::BTreeSet, str::FromStr };
+    use bc_ur::{ bytewords::Style, prelude::* };
+
+    use super::ARID;
+    use crate::Digest;
+
+    const HEX: &str = "dec7e82893c32f7a4fcec633c02c0ec32a4361ca3ee3bc8758ae07742e940550";
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_arid_from_str() {
+        let arid = ARID::from_str(HEX).unwrap();
+        assert_eq!(arid.hex(), HEX);
+        assert_eq!(ARID::from_str(&format!("ARID({})", HEX)).unwrap(), arid);
+        assert_eq!(ARID::from_str(&arid.to_string()).unwrap(), arid);
+
+        let random = ARID::new();
+        assert_eq!(random.to_string().parse::<ARID>().unwrap(), random);
+    }
+
+    #[test]
+    fn test_arid_from_str_wrong_length() {
+        let err = ARID::from_str(&HEX[..62]).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid ARID size");
+    }
+
+    #[test]
+    fn test_arid_from_str_invalid_hex() {
+        let bad = format!("{}zz", &HEX[..62]);
+        let err = ARID::from_str(&bad).unwrap_err();
+        assert!(err.to_string().starts_with("Invalid ARID hex"));
+        assert!(ARID::from_str("ARID(").is_err());
+    }
+
+    #[test]
+    fn test_arid_ordering() {
+        let a = ARID::from_hex("00f0000000000000000000000000000000000000000000000000000000000000");
+        let b = ARID::from_hex("0100000000000000000000000000000000000000000000000000000000000000");
+        let c = ARID::from_hex("01000000000000000000000000000000000000000000000000000000000000ff");
+        let d = ARID::from_hex("ff00000000000000000000000000000000000000000000000000000000000000");
+
+        let mut arids = vec![d.clone(), b.clone(), c.clone(), a.clone()];
+        arids.sort();
+        assert_eq!(arids, vec![a.clone(), b.clone(), c.clone(), d.clone()]);
+        assert!(arids.windows(2).all(|w| w[0].data() < w[1].data()));
+
+        let set: BTreeSet<ARID> = [c.clone(), a.clone(), c.clone(), d.clone(), b.clone(), a.clone()]
+            .into_iter()
+            .collect();
+        assert_eq!(set.into_iter().collect::<Vec<_>>(), vec![a, b, c, d]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_arid_default() {
+        let a = ARID::default();
+        let b = ARID::default();
+        assert_eq!(a.data().len(), ARID::ARID_SIZE);
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_arid_ur() {
+        let arid = ARID::from_hex(HEX);
+        let ur_string = arid.ur_string();
+        assert!(ur_string.starts_with("ur:arid/"));
+        assert_eq!(ARID::from_ur_string(&ur_string).unwrap(), arid);
+    }
+
+    #[test]
+    fn test_ur_type_mismatch() {
+        // Both types carry 32 bytes, so only the UR type distinguishes them.
+        let arid_ur = ARID::from_hex(HEX).ur_string();
+        assert!(Digest::from_ur_string(&arid_ur).is_err());
+        let digest_ur = Digest::from_image(b"Hello").ur_string();
+        assert!(ARID::from_ur_string(digest_ur).is_err());
+    }
+
+    #[test]
+    fn test_arid_bytewords() {
+        let arid = ARID::from_hex(HEX);
+        let standard = arid.bytewords(Style::Standard);
+        assert_eq!(
+            standard,
+            "urge slot vows dice menu scar dull kiln glow taco skew echo rust draw beta scar door flux huts song film vial roof list hard pool aunt jury drum meow arch good mild tomb need soap"
+        );
+        assert_eq!(ARID::from_bytewords(&standard, Style::Standard).unwrap(), arid);
+
+        let minimal = arid.bytewords(Style::Minimal);
+        assert_eq!(minimal, "uestvsdemusrdlkngwtosweortdwbasrdrfxhssgfmvlrflthdplatjydmmwahgdmdtbndsp");
+        assert_eq!(ARID::from_bytewords(&minimal, Style::Minimal).unwrap(), arid);
+
+        // Changing one word is caught by the checksum.
+        let corrupted = standard.replacen("slot", "slug", 1);
+        assert!(ARID::from_bytewords(&corrupted, Style::Standard).is_err());
+    }
+
+    #[test]
+    fn test_derive() {
+        let parent = ARID::from_str(HEX).unwrap();
+        let child = parent.derive("invoices");
+        assert_eq!(child.hex(), "6619dd6a02b5034556cb6e48112ca6159c7641b61a706ad7dd487fc8ddad6a95");
+        assert_eq!(child.data(), bc_crypto::hash::sha256([parent.data(), b"invoices"].concat()));
+        assert_ne!(child, parent.derive("receipts"));
+        assert_ne!(child, parent);
+        assert_ne!(child.derive("invoices"), child);
+    }
+
+    #[test]
+    fn test_bech32() {
+        let arid = ARID::from_str(HEX).unwrap();
+        let encoded = arid.bech32("arid").unwrap();
+        assert_eq!(encoded, "arid1mmr7s2yncvhh5n7wcceuqtqwcv4yxcw28m3mep6c4crhgt55q4gq5r7rhy");
+        assert_eq!(ARID::from_bech32(&encoded, "arid").unwrap(), arid);
+        assert!(ARID::from_bech32(&encoded.replacen("mmr7", "mmr8", 1), "arid").is_err());
+        let digest = Digest::from_image(b"hello world").bech32("arid").unwrap();
+        assert_eq!(ARID::from_bech32(&digest, "arid").unwrap().data(), Digest::from_image(b"hello world").data());
     }
 }
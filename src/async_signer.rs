@@ -0,0 +1,79 @@
+use core::future::Future;
+
+use anyhow::Result;
+
+use crate::{ Signature, Signer, SigningPrivateKey, SigningPublicKey };
+
+/// A signer whose key is held elsewhere, such as in a remote KMS or an HSM,
+/// so that fetching the public key and signing are both asynchronous.
+///
+/// The signatures produced are ordinary `Signature`s, verifiable with the
+/// `SigningPublicKey` returned by `public_key`. `SigningPrivateKey`
+/// implements this trait by signing locally, so code written against
+/// `AsyncSigner` works with local and remote keys alike. Where `Signer` is
+/// also in scope, call the methods as `AsyncSigner::sign(&key, message)` to
+/// avoid ambiguity.
+///
+/// Requires the `async` feature.
+pub trait AsyncSigner {
+    /// Fetches the public key that verifies this signer's signatures.
+    fn public_key(&self) -> impl Future<Output = Result<SigningPublicKey>> + Send;
+
+    /// Signs `message`.
+    fn sign(&self, message: &[u8]) -> impl Future<Output = Result<Signature>> + Send;
+}
+
+impl AsyncSigner for SigningPrivateKey {
+    async fn public_key(&self) -> Result<SigningPublicKey> {
+        Ok(SigningPrivateKey::public_key(self))
+    }
+
+    async fn sign(&self, message: &[u8]) -> Result<Signature> {
+        Signer::sign(self, &message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::AsyncSigner;
+    use crate::{ ECPrivateKey, Signature, SigningPrivateKey, SigningPublicKey, Verifier };
+
+    /// Stands in for a remote KMS, yielding to the runtime before each
+    /// operation as a network round trip would.
+    struct MockKms {
+        key: SigningPrivateKey,
+    }
+
+    impl AsyncSigner for MockKms {
+        async fn public_key(&self) -> Result<SigningPublicKey> {
+            tokio::task::yield_now().await;
+            AsyncSigner::public_key(&self.key).await
+        }
+
+        async fn sign(&self, message: &[u8]) -> Result<Signature> {
+            tokio::task::yield_now().await;
+            AsyncSigner::sign(&self.key, message).await
+        }
+    }
+
+    async fn sign_and_verify(signer: &impl AsyncSigner, message: &[u8]) -> bool {
+        let public_key = signer.public_key().await.unwrap();
+        let signature = signer.sign(message).await.unwrap();
+        public_key.verify(&signature, &message)
+    }
+
+    #[tokio::test]
+    async fn test_async_signer() {
+        let message = b"Wolf McNally";
+        let key = SigningPrivateKey::new_schnorr(ECPrivateKey::new());
+        assert!(sign_and_verify(&key, message).await);
+
+        let kms = MockKms { key: SigningPrivateKey::new_ecdsa(ECPrivateKey::new()) };
+        assert!(sign_and_verify(&kms, message).await);
+
+        let signature = kms.sign(message).await.unwrap();
+        assert!(!key.public_key().verify(&signature, &message));
+    }
+}
@@ -1,8 +1,10 @@
-use std::rc::Rc;
+use alloc::{ rc::Rc, vec::Vec };
 
 use anyhow::{ bail, Error, Result };
 use dcbor::prelude::*;
 
+use crate::ComponentError;
+
 /// The HMAC authentication tag produced by the encryption process.
 #[derive(Clone, Eq, PartialEq)]
 pub struct AuthenticationTag([u8; Self::AUTHENTICATION_TAG_SIZE]);
@@ -19,7 +21,7 @@ impl AuthenticationTag {
     pub fn from_data_ref(data: impl AsRef<[u8]>) -> Result<Self> {
         let data = data.as_ref();
         if data.len() != Self::AUTHENTICATION_TAG_SIZE {
-            bail!("Invalid authentication tag size");
+            bail!(ComponentError::InvalidLength { component: "authentication tag" });
         }
         let mut arr = [0u8; Self::AUTHENTICATION_TAG_SIZE];
         arr.copy_from_slice(data.as_ref());
@@ -38,8 +40,8 @@ impl AsRef<AuthenticationTag> for AuthenticationTag {
     }
 }
 
-impl std::fmt::Debug for AuthenticationTag {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Debug for AuthenticationTag {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_tuple("AuthenticationTag").field(&hex::encode(self.data())).finish()
     }
 }
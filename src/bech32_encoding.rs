@@ -0,0 +1,25 @@
+use alloc::{ string::String, vec::Vec };
+use anyhow::{ bail, Result };
+use bech32::{ primitives::decode::CheckedHrpstring, Bech32m, Hrp };
+
+/// Encodes `data` as a bech32m (BIP350) string with the given human-readable
+/// part.
+///
+/// Returns an error if the human-readable part is invalid or the encoding
+/// would be longer than bech32m allows.
+pub(crate) fn encode(hrp: &str, data: &[u8]) -> Result<String> {
+    Ok(bech32::encode::<Bech32m>(Hrp::parse(hrp)?, data)?)
+}
+
+/// Decodes a bech32m (BIP350) string, which must have the given
+/// human-readable part, returning its data.
+///
+/// Returns an error if the checksum doesn't match or the human-readable part
+/// is not `hrp`, which is compared case-insensitively.
+pub(crate) fn decode(bech32: &str, hrp: &str) -> Result<Vec<u8>> {
+    let checked = CheckedHrpstring::new::<Bech32m>(bech32)?;
+    if checked.hrp().to_lowercase() != Hrp::parse(hrp)?.to_lowercase() {
+        bail!("Unexpected bech32 human-readable part: {}", checked.hrp());
+    }
+    Ok(checked.byte_iter().collect())
+}
@@ -0,0 +1,155 @@
+use anyhow::{ bail, Result };
+use dcbor::{ CBORCase, CBORTaggedDecodable, CBOR };
+
+/// The ways decoding or using a component can fail.
+///
+/// Decoders still return `anyhow::Error`, so existing callers are unaffected,
+/// but the errors they raise for these cases are `ComponentError`s, which
+/// can be recovered with `downcast_ref` to tell the cases apart. The last two
+/// variants come from encrypting with a caller-supplied nonce.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ComponentError {
+    /// The data carried a CBOR tag not valid for the component.
+    WrongTag { component: &'static str, tag: u64 },
+    /// The data had the wrong number of bytes for the component.
+    InvalidLength { component: &'static str },
+    /// The CBOR structure was not one the component is encoded as.
+    InvalidFormat { component: &'static str },
+    /// The structure was right, but the bytes inside didn't parse.
+    MalformedBytes { component: &'static str },
+    /// The data named an algorithm this crate doesn't know.
+    UnknownAlgorithm { component: &'static str, value: u64 },
+    /// The algorithm is known, but this build was compiled without the
+    /// feature it needs.
+    UnsupportedAlgorithm { component: &'static str, value: u64 },
+    /// An all-zero nonce was passed where it's refused.
+    ZeroNonce,
+    /// A `NonceTracker` has already seen the nonce.
+    NonceReuse,
+}
+
+impl core::fmt::Display for ComponentError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::WrongTag { component, tag } => write!(f, "Unexpected tag {} in {}", tag, component),
+            Self::InvalidLength { component } => write!(f, "Invalid {} size", component),
+            Self::InvalidFormat { component } => write!(f, "Invalid {} format", component),
+            Self::MalformedBytes { component } => write!(f, "Malformed {}", component),
+            Self::UnknownAlgorithm { component, value } => {
+                write!(f, "Unknown {} algorithm: {}", component, value)
+            }
+            Self::UnsupportedAlgorithm { component, value } => {
+                write!(f, "Unsupported {} algorithm: {}", component, value)
+            }
+            Self::ZeroNonce => write!(f, "All-zero nonce rejected"),
+            Self::NonceReuse => write!(f, "Nonce reuse detected"),
+        }
+    }
+}
+
+impl core::error::Error for ComponentError {}
+
+/// Decodes `T` from tagged CBOR like the default
+/// `CBORTaggedDecodable::from_tagged_cbor`, but reports a tag not valid for
+/// `T` as `ComponentError::WrongTag` and untagged CBOR as
+/// `ComponentError::InvalidFormat`.
+///
+/// Every component overrides `from_tagged_cbor` with this, so the error is the
+/// same through `TryFrom<CBOR>`, `from_tagged_cbor_data`, and URs.
+pub(crate) fn decode_tagged<T: CBORTaggedDecodable>(cbor: CBOR, component: &'static str) -> Result<T> {
+    match cbor.into_case() {
+        CBORCase::Tagged(tag, item) if T::cbor_tags().contains(&tag) => T::from_untagged_cbor(item),
+        CBORCase::Tagged(tag, _) => bail!(ComponentError::WrongTag { component, tag: tag.value() }),
+        _ => bail!(ComponentError::InvalidFormat { component }),
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use dcbor::prelude::*;
+
+    use super::ComponentError;
+    use crate::{
+        tags, AgreementPublicKey, Digest, EncryptedMessage, Nonce, Signature, SigningPrivateKey,
+        SigningPublicKey, ARID, UUID,
+    };
+
+    fn decode_error<T: CBORTaggedDecodable>(cbor: CBOR) -> ComponentError {
+        let err = T::from_tagged_cbor(cbor).err().unwrap();
+        err.downcast_ref::<ComponentError>().unwrap().clone()
+    }
+
+    #[test]
+    fn test_failure_modes() {
+        let short = CBOR::to_tagged_value(tags::SIGNATURE, CBOR::to_byte_string([0u8; 63]));
+        assert_eq!(
+            decode_error::<Signature>(short),
+            ComponentError::InvalidLength { component: "Schnorr signature" }
+        );
+
+        let not_bytes = CBOR::to_tagged_value(tags::SIGNATURE, CBOR::from("signature"));
+        assert_eq!(
+            decode_error::<Signature>(not_bytes),
+            ComponentError::InvalidFormat { component: "signature" }
+        );
+
+        let unknown = CBOR::to_tagged_value(
+            tags::DIGEST,
+            CBOR::from(vec![CBOR::from(99), CBOR::to_byte_string([0u8; 32])])
+        );
+        let err = decode_error::<Digest>(unknown);
+        assert_eq!(err, ComponentError::UnknownAlgorithm { component: "digest", value: 99 });
+        assert_eq!(err.to_string(), "Unknown digest algorithm: 99");
+
+        // A component under another component's tag, through every entry point.
+        let nonce = Nonce::new();
+        let expected = ComponentError::WrongTag { component: "ARID", tag: tags::NONCE.value() };
+        assert_eq!(decode_error::<ARID>(nonce.tagged_cbor()), expected);
+        let err = ARID::try_from(nonce.tagged_cbor()).unwrap_err();
+        assert_eq!(err.downcast_ref::<ComponentError>(), Some(&expected));
+        let err = ARID::from_tagged_cbor_data(nonce.tagged_cbor_data()).unwrap_err();
+        assert_eq!(err.downcast_ref::<ComponentError>(), Some(&expected));
+        let err = EncryptedMessage::from_tagged_cbor_data(Digest::from_image(b"").tagged_cbor_data()).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ComponentError>(),
+            Some(&ComponentError::WrongTag { component: "EncryptedMessage", tag: tags::DIGEST.value() })
+        );
+        assert_eq!(
+            decode_error::<UUID>(CBOR::to_byte_string([0u8; 16])),
+            ComponentError::InvalidFormat { component: "UUID" }
+        );
+
+        // Bytes of the right size that aren't a valid key.
+        let low_order = CBOR::to_tagged_value(
+            tags::AGREEMENT_PUBLIC_KEY,
+            CBOR::from(vec![CBOR::from(1), CBOR::to_byte_string([0u8; 56])])
+        );
+        assert_eq!(
+            decode_error::<AgreementPublicKey>(low_order),
+            ComponentError::MalformedBytes { component: "X448 public key" }
+        );
+
+        let unknown = CBOR::to_tagged_value(
+            tags::SIGNING_PUBLIC_KEY,
+            CBOR::from(vec![CBOR::from(7), CBOR::to_byte_string([0u8; 32])])
+        );
+        assert_eq!(
+            decode_error::<SigningPublicKey>(unknown),
+            ComponentError::UnknownAlgorithm { component: "signing public key", value: 7 }
+        );
+        let empty = CBOR::to_tagged_value(tags::SIGNING_PRIVATE_KEY, CBOR::from(Vec::<CBOR>::new()));
+        assert_eq!(
+            decode_error::<SigningPrivateKey>(empty),
+            ComponentError::InvalidFormat { component: "signing private key" }
+        );
+        let short = CBOR::to_tagged_value(tags::UUID, CBOR::to_byte_string([0u8; 15]));
+        assert_eq!(decode_error::<UUID>(short), ComponentError::InvalidLength { component: "UUID" });
+
+        // The errors remain ordinary anyhow errors for existing callers.
+        let err = Signature::from_tagged_cbor(
+            CBOR::to_tagged_value(tags::SIGNATURE, CBOR::to_byte_string([0u8; 1]))
+        ).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid Schnorr signature size");
+    }
+}
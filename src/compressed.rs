@@ -1,16 +1,156 @@
-use std::{ fmt::Formatter, borrow::Cow };
+use alloc::{ borrow::Cow, string::ToString, vec, vec::Vec };
+use core::fmt::Formatter;
+#[cfg(feature = "std")]
+use std::io::{ Read, Write };
 use bc_ur::prelude::*;
 use bc_crypto::hash::crc32;
+#[cfg(feature = "std")]
+use flate2::{ read::{ DeflateDecoder, GzDecoder }, write::GzEncoder, Compression };
 use miniz_oxide::{ inflate::decompress_to_vec, deflate::compress_to_vec };
-use crate::{ digest::Digest, DigestProvider, tags };
+use crate::{ digest::Digest, ComponentError, DigestProvider, tags };
 use anyhow::{ anyhow, bail, Error, Result };
 
+/// The algorithm used to compress the data in a `Compressed` object.
+///
+/// Every algorithm can be named and decoded from CBOR in any build, but only
+/// DEFLATE is available without the `std` feature. Compressing or
+/// uncompressing with another algorithm then fails with
+/// `ComponentError::UnsupportedAlgorithm`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum CompressionAlgorithm {
+    /// The raw DEFLATE format as described in
+    /// [IETF RFC 1951](https://www.ietf.org/rfc/rfc1951.txt).
+    #[default]
+    Deflate,
+    /// Zstandard as described in
+    /// [IETF RFC 8878](https://www.ietf.org/rfc/rfc8878.txt).
+    ///
+    /// Requires the `std` feature, as it wraps the C library.
+    Zstd,
+    /// The gzip file format as described in
+    /// [IETF RFC 1952](https://www.ietf.org/rfc/rfc1952.txt).
+    ///
+    /// Requires the `std` feature.
+    Gzip,
+}
+
+impl CompressionAlgorithm {
+    /// The identifier written to CBOR, or `None` for the default algorithm,
+    /// which is implied when the identifier is absent.
+    fn discriminator(&self) -> Option<u64> {
+        match self {
+            Self::Deflate => None,
+            Self::Zstd => Some(1),
+            Self::Gzip => Some(2),
+        }
+    }
+
+    fn from_discriminator(discriminator: u64) -> Result<Self> {
+        match discriminator {
+            1 => Ok(Self::Zstd),
+            2 => Ok(Self::Gzip),
+            _ => bail!(ComponentError::UnknownAlgorithm { component: "compression", value: discriminator }),
+        }
+    }
+
+    /// Returns `true` if this build can compress and uncompress with this
+    /// algorithm.
+    pub fn is_supported(&self) -> bool {
+        matches!(self, Self::Deflate) || cfg!(feature = "std")
+    }
+
+    fn check_supported(&self) -> Result<()> {
+        if !self.is_supported() {
+            bail!(ComponentError::UnsupportedAlgorithm {
+                component: "compression",
+                value: self.discriminator().unwrap_or_default(),
+            });
+        }
+        Ok(())
+    }
+
+    /// The compression level used when none is given.
+    fn default_level(&self) -> u8 {
+        match self {
+            Self::Deflate | Self::Gzip => 6,
+            Self::Zstd => 3,
+        }
+    }
+
+    /// Clamps `level` to the range the backend accepts: 0 to 9 for DEFLATE
+    /// and gzip, and 1 to 22 for Zstandard.
+    fn clamp_level(&self, level: u8) -> u8 {
+        match self {
+            Self::Deflate | Self::Gzip => level.min(9),
+            Self::Zstd => level.clamp(1, 22),
+        }
+    }
+
+    /// Compresses the data at the given level, returning an empty vector if
+    /// it can't be compressed or the algorithm isn't supported.
+    fn compress(&self, data: &[u8], level: u8) -> Vec<u8> {
+        let level = self.clamp_level(level);
+        match self {
+            Self::Deflate => compress_to_vec(data, level),
+            #[cfg(feature = "std")]
+            Self::Zstd => zstd::bulk::compress(data, level.into()).unwrap_or_default(),
+            #[cfg(feature = "std")]
+            Self::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::new(level.into()));
+                encoder
+                    .write_all(data)
+                    .and_then(|_| encoder.finish())
+                    .unwrap_or_default()
+            }
+            #[cfg(not(feature = "std"))]
+            Self::Zstd | Self::Gzip => Vec::new(),
+        }
+    }
+
+    /// Decompresses the data, reading at most `limit` bytes of output.
+    #[cfg_attr(not(feature = "std"), allow(unused_variables))]
+    fn decompress(&self, data: &[u8], limit: usize) -> Result<Vec<u8>> {
+        self.check_supported()?;
+        let result = match self {
+            Self::Deflate => decompress_to_vec(data).ok(),
+            #[cfg(feature = "std")]
+            Self::Zstd => zstd::bulk::decompress(data, limit).ok(),
+            #[cfg(feature = "std")]
+            Self::Gzip => {
+                let mut result = Vec::with_capacity(limit);
+                GzDecoder::new(data)
+                    .take((limit as u64) + 1)
+                    .read_to_end(&mut result)
+                    .ok()
+                    .map(|_| result)
+            }
+            #[cfg(not(feature = "std"))]
+            Self::Zstd | Self::Gzip => unreachable!(),
+        };
+        result.ok_or_else(|| anyhow!("corrupt compressed data"))
+    }
+
+    /// Returns a reader that decompresses the data as it is read.
+    #[cfg(feature = "std")]
+    fn decoder<'a>(&self, data: &'a [u8]) -> Result<Box<dyn Read + 'a>> {
+        Ok(match self {
+            Self::Deflate => Box::new(DeflateDecoder::new(data)),
+            Self::Zstd => Box::new(
+                zstd::stream::read::Decoder::with_buffer(data)
+                    .map_err(|_| anyhow!("corrupt compressed data"))?
+            ),
+            Self::Gzip => Box::new(GzDecoder::new(data)),
+        })
+    }
+}
+
 /// A compressed binary object.
 ///
-/// Implemented using the raw DEFLATE format as described in
-/// [IETF RFC 1951](https://www.ietf.org/rfc/rfc1951.txt).
+/// By default implemented using the raw DEFLATE format as described in
+/// [IETF RFC 1951](https://www.ietf.org/rfc/rfc1951.txt). Zstandard and gzip
+/// may be selected instead; see `CompressionAlgorithm`.
 ///
-/// The following obtains the equivalent configuration of the encoder:
+/// The following obtains the equivalent configuration of the DEFLATE encoder:
 ///
 /// `deflateInit2(zstream,5,Z_DEFLATED,-15,8,Z_DEFAULT_STRATEGY)`
 ///
@@ -23,6 +163,7 @@ pub struct Compressed {
     uncompressed_size: usize,
     compressed_data: Vec<u8>,
     digest: Option<Digest>,
+    algorithm: CompressionAlgorithm,
 }
 
 impl Compressed {
@@ -36,6 +177,24 @@ impl Compressed {
         uncompressed_size: usize,
         compressed_data: Vec<u8>,
         digest: Option<Digest>
+    ) -> Result<Self> {
+        Self::new_with_algorithm(
+            checksum,
+            uncompressed_size,
+            compressed_data,
+            digest,
+            CompressionAlgorithm::default()
+        )
+    }
+
+    /// Creates a new `Compressed` object like `new`, with data compressed by
+    /// the given algorithm.
+    pub fn new_with_algorithm(
+        checksum: u32,
+        uncompressed_size: usize,
+        compressed_data: Vec<u8>,
+        digest: Option<Digest>,
+        algorithm: CompressionAlgorithm
     ) -> Result<Self> {
         if compressed_data.len() > uncompressed_size {
             bail!("Compressed data is larger than uncompressed size");
@@ -45,6 +204,7 @@ impl Compressed {
             uncompressed_size,
             compressed_data,
             digest,
+            algorithm,
         })
     }
 
@@ -57,9 +217,59 @@ impl Compressed {
     pub fn from_uncompressed_data(
         uncompressed_data: impl Into<Vec<u8>>,
         digest: Option<Digest>
+    ) -> Self {
+        let algorithm = CompressionAlgorithm::default();
+        Self::compress_supported(uncompressed_data, digest, algorithm, algorithm.default_level())
+    }
+
+    /// Creates a new `Compressed` object from the given uncompressed data and
+    /// digest, compressed with the given algorithm.
+    ///
+    /// As with `from_uncompressed_data`, the uncompressed data is stored if
+    /// compressing it doesn't make it smaller.
+    ///
+    /// Returns an error if the algorithm isn't supported by this build.
+    pub fn from_uncompressed_data_with_algorithm(
+        uncompressed_data: impl Into<Vec<u8>>,
+        digest: Option<Digest>,
+        algorithm: CompressionAlgorithm
+    ) -> Result<Self> {
+        Self::from_uncompressed_data_with_level(
+            uncompressed_data,
+            digest,
+            algorithm,
+            algorithm.default_level()
+        )
+    }
+
+    /// Creates a new `Compressed` object from the given uncompressed data and
+    /// digest, compressed with the given algorithm at the given level.
+    ///
+    /// Higher levels trade CPU time for a better ratio. The level is clamped
+    /// to the algorithm's range: 0 (no compression) to 9 for DEFLATE and
+    /// gzip, and 1 to 22 for Zstandard. It isn't recorded, because
+    /// uncompressing doesn't need it. As with `from_uncompressed_data`, the
+    /// uncompressed data is stored if compressing it doesn't make it smaller.
+    ///
+    /// Returns an error if the algorithm isn't supported by this build.
+    pub fn from_uncompressed_data_with_level(
+        uncompressed_data: impl Into<Vec<u8>>,
+        digest: Option<Digest>,
+        algorithm: CompressionAlgorithm,
+        level: u8
+    ) -> Result<Self> {
+        algorithm.check_supported()?;
+        Ok(Self::compress_supported(uncompressed_data, digest, algorithm, level))
+    }
+
+    fn compress_supported(
+        uncompressed_data: impl Into<Vec<u8>>,
+        digest: Option<Digest>,
+        algorithm: CompressionAlgorithm,
+        level: u8
     ) -> Self {
         let uncompressed_data = uncompressed_data.into();
-        let compressed_data = compress_to_vec(&uncompressed_data, 6);
+        let compressed_data = algorithm.compress(&uncompressed_data, level);
         let checksum = crc32(&uncompressed_data);
         let uncompressed_size = uncompressed_data.len();
         let compressed_size = compressed_data.len();
@@ -69,6 +279,7 @@ impl Compressed {
                 uncompressed_size,
                 compressed_data,
                 digest,
+                algorithm,
             }
         } else {
             Self {
@@ -76,22 +287,53 @@ impl Compressed {
                 uncompressed_size,
                 compressed_data: uncompressed_data,
                 digest,
+                algorithm,
             }
         }
     }
 
+    /// Creates a new `Compressed` object from the given data, compressing it
+    /// with the default algorithm only if that makes it smaller.
+    ///
+    /// Otherwise the data is stored verbatim, which `is_stored` reports. This
+    /// is `from_uncompressed_data` without a digest, named for what it does.
+    ///
+    /// The CBOR has no separate stored flag because the lengths already say
+    /// which form it is. Compressed output is kept only when it's strictly
+    /// shorter than the input, and `new` rejects data longer than the
+    /// uncompressed size, so a payload exactly as long as the uncompressed
+    /// size can only be the stored form. Decoders that predate this method
+    /// read it the same way.
+    pub fn compress_or_store(data: impl Into<Vec<u8>>) -> Self {
+        Self::from_uncompressed_data(data, None)
+    }
+
+    /// Creates a new `Compressed` object from the given data like
+    /// `compress_or_store`, compressing it with the default algorithm at the
+    /// given DEFLATE level, from 0 to 9. Higher levels are clamped to 9.
+    pub fn compress_with_level(data: impl Into<Vec<u8>>, level: u8) -> Self {
+        Self::compress_supported(data, None, CompressionAlgorithm::default(), level)
+    }
+
+    /// Returns `true` if the data is stored verbatim rather than compressed.
+    pub fn is_stored(&self) -> bool {
+        self.compressed_data.len() >= self.uncompressed_size
+    }
+
     /// Uncompresses the compressed data and returns the uncompressed data.
     ///
+    /// Stored data is returned as is.
+    ///
     /// Returns an error if the compressed data is corrupt or the checksum does not match the uncompressed data.
     pub fn uncompress(&self) -> Result<Vec<u8>> {
-        let compressed_size = self.compressed_data.len();
-        if compressed_size >= self.uncompressed_size {
-            return Ok(self.compressed_data.clone());
+        let uncompressed_data = if self.is_stored() {
+            self.compressed_data.clone()
+        } else {
+            self.algorithm.decompress(&self.compressed_data, self.uncompressed_size)?
+        };
+        if uncompressed_data.len() != self.uncompressed_size {
+            bail!("compressed data size mismatch");
         }
-
-        let uncompressed_data = decompress_to_vec(&self.compressed_data).map_err(|_|
-            anyhow!("corrupt compressed data")
-        )?;
         if crc32(&uncompressed_data) != self.checksum {
             bail!("compressed data checksum mismatch");
         }
@@ -99,12 +341,68 @@ impl Compressed {
         Ok(uncompressed_data)
     }
 
+    /// Uncompresses the compressed data, writing it to `out` in chunks as it
+    /// is decompressed rather than collecting it in memory, and returns the
+    /// number of bytes written.
+    ///
+    /// Returns an error if the compressed data is corrupt, the size or
+    /// checksum does not match, or writing fails. The checksum can only be
+    /// checked at the end, so on error `out` may already have received some,
+    /// or in the case of a checksum mismatch all, of the possibly-incorrect
+    /// output, and should be discarded.
+    #[cfg(feature = "std")]
+    pub fn uncompress_to(&self, mut out: impl Write) -> Result<usize> {
+        let mut reader: Box<dyn Read + '_> = if self.is_stored() {
+            Box::new(self.compressed_data.as_slice())
+        } else {
+            self.algorithm.decoder(&self.compressed_data)?
+        };
+        let mut hasher = crc32fast::Hasher::new();
+        let mut buffer = [0u8; 8192];
+        let mut written = 0;
+        loop {
+            let count = match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(count) => count,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(_) => bail!("corrupt compressed data"),
+            };
+            if written + count > self.uncompressed_size {
+                bail!("compressed data size mismatch");
+            }
+            hasher.update(&buffer[..count]);
+            out.write_all(&buffer[..count])?;
+            written += count;
+        }
+        if written != self.uncompressed_size {
+            bail!("compressed data size mismatch");
+        }
+        if hasher.finalize() != self.checksum {
+            bail!("compressed data checksum mismatch");
+        }
+        out.flush()?;
+        Ok(written)
+    }
+
+    /// Returns the algorithm used to compress the data.
+    pub fn algorithm(&self) -> CompressionAlgorithm {
+        self.algorithm
+    }
+
+    /// Returns the size of the data before compression.
+    pub fn uncompressed_size(&self) -> usize {
+        self.uncompressed_size
+    }
+
     /// Returns the size of the compressed data.
     pub fn compressed_size(&self) -> usize {
         self.compressed_data.len()
     }
 
-    /// Returns the compression ratio of the compressed data.
+    /// Returns the compression ratio of the compressed data, that is the
+    /// compressed size divided by the uncompressed size.
+    ///
+    /// The ratio of empty data is `NaN`.
     pub fn compression_ratio(&self) -> f64 {
         (self.compressed_size() as f64) / (self.uncompressed_size as f64)
     }
@@ -120,20 +418,26 @@ impl Compressed {
     }
 }
 
+/// Like `EncryptedMessage`, `Compressed` returns the digest of the content it
+/// wraps rather than digesting its own CBOR.
+///
+/// # Panics
+///
+/// Panics if no digest was recorded; check `has_digest` first.
 impl DigestProvider for Compressed {
     fn digest(&self) -> Cow<'_, Digest> {
         Cow::Owned(self.digest.as_ref().unwrap().clone())
     }
 }
 
-impl std::fmt::Debug for Compressed {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Debug for Compressed {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         write!(
             f,
             "Compressed(checksum: {}, size: {}/{}, ratio: {:.2}, digest: {})",
             hex::encode(self.checksum.to_be_bytes()),
             self.compressed_size(),
-            self.uncompressed_size,
+            self.uncompressed_size(),
             self.compression_ratio(),
             self
                 .digest_ref_opt()
@@ -168,6 +472,9 @@ impl CBORTaggedEncodable for Compressed {
             self.uncompressed_size.into(),
             CBOR::to_byte_string(&self.compressed_data)
         ];
+        if let Some(discriminator) = self.algorithm.discriminator() {
+            elements.push(discriminator.into());
+        }
         if let Some(digest) = self.digest.clone() {
             elements.push(digest.into());
         }
@@ -184,22 +491,43 @@ impl TryFrom<CBOR> for Compressed {
 }
 
 impl CBORTaggedDecodable for Compressed {
+    fn from_tagged_cbor(cbor: CBOR) -> Result<Self> {
+        crate::component_error::decode_tagged(cbor, "Compressed")
+    }
+
     fn from_untagged_cbor(cbor: CBOR) -> Result<Self> {
         let elements = cbor.try_into_array()?;
-        if elements.len() < 3 || elements.len() > 4 {
-            bail!("invalid number of elements in compressed");
+        if elements.len() < 3 || elements.len() > 5 {
+            bail!(ComponentError::InvalidFormat { component: "Compressed" });
         }
         let checksum = elements[0].clone().try_into()?;
         let uncompressed_size = elements[1].clone().try_into()?;
         let compressed_data = elements[2].clone().try_into_byte_string()?;
-        let digest = if elements.len() == 4 { Some(elements[3].clone().try_into()?) } else { None };
-        Self::new(checksum, uncompressed_size, compressed_data, digest)
+        let mut rest = elements[3..].iter().peekable();
+        let algorithm = match rest.peek().map(|e| e.as_case()) {
+            Some(CBORCase::Unsigned(discriminator)) => {
+                let algorithm = CompressionAlgorithm::from_discriminator(*discriminator)?;
+                rest.next();
+                algorithm
+            }
+            _ => CompressionAlgorithm::default(),
+        };
+        let digest = rest.next().map(|e| e.clone().try_into()).transpose()?;
+        if rest.next().is_some() {
+            bail!(ComponentError::InvalidFormat { component: "Compressed" });
+        }
+        Self::new_with_algorithm(checksum, uncompressed_size, compressed_data, digest, algorithm)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::Compressed;
+    use dcbor::prelude::*;
+
+    use crate::{ Compressed, CompressionAlgorithm, Digest };
+
+    const LOREM: &[u8] =
+        b"Lorem ipsum dolor sit amet consectetur adipiscing elit mi nibh ornare proin blandit diam ridiculus, faucibus mus dui eu vehicula nam donec dictumst sed vivamus bibendum aliquet efficitur. Felis imperdiet sodales dictum morbi vivamus augue dis duis aliquet velit ullamcorper porttitor, lobortis dapibus hac purus aliquam natoque iaculis blandit montes nunc pretium.";
 
     #[test]
     fn test_1() {
@@ -245,4 +573,257 @@ mod tests {
         );
         assert_eq!(compressed.uncompress().unwrap(), source);
     }
+
+    #[test]
+    fn test_sizes() {
+        let compressed = Compressed::from_uncompressed_data(LOREM, None);
+        assert_eq!(compressed.uncompressed_size(), 364);
+        assert_eq!(compressed.compressed_size(), 217);
+        assert_eq!(compressed.compression_ratio(), 217.0 / 364.0);
+
+        let stored = Compressed::from_uncompressed_data(b"Lorem", None);
+        assert_eq!(stored.uncompressed_size(), 5);
+        assert_eq!(stored.compressed_size(), 5);
+        assert_eq!(stored.compression_ratio(), 1.0);
+    }
+
+    #[test]
+    fn test_compress_or_store() {
+        let compressed = Compressed::compress_or_store(LOREM);
+        assert!(!compressed.is_stored());
+        assert!(compressed.compressed_size() < LOREM.len());
+        assert_eq!(compressed.uncompress().unwrap(), LOREM);
+
+        // High-entropy data doesn't compress, so it's stored verbatim.
+        let random = bc_rand::random_data(64);
+        let stored = Compressed::compress_or_store(random.clone());
+        assert!(stored.is_stored());
+        assert_eq!(stored.compressed_size(), random.len());
+        let elements = stored.untagged_cbor().try_into_array().unwrap();
+        assert_eq!(elements[2].clone().try_into_byte_string().unwrap(), random);
+        let decoded = Compressed::from_tagged_cbor_data(stored.tagged_cbor().to_cbor_data()).unwrap();
+        assert!(decoded.is_stored());
+        assert_eq!(decoded.uncompress().unwrap(), random);
+
+        // Stored data is still checked against the checksum.
+        let tampered = Compressed::new(stored.checksum ^ 1, random.len(), random, None).unwrap();
+        assert!(tampered.uncompress().is_err());
+    }
+
+    #[test]
+    fn test_stored_wire_format() {
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        let random = bc_rand::rng_random_data(&mut rng, 64);
+        let stored = Compressed::compress_or_store(random.clone());
+        assert!(stored.is_stored());
+
+        // 40003([checksum, 64, h'<the 64 input bytes>']): the payload is the
+        // input itself, and its length matches the uncompressed size.
+        let expected = format!("d99c4383{}{}{}{}", "1a3678f03d", "1840", "5840", hex::encode(&random));
+        assert_eq!(hex::encode(stored.tagged_cbor_data()), expected);
+        let decoded = Compressed::from_tagged_cbor_data(hex::decode(expected).unwrap()).unwrap();
+        assert!(decoded.is_stored());
+        assert_eq!(decoded.uncompress().unwrap(), random);
+    }
+
+    #[test]
+    fn test_compress_with_level() {
+        let data = LOREM.repeat(20);
+
+        // The default level is the one `compress_or_store` uses.
+        assert_eq!(Compressed::compress_with_level(data.clone(), 6), Compressed::compress_or_store(data.clone()));
+
+        let low = Compressed::compress_with_level(data.clone(), 1);
+        let high = Compressed::compress_with_level(data.clone(), 9);
+        assert!(high.compressed_size() <= low.compressed_size());
+        assert_eq!(low.uncompress().unwrap(), data);
+        assert_eq!(high.uncompress().unwrap(), data);
+
+        // Out of range levels are clamped.
+        assert_eq!(Compressed::compress_with_level(data.clone(), 200), high);
+        let none = Compressed::compress_with_level(data.clone(), 0);
+        assert_eq!(none.uncompress().unwrap(), data);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_zstd_level() {
+        let data = LOREM.repeat(20);
+        let low = Compressed::from_uncompressed_data_with_level(data.clone(), None, CompressionAlgorithm::Zstd, 1).unwrap();
+        let high = Compressed::from_uncompressed_data_with_level(data.clone(), None, CompressionAlgorithm::Zstd, 22).unwrap();
+        assert!(high.compressed_size() <= low.compressed_size());
+        assert_eq!(low.uncompress().unwrap(), data);
+        assert_eq!(high.uncompress().unwrap(), data);
+        let clamped = Compressed::from_uncompressed_data_with_level(data.clone(), None, CompressionAlgorithm::Zstd, 0).unwrap();
+        assert_eq!(clamped, low);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_algorithms() {
+        let digest = Digest::from_image(LOREM);
+        for algorithm in [
+            CompressionAlgorithm::Deflate,
+            CompressionAlgorithm::Zstd,
+            CompressionAlgorithm::Gzip,
+        ] {
+            let compressed = Compressed::from_uncompressed_data_with_algorithm(
+                LOREM,
+                Some(digest.clone()),
+                algorithm
+            ).unwrap();
+            assert_eq!(compressed.algorithm(), algorithm);
+            assert!(compressed.compressed_size() < LOREM.len());
+            assert_eq!(compressed.uncompress().unwrap(), LOREM);
+
+            // The algorithm travels with the CBOR, with and without a digest.
+            let decoded = Compressed::from_tagged_cbor_data(
+                compressed.tagged_cbor().to_cbor_data()
+            ).unwrap();
+            assert_eq!(decoded, compressed);
+            assert_eq!(decoded.uncompress().unwrap(), LOREM);
+
+            let compressed = Compressed::from_uncompressed_data_with_algorithm(LOREM, None, algorithm).unwrap();
+            let decoded = Compressed::from_tagged_cbor_data(
+                compressed.tagged_cbor().to_cbor_data()
+            ).unwrap();
+            assert_eq!(decoded, compressed);
+            assert_eq!(decoded.uncompress().unwrap(), LOREM);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_default_algorithm_encoding() {
+        // DEFLATE data encodes exactly as it did before algorithms were selectable.
+        let compressed = Compressed::from_uncompressed_data(LOREM, None);
+        assert_eq!(compressed.algorithm(), CompressionAlgorithm::Deflate);
+        assert_eq!(compressed.untagged_cbor().try_into_array().unwrap().len(), 3);
+
+        let zstd = Compressed::from_uncompressed_data_with_algorithm(
+            LOREM,
+            None,
+            CompressionAlgorithm::Zstd
+        ).unwrap();
+        let elements = zstd.untagged_cbor().try_into_array().unwrap();
+        assert_eq!(elements.len(), 4);
+        assert_eq!(elements[3], CBOR::from(1));
+    }
+
+    #[test]
+    fn test_non_canonical_cbor_rejected() {
+        let compressed = Compressed::from_uncompressed_data(LOREM, Some(Digest::from_image(LOREM)));
+        let elements = compressed.untagged_cbor().try_into_array().unwrap();
+        assert_eq!(elements.len(), 4);
+        assert_eq!(Compressed::from_untagged_cbor(elements.clone().into()).unwrap(), compressed);
+
+        // DEFLATE is never named explicitly.
+        let mut explicit_default = elements.clone();
+        explicit_default.insert(3, CBOR::from(0));
+        // Nothing follows the digest.
+        let mut trailing = elements.clone();
+        trailing.push(elements[3].clone());
+        let mut too_long = explicit_default.clone();
+        too_long.push(elements[3].clone());
+        for elements in [explicit_default, trailing, too_long] {
+            assert!(Compressed::from_untagged_cbor(elements.into()).is_err());
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_corrupt_data() {
+        for algorithm in [
+            CompressionAlgorithm::Deflate,
+            CompressionAlgorithm::Zstd,
+            CompressionAlgorithm::Gzip,
+        ] {
+            let compressed = Compressed::from_uncompressed_data_with_algorithm(LOREM, None, algorithm).unwrap();
+            let data = compressed.untagged_cbor().try_into_array().unwrap()[2]
+                .clone()
+                .try_into_byte_string()
+                .unwrap();
+
+            let truncated = Compressed::new_with_algorithm(
+                bc_crypto::hash::crc32(LOREM),
+                LOREM.len(),
+                data[..data.len() / 2].to_vec(),
+                None,
+                algorithm
+            ).unwrap();
+            assert!(truncated.uncompress().is_err());
+
+            let mut flipped = data.clone();
+            let middle = flipped.len() / 2;
+            flipped[middle] ^= 0xff;
+            let corrupt = Compressed::new_with_algorithm(
+                bc_crypto::hash::crc32(LOREM),
+                LOREM.len(),
+                flipped,
+                None,
+                algorithm
+            ).unwrap();
+            assert!(corrupt.uncompress().is_err());
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_uncompress_to() {
+        let data: Vec<u8> = LOREM.iter().cycle().take(100_000).copied().collect();
+        for algorithm in [
+            CompressionAlgorithm::Deflate,
+            CompressionAlgorithm::Zstd,
+            CompressionAlgorithm::Gzip,
+        ] {
+            let compressed = Compressed::from_uncompressed_data_with_algorithm(data.clone(), None, algorithm).unwrap();
+            let mut out = Vec::new();
+            assert_eq!(compressed.uncompress_to(&mut out).unwrap(), data.len());
+            assert_eq!(out, compressed.uncompress().unwrap());
+
+            let corrupt_data = compressed.untagged_cbor().try_into_array().unwrap()[2]
+                .clone()
+                .try_into_byte_string()
+                .unwrap();
+            let truncated = Compressed::new_with_algorithm(
+                compressed.checksum,
+                data.len(),
+                corrupt_data[..corrupt_data.len() / 2].to_vec(),
+                None,
+                algorithm
+            ).unwrap();
+            assert!(truncated.uncompress_to(Vec::new()).is_err());
+        }
+
+        let stored = Compressed::compress_or_store(b"tiny".to_vec());
+        assert!(stored.is_stored());
+        let mut out = Vec::new();
+        assert_eq!(stored.uncompress_to(&mut out).unwrap(), 4);
+        assert_eq!(out, b"tiny");
+
+        let wrong_checksum = Compressed::new(0, 4, b"tiny".to_vec(), None).unwrap();
+        let err = wrong_checksum.uncompress_to(Vec::new()).unwrap_err();
+        assert_eq!(err.to_string(), "compressed data checksum mismatch");
+    }
+
+    #[cfg(not(feature = "std"))]
+    #[test]
+    fn test_unsupported_algorithms() {
+        use crate::ComponentError;
+
+        for (algorithm, value) in [(CompressionAlgorithm::Zstd, 1), (CompressionAlgorithm::Gzip, 2)] {
+            assert!(!algorithm.is_supported());
+            let expected = ComponentError::UnsupportedAlgorithm { component: "compression", value };
+            let err = Compressed::from_uncompressed_data_with_algorithm(LOREM, None, algorithm).unwrap_err();
+            assert_eq!(err.downcast_ref::<ComponentError>(), Some(&expected));
+
+            // Such data still decodes, and fails only when uncompressed.
+            let compressed = Compressed::new_with_algorithm(0, LOREM.len(), vec![0; 10], None, algorithm).unwrap();
+            let decoded = Compressed::from_tagged_cbor_data(compressed.tagged_cbor_data()).unwrap();
+            assert_eq!(decoded.algorithm(), algorithm);
+            let err = decoded.uncompress().unwrap_err();
+            assert_eq!(err.downcast_ref::<ComponentError>(), Some(&expected));
+        }
+        assert!(CompressionAlgorithm::Deflate.is_supported());
+    }
 }
@@ -0,0 +1,83 @@
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+#[cfg(feature = "std")]
+use bc_crypto::hash::sha256;
+
+use crate::ARID;
+
+/// A store of blobs addressed by the digest of their content.
+///
+/// The identifier of a blob is its SHA-256 digest as an `ARID`, so it is the
+/// same bytes as `Digest::from_image` of the blob, and putting the same data
+/// twice gives the same identifier and stores it once.
+pub trait ContentStore {
+    /// Stores `data` and returns its content identifier.
+    fn put(&mut self, data: &[u8]) -> ARID;
+
+    /// Returns the data stored under `id`, if any.
+    fn get(&self, id: &ARID) -> Option<Vec<u8>>;
+}
+
+/// An in-memory `ContentStore` backed by a `HashMap`.
+#[cfg(feature = "std")]
+#[derive(Clone, Default, Debug)]
+pub struct HashMapContentStore(HashMap<ARID, Vec<u8>>);
+
+#[cfg(feature = "std")]
+impl HashMapContentStore {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of distinct blobs in the store.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the store holds no blobs.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[cfg(feature = "std")]
+impl ContentStore for HashMapContentStore {
+    fn put(&mut self, data: &[u8]) -> ARID {
+        let id = ARID::from_data(sha256(data));
+        self.0.entry(id.clone()).or_insert_with(|| data.to_vec());
+        id
+    }
+
+    fn get(&self, id: &ARID) -> Option<Vec<u8>> {
+        self.0.get(id).cloned()
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use crate::{ ContentStore, Digest, HashMapContentStore, ARID };
+
+    #[test]
+    fn test_put_get() {
+        let mut store = HashMapContentStore::new();
+        assert!(store.is_empty());
+
+        let id = store.put(b"Wolf McNally");
+        assert_eq!(id.data(), Digest::from_image(b"Wolf McNally").data());
+        assert_eq!(store.get(&id).unwrap(), b"Wolf McNally");
+
+        // Identical data gives the same identifier and is stored once.
+        assert_eq!(store.put(b"Wolf McNally"), id);
+        assert_eq!(store.len(), 1);
+
+        let other = store.put(b"Other data");
+        assert_ne!(other, id);
+        assert_eq!(store.len(), 2);
+        assert_eq!(store.get(&other).unwrap(), b"Other data");
+
+        assert_eq!(store.get(&ARID::new()), None);
+    }
+}
@@ -1,78 +1,297 @@
-use std::borrow::Cow;
-use bc_crypto::hash::sha256;
-use dcbor::{ CBORTagged, Tag, CBOR, CBORTaggedEncodable, CBORTaggedDecodable };
-use crate::{ digest_provider::DigestProvider, tags };
+use alloc::{ borrow::Cow, string::String, vec, vec::Vec };
+#[cfg(feature = "std")]
+use std::io::{ self, Read };
+use bc_ur::bytewords;
+use dcbor::{ CBORTagged, Tag, CBOR, CBORCase, CBORTaggedEncodable, CBORTaggedDecodable };
+use crate::{
+    bech32_encoding, digest_provider::DigestProvider, tags, ComponentError, DigestHasher, ShortDigest,
+};
 use anyhow::{ bail, Result, Error };
+use sha2::{ Digest as _, Sha512 };
+use subtle::ConstantTimeEq;
+
+/// The hash algorithm a `Digest` was computed with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum DigestAlgorithm {
+    /// SHA-256, with a 32-byte digest.
+    #[default]
+    Sha256,
+    /// SHA-512, with a 64-byte digest.
+    Sha512,
+    /// BLAKE3, with a 32-byte digest.
+    Blake3,
+}
+
+impl DigestAlgorithm {
+    /// The size in bytes of digests computed with this algorithm.
+    pub fn digest_size(&self) -> usize {
+        match self {
+            Self::Sha256 | Self::Blake3 => 32,
+            Self::Sha512 => 64,
+        }
+    }
+
+    /// The value identifying this algorithm in CBOR, or `None` for SHA-256,
+    /// which is encoded without one.
+    fn discriminator(&self) -> Option<u64> {
+        match self {
+            Self::Sha256 => None,
+            Self::Sha512 => Some(1),
+            Self::Blake3 => Some(2),
+        }
+    }
 
-/// A cryptographically secure digest, implemented with SHA-256.
+    fn from_discriminator(discriminator: u64) -> Option<Self> {
+        match discriminator {
+            1 => Some(Self::Sha512),
+            2 => Some(Self::Blake3),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Sha256 => "SHA-256",
+            Self::Sha512 => "SHA-512",
+            Self::Blake3 => "BLAKE3",
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum DigestData {
+    Sha256([u8; Digest::DIGEST_SIZE]),
+    Sha512([u8; 64]),
+    Blake3([u8; 32]),
+}
+
+/// A cryptographically secure digest.
+///
+/// Digests are computed with SHA-256 unless another `DigestAlgorithm` is
+/// chosen explicitly. Digests computed with different algorithms are never
+/// equal, even if their bytes are.
 #[derive(Clone, PartialEq, Eq, Hash)]
-pub struct Digest([u8; Self::DIGEST_SIZE]);
+pub struct Digest(DigestData);
 
 impl Digest {
+    /// The size of a SHA-256 digest.
     pub const DIGEST_SIZE: usize = 32;
 
-    /// Create a new digest from data.
+    /// Create a new SHA-256 digest from data.
     pub fn from_data(data: [u8; Self::DIGEST_SIZE]) -> Self {
-        Self(data)
+        Self(DigestData::Sha256(data))
     }
 
-    /// Create a new digest from data.
+    /// Create a new SHA-256 digest from data.
     ///
-    /// Returns `None` if the data is not the correct length.
+    /// Returns an error if the data is not the correct length.
     pub fn from_data_ref(data: impl AsRef<[u8]>) -> Result<Self> {
+        Self::from_data_with_algorithm(DigestAlgorithm::Sha256, data)
+    }
+
+    /// Create a new digest computed with the given algorithm from data.
+    ///
+    /// Returns an error if the data is not the correct length for the algorithm.
+    pub fn from_data_with_algorithm(algorithm: DigestAlgorithm, data: impl AsRef<[u8]>) -> Result<Self> {
         let data = data.as_ref();
-        if data.len() != Self::DIGEST_SIZE {
-            bail!("Invalid digest size");
+        if data.len() != algorithm.digest_size() {
+            bail!(ComponentError::InvalidLength { component: "digest" });
+        }
+        let data = match algorithm {
+            DigestAlgorithm::Sha256 => DigestData::Sha256(data.try_into().unwrap()),
+            DigestAlgorithm::Sha512 => DigestData::Sha512(data.try_into().unwrap()),
+            DigestAlgorithm::Blake3 => DigestData::Blake3(data.try_into().unwrap()),
+        };
+        Ok(Self(data))
+    }
+
+    /// Create a new digest from the given image, hashed with the given algorithm.
+    pub fn from_image_with_algorithm(algorithm: DigestAlgorithm, image: impl AsRef<[u8]>) -> Self {
+        match algorithm {
+            DigestAlgorithm::Sha256 => Self::from_image(image),
+            DigestAlgorithm::Sha512 => Self(DigestData::Sha512(Sha512::digest(image.as_ref()).into())),
+            DigestAlgorithm::Blake3 => Self(DigestData::Blake3(blake3::hash(image.as_ref()).into())),
+        }
+    }
+
+    /// Create a new digest from the given image, hashed with SHA-512.
+    pub fn sha512(image: impl AsRef<[u8]>) -> Self {
+        Self::from_image_with_algorithm(DigestAlgorithm::Sha512, image)
+    }
+
+    /// Create a new digest from the given image, hashed with BLAKE3.
+    pub fn blake3(image: impl AsRef<[u8]>) -> Self {
+        Self::from_image_with_algorithm(DigestAlgorithm::Blake3, image)
+    }
+
+    /// The algorithm this digest was computed with.
+    pub fn algorithm(&self) -> DigestAlgorithm {
+        match self.0 {
+            DigestData::Sha256(_) => DigestAlgorithm::Sha256,
+            DigestData::Sha512(_) => DigestAlgorithm::Sha512,
+            DigestData::Blake3(_) => DigestAlgorithm::Blake3,
         }
-        let mut arr = [0u8; Self::DIGEST_SIZE];
-        arr.copy_from_slice(data.as_ref());
-        Ok(Self::from_data(arr))
     }
 
     /// Create a new digest from the given image.
     ///
     /// The image is hashed with SHA-256.
     pub fn from_image(image: impl AsRef<[u8]>) -> Self {
-        Self::from_data(sha256(image.as_ref()))
+        let mut hasher = DigestHasher::new();
+        hasher.update(image);
+        hasher.finalize()
+    }
+
+    /// Create a new digest of the tagged CBOR encoding of `value`.
+    ///
+    /// This is the "hash of the serialized form" used for component digests
+    /// and by Gordian Envelope for its elements.
+    pub fn from_cbor_encodable(value: &impl CBORTaggedEncodable) -> Self {
+        Self::from_image(value.tagged_cbor_data())
     }
 
     /// Create a new digest from an array of data items.
     ///
     /// The image parts are concatenated and hashed with SHA-256.
     pub fn from_image_parts(image_parts: &[&[u8]]) -> Self {
-        let mut buf = Vec::new();
+        let mut hasher = DigestHasher::new();
         for part in image_parts {
-            buf.extend_from_slice(part);
+            hasher.update(part);
+        }
+        hasher.finalize()
+    }
+
+    /// Create a new digest from everything read from `reader` until EOF.
+    ///
+    /// The data is read in chunks and hashed with SHA-256, so it is never
+    /// held in memory all at once. Any I/O error other than
+    /// `ErrorKind::Interrupted` is returned unchanged.
+    #[cfg(feature = "std")]
+    pub fn from_reader(mut reader: impl Read) -> io::Result<Self> {
+        let mut hasher = DigestHasher::new();
+        let mut buffer = [0u8; 8192];
+        loop {
+            match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(count) => hasher.update(&buffer[..count]),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(hasher.finalize())
+    }
+
+    /// Create a new digest from an array of data items, framing each item so
+    /// that different splits of the same bytes give different digests.
+    ///
+    /// Each part is preceded by its length in bytes as an 8-byte big-endian
+    /// unsigned integer, and the result is hashed with SHA-256:
+    ///
+    /// `SHA-256(len(p1) || p1 || len(p2) || p2 || ...)`
+    ///
+    /// Unlike `from_image_parts`, a single part does not give the same
+    /// digest as `from_image` of that part.
+    pub fn from_parts(parts: &[&[u8]]) -> Self {
+        let mut hasher = DigestHasher::new();
+        for part in parts {
+            hasher.update((part.len() as u64).to_be_bytes());
+            hasher.update(part);
         }
-        Self::from_image(&buf)
+        hasher.finalize()
     }
 
     /// Create a new digest from an array of Digests.
     ///
     /// The image parts are concatenated and hashed with SHA-256.
     pub fn from_digests(digests: &[Digest]) -> Self {
-        let mut buf = Vec::new();
+        let mut hasher = DigestHasher::new();
         for digest in digests {
-            buf.extend_from_slice(digest.data());
+            hasher.update(digest);
         }
-        Self::from_image(&buf)
+        hasher.finalize()
+    }
+
+    /// Combine this digest with another, independently of their order.
+    ///
+    /// The result is the bytewise XOR of the two digests, so
+    /// `a.combine(b) == b.combine(a)`, and combining a digest with itself
+    /// gives all zeros. Use `combine_ordered` when the order matters.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the digests were computed with different algorithms.
+    pub fn combine(&self, other: &Digest) -> Digest {
+        self.assert_same_algorithm(other);
+        let data: Vec<u8> = self.as_bytes().iter().zip(other.as_bytes()).map(|(a, b)| a ^ b).collect();
+        Self::from_data_with_algorithm(self.algorithm(), data).unwrap()
     }
 
-    /// Get the data of the digest.
-    pub fn data(&self) -> &[u8; 32] {
+    /// Combine this digest with another, followed by the other.
+    ///
+    /// The result is the digest of the concatenation of the two digests,
+    /// hashed with their algorithm, so in general
+    /// `a.combine_ordered(b) != b.combine_ordered(a)`. For SHA-256 digests
+    /// this is the same as `Digest::from_digests(&[a, b])`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the digests were computed with different algorithms.
+    pub fn combine_ordered(&self, other: &Digest) -> Digest {
+        self.assert_same_algorithm(other);
+        Self::from_image_with_algorithm(self.algorithm(), [self.as_bytes(), other.as_bytes()].concat())
+    }
+
+    fn assert_same_algorithm(&self, other: &Digest) {
+        assert!(
+            self.algorithm() == other.algorithm(),
+            "Cannot combine a {} digest with a {} digest",
+            self.algorithm().name(),
+            other.algorithm().name()
+        );
+    }
+
+    /// Get a reference to the fixed-size array of bytes of a 32-byte digest,
+    /// such as any SHA-256 digest.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this is a SHA-512 digest. Use `as_bytes` for the bytes of a
+    /// digest computed with any algorithm.
+    pub fn data(&self) -> &[u8; Self::DIGEST_SIZE] {
         self.into()
     }
 
+    /// Get a reference to the bytes of the digest, whose length depends on
+    /// its algorithm.
+    pub fn as_bytes(&self) -> &[u8] {
+        match &self.0 {
+            DigestData::Sha256(data) => data,
+            DigestData::Sha512(data) => data,
+            DigestData::Blake3(data) => data,
+        }
+    }
+
     /// Validate the digest against the given image.
     ///
-    /// The image is hashed with SHA-256 and compared to the digest.
-    /// Returns `true` if the digest matches the image.
+    /// The image is hashed with this digest's algorithm and compared to the
+    /// digest in constant time. Returns `true` if the digest matches the image.
     pub fn validate(&self, image: impl AsRef<[u8]>) -> bool {
-        self == &Self::from_image(image)
+        self.ct_eq(&Self::from_image_with_algorithm(self.algorithm(), image))
+    }
+
+    /// Compare two digests in constant time.
+    ///
+    /// Use this instead of `==` when either digest may be attacker-influenced,
+    /// such as when checking an expected hash in an authentication flow.
+    /// Digests computed with different algorithms are never equal.
+    pub fn ct_eq(&self, other: &Digest) -> bool {
+        self.algorithm() == other.algorithm() && bool::from(self.as_bytes().ct_eq(other.as_bytes()))
     }
 
     /// Create a new digest from the given hexadecimal string.
     ///
+    /// The digest is taken to be SHA-256.
+    ///
     /// # Panics
     /// Panics if the string is not exactly 64 hexadecimal digits.
     pub fn from_hex(hex: impl AsRef<str>) -> Self {
@@ -81,12 +300,54 @@ impl Digest {
 
     /// The data as a hexadecimal string.
     pub fn hex(&self) -> String {
-        hex::encode(self.0)
+        hex::encode(self.as_bytes())
+    }
+
+    /// Returns the first `size` bytes of this digest.
+    ///
+    /// Returns an error if `size` is zero or larger than the digest. See
+    /// `ShortDigest` on how short is too short.
+    pub fn truncated(&self, size: usize) -> Result<ShortDigest> {
+        if size == 0 || size > self.as_bytes().len() {
+            bail!("Invalid digest truncation size: {}", size);
+        }
+        ShortDigest::from_data_ref(&self.as_bytes()[..size])
     }
 
     /// The first four bytes of the digest as a hexadecimal string.
     pub fn short_description(&self) -> String {
-        hex::encode(&self.0[0..4])
+        hex::encode(&self.as_bytes()[0..4])
+    }
+
+    /// The data as bytewords in the given style, followed by a CRC-32 checksum.
+    pub fn bytewords(&self, style: bytewords::Style) -> String {
+        bytewords::encode(self.as_bytes(), style)
+    }
+
+    /// Create a new digest from bytewords in the given style.
+    ///
+    /// Returns an error if the checksum does not match or the data is not the
+    /// correct length.
+    pub fn from_bytewords(bytewords: &str, style: bytewords::Style) -> Result<Self> {
+        Self::from_data_ref(bytewords::decode(bytewords, style)?)
+    }
+
+    /// The data as a bech32m string with the given human-readable part, such
+    /// as `digest`.
+    ///
+    /// Returns an error if the human-readable part is invalid.
+    pub fn bech32(&self, hrp: &str) -> Result<String> {
+        bech32_encoding::encode(hrp, self.as_bytes())
+    }
+
+    /// Create a new digest from a bech32m string with the given
+    /// human-readable part.
+    ///
+    /// The digest is taken to be SHA-256. Returns an error if the checksum
+    /// does not match, the human-readable part differs, or the data is not
+    /// the correct length.
+    pub fn from_bech32(bech32: &str, hrp: &str) -> Result<Self> {
+        Self::from_data_ref(bech32_encoding::decode(bech32, hrp)?)
     }
 
     /// Validate the given data against the digest, if any.
@@ -101,21 +362,182 @@ impl Digest {
     }
 }
 
+/// Binary Merkle trees over ordered lists of digests.
+///
+/// Leaves and internal nodes are hashed with distinct one-byte prefixes, so
+/// an internal node can never be passed off as a leaf:
+///
+/// - leaf node: `SHA-256(0x00 || leaf)`
+/// - internal node: `SHA-256(0x01 || left || right)`
+///
+/// When a level has an odd number of nodes, the last node is paired with
+/// itself. As a consequence the root does not commit to the number of
+/// leaves: `[a, b, c]` and `[a, b, c, c]` have the same root. Callers that
+/// need to distinguish these should commit to the leaf count separately, and
+/// `verify_merkle_proof` takes that count to reject indexes past the end.
+impl Digest {
+    const MERKLE_LEAF_PREFIX: u8 = 0x00;
+    const MERKLE_NODE_PREFIX: u8 = 0x01;
+
+    fn merkle_leaf(leaf: &Digest) -> Digest {
+        Self::from_image_parts(&[&[Self::MERKLE_LEAF_PREFIX], leaf.as_bytes()])
+    }
+
+    fn merkle_node(left: &Digest, right: &Digest) -> Digest {
+        Self::from_image_parts(&[&[Self::MERKLE_NODE_PREFIX], left.as_bytes(), right.as_bytes()])
+    }
+
+    fn merkle_parent_level(level: &[Digest]) -> Vec<Digest> {
+        level
+            .chunks(2)
+            .map(|pair| Self::merkle_node(&pair[0], pair.get(1).unwrap_or(&pair[0])))
+            .collect()
+    }
+
+    /// Compute the Merkle root of the given ordered leaves.
+    ///
+    /// The root of an empty list is the SHA-256 digest of the empty string.
+    ///
+    /// The root doesn't commit to the number of leaves, so lists that differ
+    /// only by a repeated last leaf can share a root (see above). Record the
+    /// leaf count alongside the root if it matters.
+    pub fn merkle_root(leaves: &[Digest]) -> Digest {
+        if leaves.is_empty() {
+            return Self::from_image([]);
+        }
+        let mut level: Vec<Digest> = leaves.iter().map(Self::merkle_leaf).collect();
+        while level.len() > 1 {
+            level = Self::merkle_parent_level(&level);
+        }
+        level.remove(0)
+    }
+
+    /// Compute the Merkle root of the given ordered leaves like `merkle_root`,
+    /// hashing the nodes of each level on the rayon thread pool.
+    ///
+    /// The result is identical to `merkle_root` for any number of threads:
+    /// only the hashing within a level is parallel, and every level keeps the
+    /// order of its nodes.
+    #[cfg(feature = "rayon")]
+    pub fn merkle_root_parallel(leaves: &[Digest]) -> Digest {
+        use rayon::prelude::*;
+
+        if leaves.is_empty() {
+            return Self::from_image([]);
+        }
+        let mut level: Vec<Digest> = leaves.par_iter().map(Self::merkle_leaf).collect();
+        while level.len() > 1 {
+            level = level
+                .par_chunks(2)
+                .map(|pair| Self::merkle_node(&pair[0], pair.get(1).unwrap_or(&pair[0])))
+                .collect();
+        }
+        level.remove(0)
+    }
+
+    /// Compute the inclusion proof for the leaf at `index`.
+    ///
+    /// The proof is the list of sibling digests from the leaf level up to,
+    /// but not including, the root.
+    ///
+    /// Returns `None` if `index` is out of range for `leaves`.
+    pub fn merkle_proof(leaves: &[Digest], index: usize) -> Option<Vec<Digest>> {
+        if index >= leaves.len() {
+            return None;
+        }
+        let mut proof = Vec::new();
+        let mut level: Vec<Digest> = leaves.iter().map(Self::merkle_leaf).collect();
+        let mut index = index;
+        while level.len() > 1 {
+            let sibling = level.get(index ^ 1).unwrap_or(&level[index]);
+            proof.push(sibling.clone());
+            level = Self::merkle_parent_level(&level);
+            index >>= 1;
+        }
+        Some(proof)
+    }
+
+    /// Verify that `leaf` is at `index` in the tree of `leaf_count` leaves
+    /// with the given `root`, using a proof produced by `merkle_proof`.
+    ///
+    /// Because the last node of an odd level is paired with itself, the last
+    /// leaf of a tree with an odd number of leaves would also verify at the
+    /// next index, which doesn't exist. Checking `index` against `leaf_count`
+    /// rules that out, so `leaf_count` must come from the same trusted source
+    /// as `root`.
+    pub fn verify_merkle_proof(
+        leaf: &Digest,
+        proof: &[Digest],
+        root: &Digest,
+        index: usize,
+        leaf_count: usize
+    ) -> bool {
+        if index >= leaf_count || proof.len() != Self::merkle_depth(leaf_count) {
+            return false;
+        }
+        let mut node = Self::merkle_leaf(leaf);
+        let mut index = index;
+        for sibling in proof {
+            node = if index & 1 == 0 {
+                Self::merkle_node(&node, sibling)
+            } else {
+                Self::merkle_node(sibling, &node)
+            };
+            index >>= 1;
+        }
+        node.ct_eq(root)
+    }
+
+    /// The number of levels above the leaves in a tree of `leaf_count`
+    /// leaves, which is the length of each of its proofs.
+    fn merkle_depth(leaf_count: usize) -> usize {
+        let mut depth = 0;
+        let mut width = leaf_count;
+        while width > 1 {
+            width = width.div_ceil(2);
+            depth += 1;
+        }
+        depth
+    }
+}
+
+/// # Panics
+///
+/// Panics if the digest is a SHA-512 digest.
 impl<'a> From<&'a Digest> for &'a [u8; Digest::DIGEST_SIZE] {
     fn from(value: &'a Digest) -> Self {
-        &value.0
+        match &value.0 {
+            DigestData::Sha256(data) | DigestData::Blake3(data) => data,
+            DigestData::Sha512(_) => panic!("SHA-512 digests are not 32 bytes long"),
+        }
     }
 }
 
 impl<'a> From<&'a Digest> for &'a [u8] {
     fn from(value: &'a Digest) -> Self {
-        &value.0
+        value.as_bytes()
     }
 }
 
 impl AsRef<[u8]> for Digest {
     fn as_ref(&self) -> &[u8] {
-        &self.0
+        self.as_bytes()
+    }
+}
+
+/// Interprets the bytes as a SHA-256 digest, like `Digest::from_data_ref`.
+impl TryFrom<&[u8]> for Digest {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_data_ref(data)
+    }
+}
+
+/// The data is taken to be a SHA-256 digest.
+impl From<[u8; Self::DIGEST_SIZE]> for Digest {
+    fn from(data: [u8; Self::DIGEST_SIZE]) -> Self {
+        Self::from_data(data)
     }
 }
 
@@ -125,14 +547,14 @@ impl AsRef<Digest> for Digest {
     }
 }
 
-impl std::cmp::PartialOrd for Digest {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.0.cmp(&other.0))
+impl core::cmp::PartialOrd for Digest {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
     }
 }
 
-impl std::cmp::Ord for Digest {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+impl core::cmp::Ord for Digest {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
         self.0.cmp(&other.0)
     }
 }
@@ -143,15 +565,18 @@ impl DigestProvider for Digest {
     }
 }
 
-impl std::fmt::Debug for Digest {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Digest({})", self.hex())
+impl core::fmt::Debug for Digest {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self)
     }
 }
 
-impl std::fmt::Display for Digest {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Digest({})", self.hex())
+impl core::fmt::Display for Digest {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.algorithm() {
+            DigestAlgorithm::Sha256 => write!(f, "Digest({})", self.hex()),
+            algorithm => write!(f, "Digest({}, {})", algorithm.name(), self.hex()),
+        }
     }
 }
 
@@ -167,9 +592,16 @@ impl From<Digest> for CBOR {
     }
 }
 
+/// A SHA-256 digest is encoded as a bare byte string, and a digest computed
+/// with another algorithm as the array `[algorithm, bytes]`, where the
+/// algorithm is 1 for SHA-512 and 2 for BLAKE3.
 impl CBORTaggedEncodable for Digest {
     fn untagged_cbor(&self) -> CBOR {
-        CBOR::to_byte_string(self.0)
+        let data = CBOR::to_byte_string(self.as_bytes());
+        match self.algorithm().discriminator() {
+            None => data,
+            Some(discriminator) => vec![discriminator.into(), data].into(),
+        }
     }
 }
 
@@ -182,9 +614,27 @@ impl TryFrom<CBOR> for Digest {
 }
 
 impl CBORTaggedDecodable for Digest {
+    fn from_tagged_cbor(cbor: CBOR) -> Result<Self> {
+        crate::component_error::decode_tagged(cbor, "digest")
+    }
+
     fn from_untagged_cbor(cbor: CBOR) -> Result<Self> {
-        let data = CBOR::try_into_byte_string(cbor)?;
-        Self::from_data_ref(data)
+        match cbor.into_case() {
+            CBORCase::ByteString(data) => Self::from_data_ref(data),
+            CBORCase::Array(elements) if elements.len() == 2 => {
+                let mut drain = elements.into_iter();
+                let ele_0 = drain.next().unwrap().into_case();
+                let ele_1 = drain.next().unwrap().into_case();
+                let (CBORCase::Unsigned(discriminator), CBORCase::ByteString(data)) = (ele_0, ele_1) else {
+                    bail!(ComponentError::InvalidFormat { component: "digest" });
+                };
+                let Some(algorithm) = DigestAlgorithm::from_discriminator(discriminator) else {
+                    bail!(ComponentError::UnknownAlgorithm { component: "digest", value: discriminator });
+                };
+                Self::from_data_with_algorithm(algorithm, data)
+            }
+            _ => bail!(ComponentError::InvalidFormat { component: "digest" }),
+        }
     }
 }
 
@@ -198,22 +648,24 @@ impl From<&Digest> for Digest {
 // Convert from a byte vector to an instance.
 impl From<Digest> for Vec<u8> {
     fn from(digest: Digest) -> Self {
-        digest.0.to_vec()
+        digest.as_bytes().to_vec()
     }
 }
 
 // Convert a reference to an instance to a byte vector.
 impl From<&Digest> for Vec<u8> {
     fn from(digest: &Digest) -> Self {
-        digest.0.to_vec()
+        digest.as_bytes().to_vec()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use bc_crypto::hash::sha256;
     use bc_ur::prelude::*;
     use hex_literal::hex;
+    use indoc::indoc;
 
     #[test]
     fn test_digest() {
@@ -286,4 +738,272 @@ mod tests {
         let invalid_ur = "ur:not_digest/invalid";
         let _ = Digest::from_ur_string(invalid_ur).unwrap();
     }
+
+    #[test]
+    fn test_bytewords() {
+        use bc_ur::bytewords::Style;
+
+        let digest = Digest::from_image(b"Hello");
+        let minimal = digest.bytewords(Style::Minimal);
+        assert_eq!(minimal, "cshelgqdcpjszedaykhsolztmuludmdsfxamwpdygltngylaatttkofddsetcfinstpfbeqz");
+        assert_eq!(Digest::from_bytewords(&minimal, Style::Minimal).unwrap(), digest);
+        let standard = digest.bytewords(Style::Standard);
+        assert_eq!(Digest::from_bytewords(&standard, Style::Standard).unwrap(), digest);
+
+        let corrupted = standard.replacen("hope", "huts", 1);
+        assert!(Digest::from_bytewords(&corrupted, Style::Standard).is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_sha512_data_panics() {
+        let _ = Digest::sha512(b"abc").data();
+    }
+
+    #[test]
+    fn test_algorithms() {
+        let sha512 = Digest::sha512(b"abc");
+        assert_eq!(sha512.algorithm(), DigestAlgorithm::Sha512);
+        assert_eq!(sha512.as_bytes(), hex!("ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"));
+        let blake3 = Digest::blake3(b"");
+        assert_eq!(blake3.algorithm(), DigestAlgorithm::Blake3);
+        assert_eq!(*blake3.data(), hex!("af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"));
+        assert_eq!(Digest::from_image(b"abc").algorithm(), DigestAlgorithm::default());
+
+        assert!(sha512.validate(b"abc"));
+        assert!(!sha512.validate(b"abd"));
+        assert!(blake3.validate(b""));
+
+        // The same bytes under different algorithms are different digests.
+        let sha256 = Digest::from_data_ref(blake3.data()).unwrap();
+        assert_ne!(sha256, blake3);
+        assert!(!sha256.ct_eq(&blake3));
+        assert!(Digest::from_data_with_algorithm(DigestAlgorithm::Sha512, blake3.data()).is_err());
+
+        // `data` is the fixed-size view of a 32-byte digest, and `as_bytes`
+        // the view of any digest.
+        let bytes: &[u8; Digest::DIGEST_SIZE] = (&blake3).into();
+        assert_eq!(bytes, blake3.data());
+        assert_eq!(blake3.as_bytes(), bytes);
+        assert_eq!(sha512.as_bytes().len(), 64);
+
+        for digest in [Digest::from_image(b"abc"), sha512, blake3] {
+            let cbor = digest.to_cbor_data();
+            let decoded = Digest::from_tagged_cbor_data(&cbor).unwrap();
+            assert_eq!(decoded, digest);
+            assert_eq!(decoded.algorithm(), digest.algorithm());
+        }
+        assert_eq!(
+            Digest::blake3(b"").untagged_cbor().diagnostic(),
+            indoc! {"
+            [
+               2,
+               h'af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262'
+            ]
+            "}.trim()
+        );
+        let unknown = CBOR::from(vec![CBOR::from(9), CBOR::to_byte_string([0u8; 32])]);
+        assert!(Digest::from_untagged_cbor(unknown).is_err());
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut data = b"Some mysteries aren't meant to be solved.".to_vec();
+        let digest = Digest::from_image(&data);
+        assert!(digest.validate(&data));
+        assert!(Digest::validate_opt(&data, Some(&digest)));
+        assert!(Digest::validate_opt(&data, None));
+
+        data[7] ^= 0x01;
+        assert!(!digest.validate(&data));
+        assert!(!Digest::validate_opt(&data, Some(&digest)));
+    }
+
+    #[test]
+    fn test_from_parts() {
+        let ab_c = Digest::from_parts(&[b"ab", b"c"]);
+        let a_bc = Digest::from_parts(&[b"a", b"bc"]);
+        assert_ne!(ab_c, a_bc);
+        assert_eq!(Digest::from_image_parts(&[b"ab", b"c"]), Digest::from_image_parts(&[b"a", b"bc"]));
+
+        // The framing is the 8-byte big-endian length of each part.
+        let framed = [&2u64.to_be_bytes()[..], b"ab", &1u64.to_be_bytes()[..], b"c"].concat();
+        assert_eq!(ab_c, Digest::from_image(framed));
+        assert_ne!(Digest::from_parts(&[b"abc"]), Digest::from_image(b"abc"));
+        assert_ne!(Digest::from_parts(&[b"", b"abc"]), Digest::from_parts(&[b"abc"]));
+        assert_eq!(Digest::from_parts(&[]), Digest::from_image([]));
+    }
+
+    #[test]
+    fn test_combine() {
+        let a = Digest::from_image(b"a");
+        let b = Digest::from_image(b"b");
+        let combined = a.combine(&b);
+        assert_eq!(combined, b.combine(&a));
+        assert_eq!(a.combine(&a), Digest::from_data([0; Digest::DIGEST_SIZE]));
+        assert_eq!(combined.combine(&b), a);
+
+        let ordered = a.combine_ordered(&b);
+        assert_ne!(ordered, b.combine_ordered(&a));
+        assert_eq!(ordered, Digest::from_digests(&[a.clone(), b.clone()]));
+        let sha512 = Digest::sha512(b"a").combine_ordered(&Digest::sha512(b"b"));
+        assert_eq!(sha512.algorithm(), DigestAlgorithm::Sha512);
+
+        for digest in [combined, ordered, sha512] {
+            assert_eq!(Digest::from_tagged_cbor_data(digest.to_cbor_data()).unwrap(), digest);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot combine a SHA-256 digest with a BLAKE3 digest")]
+    fn test_combine_different_algorithms_panics() {
+        let _ = Digest::from_image(b"a").combine(&Digest::blake3(b"b"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot combine a SHA-256 digest with a SHA-512 digest")]
+    fn test_combine_ordered_different_algorithms_panics() {
+        let _ = Digest::from_image(b"a").combine_ordered(&Digest::sha512(b"b"));
+    }
+
+    #[test]
+    fn test_ct_eq() {
+        let a = Digest::from_image(b"Hello");
+        let b = Digest::from_image(b"Hello");
+        let mut data: [u8; Digest::DIGEST_SIZE] = *a.data();
+        data[Digest::DIGEST_SIZE - 1] ^= 1;
+        let c = Digest::from_data(data);
+        assert!(a.ct_eq(&b));
+        assert_eq!(a.ct_eq(&b), a == b);
+        assert!(!a.ct_eq(&c));
+        assert_eq!(a.ct_eq(&c), a == c);
+        assert!(a.validate(b"Hello"));
+        assert!(!a.validate(b"World"));
+    }
+
+    fn merkle_leaves(count: usize) -> Vec<Digest> {
+        (0..count).map(|i| Digest::from_image([i as u8])).collect()
+    }
+
+    #[test]
+    fn test_merkle_root() {
+        let leaves = merkle_leaves(4);
+        let leaf = |d: &Digest| Digest::from_image_parts(&[&[0x00], d.data()]);
+        let node = |l: &Digest, r: &Digest| {
+            Digest::from_image_parts(&[&[0x01], l.data(), r.data()])
+        };
+        let expected = node(
+            &node(&leaf(&leaves[0]), &leaf(&leaves[1])),
+            &node(&leaf(&leaves[2]), &leaf(&leaves[3])),
+        );
+        assert_eq!(Digest::merkle_root(&leaves), expected);
+
+        // An odd last node is paired with itself.
+        let three = &leaves[..3];
+        let expected = node(
+            &node(&leaf(&leaves[0]), &leaf(&leaves[1])),
+            &node(&leaf(&leaves[2]), &leaf(&leaves[2])),
+        );
+        assert_eq!(Digest::merkle_root(three), expected);
+
+        assert_eq!(Digest::merkle_root(&leaves[..1]), leaf(&leaves[0]));
+        assert_eq!(Digest::merkle_root(&[]), Digest::from_image([]));
+    }
+
+    #[test]
+    fn test_merkle_proof() {
+        for count in 1..=9 {
+            let leaves = merkle_leaves(count);
+            let root = Digest::merkle_root(&leaves);
+            for (index, leaf) in leaves.iter().enumerate() {
+                let proof = Digest::merkle_proof(&leaves, index).unwrap();
+                assert!(Digest::verify_merkle_proof(leaf, &proof, &root, index, count));
+                // An index with extra high bits must not alias a real leaf.
+                let aliased = index + (1 << proof.len());
+                assert!(!Digest::verify_merkle_proof(leaf, &proof, &root, aliased, count));
+                // Nor may a proof for a different tree size.
+                assert!(!Digest::verify_merkle_proof(leaf, &proof, &root, index, count * 2 + 1));
+            }
+        }
+
+        // The last leaf of an odd tree is its own sibling, so it would also
+        // verify one past the end without the leaf count.
+        let leaves = merkle_leaves(3);
+        let root = Digest::merkle_root(&leaves);
+        let proof = Digest::merkle_proof(&leaves, 2).unwrap();
+        assert!(Digest::verify_merkle_proof(&leaves[2], &proof, &root, 2, 3));
+        assert!(!Digest::verify_merkle_proof(&leaves[2], &proof, &root, 3, 3));
+
+        let leaves = merkle_leaves(4);
+        let root = Digest::merkle_root(&leaves);
+        let mut proof = Digest::merkle_proof(&leaves, 2).unwrap();
+        assert_eq!(proof.len(), 2);
+        assert!(!Digest::verify_merkle_proof(&leaves[1], &proof, &root, 2, 4));
+        assert!(!Digest::verify_merkle_proof(&leaves[2], &proof, &root, 3, 4));
+        assert!(!Digest::verify_merkle_proof(&leaves[2], &proof[..1], &root, 2, 4));
+        proof[1] = Digest::from_image(b"tampered");
+        assert!(!Digest::verify_merkle_proof(&leaves[2], &proof, &root, 2, 4));
+
+        assert_eq!(Digest::merkle_proof(&leaves, 4), None);
+        assert_eq!(Digest::merkle_proof(&[], 0), None);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_merkle_root_parallel() {
+        for count in [0, 1, 2, 3, 7, 8, 1000, 4097] {
+            let leaves = merkle_leaves(count);
+            assert_eq!(Digest::merkle_root_parallel(&leaves), Digest::merkle_root(&leaves));
+        }
+
+        // The root doesn't depend on the number of threads.
+        let leaves = merkle_leaves(5000);
+        let root = Digest::merkle_root(&leaves);
+        for threads in [1, 3] {
+            let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+            assert_eq!(pool.install(|| Digest::merkle_root_parallel(&leaves)), root);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_from_cbor_encodable() {
+        use crate::{ DigestProvider, ARID };
+
+        let arid = ARID::new();
+        let digest = Digest::from_cbor_encodable(&arid);
+        assert_eq!(digest, Digest::from_image(arid.to_cbor_data()));
+        assert_eq!(digest, *arid.digest());
+        assert_ne!(digest, Digest::from_image(arid.data()));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_from_reader() {
+        let data: Vec<u8> = (0..20_000).map(|i| (i % 251) as u8).collect();
+        let digest = Digest::from_reader(std::io::Cursor::new(&data)).unwrap();
+        assert_eq!(digest, Digest::from_image(&data));
+        assert_eq!(Digest::from_reader(std::io::empty()).unwrap(), Digest::from_image([]));
+
+        struct FailingReader;
+        impl Read for FailingReader {
+            fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::new(io::ErrorKind::BrokenPipe, "stream closed"))
+            }
+        }
+        let err = Digest::from_reader(FailingReader).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+    }
+
+    #[test]
+    fn test_bech32() {
+        let digest = Digest::from_image(b"hello world");
+        let encoded = digest.bech32("digest").unwrap();
+        assert_eq!(encoded, "digest1h9xj0wvnf5lq3ffw2tta5ldtltzgfmlr0ffcpm5s3rm6ech0eh5s2vp8wl");
+        assert_eq!(Digest::from_bech32(&encoded, "digest").unwrap(), digest);
+        assert_eq!(Digest::from_bech32(&encoded.to_uppercase(), "digest").unwrap(), digest);
+        assert!(Digest::from_bech32(&encoded, "arid").is_err());
+        assert!(Digest::from_bech32(&encoded.replacen("h9xj", "h9xk", 1), "digest").is_err());
+        assert!(digest.bech32("").is_err());
+    }
 }
@@ -0,0 +1,75 @@
+use sha2::{ Digest as _, Sha256 };
+
+use crate::Digest;
+
+/// An incremental SHA-256 hasher that produces a `Digest`.
+///
+/// Data may be fed to the hasher in any number of chunks, and the resulting
+/// `Digest` is identical to the one produced by `Digest::from_image` over the
+/// concatenation of all the chunks.
+#[derive(Clone, Default)]
+pub struct DigestHasher(Sha256);
+
+impl DigestHasher {
+    /// Create a new hasher with no data.
+    pub fn new() -> Self {
+        Self(Sha256::new())
+    }
+
+    /// Feed more data into the hasher.
+    pub fn update(&mut self, data: impl AsRef<[u8]>) {
+        self.0.update(data.as_ref());
+    }
+
+    /// Consume the hasher and return the `Digest` of all the data fed to it.
+    pub fn finalize(self) -> Digest {
+        Digest::from_data(self.0.finalize().into())
+    }
+}
+
+impl core::fmt::Debug for DigestHasher {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "DigestHasher")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{ BufReader, Read };
+    use dcbor::prelude::*;
+
+    use super::DigestHasher;
+    use crate::Digest;
+
+    fn make_data(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[test]
+    fn test_empty() {
+        assert_eq!(DigestHasher::new().finalize(), Digest::from_image([]));
+    }
+
+    #[test]
+    fn test_chunked_reader() {
+        const CHUNK_SIZE: usize = 64 * 1024;
+        let data = make_data(CHUNK_SIZE * 5 + 1234);
+        let mut reader = BufReader::new(data.as_slice());
+        let mut hasher = DigestHasher::new();
+        let mut buf = vec![0u8; CHUNK_SIZE];
+        loop {
+            let n = reader.read(&mut buf).unwrap();
+            if n == 0 {
+                break;
+            }
+            hasher.update(&buf[..n]);
+        }
+        let digest = hasher.finalize();
+        let one_shot = Digest::from_image(&data);
+        assert_eq!(digest, one_shot);
+
+        let cbor_data = digest.to_cbor_data();
+        assert_eq!(cbor_data, one_shot.to_cbor_data());
+        assert_eq!(Digest::from_tagged_cbor_data(cbor_data).unwrap(), one_shot);
+    }
+}
@@ -1,14 +1,44 @@
-use std::{ rc::Rc, borrow::Cow };
+use alloc::{ borrow::Cow, rc::Rc, string::String };
 
-use crate::digest::Digest;
+use crate::{
+    digest::Digest,
+    AgreementPublicKey,
+    ECPublicKey,
+    ECUncompressedPublicKey,
+    KdfParams,
+    Nonce,
+    PublicKeyBase,
+    Salt,
+    SealedMessage,
+    Signature,
+    SigningPublicKey,
+    ARID,
+    UUID,
+};
+#[cfg(feature = "std")]
+use crate::URI;
 
 /// A type that can provide a single unique digest that characterizes its contents.
 ///
 /// Returns a Cow<'_, Digest> to avoid unnecessary cloning. If the Digest is already
 /// owned by the implementor, it can be returned by borrowing. If it doesn't
 /// exist yet, it can be created and returned by owning.
+///
+/// Unless noted otherwise, components implement this by hashing their tagged
+/// CBOR serialization, so equal components always have equal digests. Types
+/// that hold secret key material deliberately don't implement it.
+///
+/// `EncryptedMessage` and `Compressed` are the exceptions: they return the
+/// digest of the content they wrap, which they carry in their AAD and digest
+/// fields. This lets an encrypted or compressed element keep the digest of
+/// its plaintext, and it panics when no digest was recorded.
 pub trait DigestProvider {
     fn digest(&self) -> Cow<'_, Digest>;
+
+    /// The digest as a hexadecimal string, for logging.
+    fn digest_provider_hex(&self) -> String {
+        self.digest().hex()
+    }
 }
 
 impl DigestProvider for &[u8] {
@@ -22,3 +52,84 @@ impl<T> DigestProvider for Rc<T> where T: DigestProvider {
         self.as_ref().digest()
     }
 }
+
+macro_rules! impl_digest_provider_via_tagged_cbor {
+    ($($type:ty),* $(,)?) => {
+        $(
+            impl DigestProvider for $type {
+                fn digest(&self) -> Cow<'_, Digest> {
+                    Cow::Owned(Digest::from_cbor_encodable(self))
+                }
+            }
+        )*
+    };
+}
+
+impl_digest_provider_via_tagged_cbor!(
+    AgreementPublicKey,
+    ARID,
+    ECPublicKey,
+    ECUncompressedPublicKey,
+    KdfParams,
+    Nonce,
+    PublicKeyBase,
+    Salt,
+    SealedMessage,
+    Signature,
+    SigningPublicKey,
+    UUID,
+);
+
+#[cfg(feature = "std")]
+impl_digest_provider_via_tagged_cbor!(URI);
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use std::collections::HashMap;
+    use dcbor::prelude::*;
+
+    use super::DigestProvider;
+    use crate::{ ECKey, ECPrivateKey, Signature, SigningPrivateKey, Signer, ARID };
+
+    #[test]
+    fn test_tagged_cbor_digest() {
+        let arid = ARID::new();
+        let digest = arid.digest();
+        assert_eq!(*digest, crate::Digest::from_image(arid.tagged_cbor().to_cbor_data()));
+        assert_eq!(digest, arid.clone().digest());
+        assert_ne!(digest, ARID::new().digest());
+        assert_eq!(arid.digest_provider_hex(), digest.hex());
+
+        let private_key = ECPrivateKey::new();
+        let public_key = private_key.public_key();
+        assert_eq!(public_key.digest(), public_key.clone().digest());
+        assert_ne!(public_key.digest(), ECPrivateKey::new().public_key().digest());
+
+        // Changing any field of a multi-field component changes the digest.
+        let sig = [7u8; 64];
+        let a = Signature::ecdsa_recoverable_from_data(sig, 0);
+        let b = Signature::ecdsa_recoverable_from_data(sig, 1);
+        let mut other_sig = sig;
+        other_sig[0] ^= 1;
+        let c = Signature::ecdsa_recoverable_from_data(other_sig, 0);
+        assert_eq!(a.digest(), Signature::ecdsa_recoverable_from_data(sig, 0).digest());
+        assert_ne!(a.digest(), b.digest());
+        assert_ne!(a.digest(), c.digest());
+    }
+
+    #[test]
+    fn test_digest_as_map_key() {
+        let signer = SigningPrivateKey::new_schnorr(ECPrivateKey::new());
+        let public_key = signer.public_key();
+        let signature = signer.sign(&b"message").unwrap();
+        let arid = ARID::new();
+
+        let mut map = HashMap::new();
+        map.insert(public_key.digest().into_owned(), "public key");
+        map.insert(signature.digest().into_owned(), "signature");
+        map.insert(arid.digest().into_owned(), "arid");
+        assert_eq!(map[&public_key.digest()], "public key");
+        assert_eq!(map[&signature.digest()], "signature");
+        assert_eq!(map[&arid.clone().digest()], "arid");
+    }
+}
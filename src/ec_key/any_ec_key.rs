@@ -0,0 +1,147 @@
+use alloc::{ vec, vec::Vec };
+use bc_ur::prelude::*;
+use anyhow::{ Error, Result };
+
+use crate::{ tags, ECKey, ECKeyBase, ECPrivateKey, ECPublicKey };
+
+/// An elliptic curve key that may be either private or public.
+///
+/// This lets keys be stored uniformly and downgraded to their public key on
+/// demand. It is encoded in CBOR exactly like the key it holds: a tagged
+/// `eckey` map in which the private case sets key `2` to `true`.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum AnyECKey {
+    Private(ECPrivateKey),
+    Public(ECPublicKey),
+}
+
+impl AnyECKey {
+    /// Returns `true` if this is a private key.
+    pub fn is_private(&self) -> bool {
+        matches!(self, Self::Private(_))
+    }
+
+    /// Returns the private key, if this is one.
+    pub fn private_key(&self) -> Option<&ECPrivateKey> {
+        match self {
+            Self::Private(key) => Some(key),
+            Self::Public(_) => None,
+        }
+    }
+
+    /// Returns the public key, deriving it if this is a private key.
+    pub fn public_key(&self) -> ECPublicKey {
+        match self {
+            Self::Private(key) => key.public_key(),
+            Self::Public(key) => key.clone(),
+        }
+    }
+
+    /// Returns the public form of this key, discarding any private key.
+    pub fn to_public(&self) -> Self {
+        Self::Public(self.public_key())
+    }
+}
+
+impl From<ECPrivateKey> for AnyECKey {
+    fn from(key: ECPrivateKey) -> Self {
+        Self::Private(key)
+    }
+}
+
+impl From<ECPublicKey> for AnyECKey {
+    fn from(key: ECPublicKey) -> Self {
+        Self::Public(key)
+    }
+}
+
+impl CBORTagged for AnyECKey {
+    fn cbor_tags() -> Vec<Tag> {
+        vec![tags::EC_KEY, tags::EC_KEY_V1]
+    }
+}
+
+impl From<AnyECKey> for CBOR {
+    fn from(value: AnyECKey) -> Self {
+        value.tagged_cbor()
+    }
+}
+
+impl CBORTaggedEncodable for AnyECKey {
+    fn untagged_cbor(&self) -> CBOR {
+        match self {
+            Self::Private(key) => key.untagged_cbor(),
+            Self::Public(key) => key.untagged_cbor(),
+        }
+    }
+}
+
+impl TryFrom<CBOR> for AnyECKey {
+    type Error = Error;
+
+    fn try_from(cbor: CBOR) -> Result<Self, Self::Error> {
+        Self::from_tagged_cbor(cbor)
+    }
+}
+
+impl CBORTaggedDecodable for AnyECKey {
+    fn from_tagged_cbor(cbor: CBOR) -> Result<Self> {
+        crate::component_error::decode_tagged(cbor, "EC key")
+    }
+
+    fn from_untagged_cbor(cbor: CBOR) -> Result<Self> {
+        let map = cbor.try_into_map()?;
+        let is_private = map.get::<i32, bool>(2).unwrap_or(false);
+        let data = map.extract::<i32, CBOR>(3)?.try_into_byte_string()?;
+        if is_private {
+            Ok(Self::Private(ECPrivateKey::from_data_ref(data)?))
+        } else {
+            Ok(Self::Public(ECPublicKey::from_data_ref(data)?))
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use bc_ur::prelude::*;
+
+    use crate::{ AnyECKey, ECKey, ECKeyBase, ECPrivateKey };
+
+    #[test]
+    fn test_public_key() {
+        let private_key = ECPrivateKey::new();
+        let key = AnyECKey::from(private_key.clone());
+        assert!(key.is_private());
+        assert_eq!(key.private_key(), Some(&private_key));
+        assert_eq!(key.public_key(), private_key.public_key());
+
+        let public = key.to_public();
+        assert!(!public.is_private());
+        assert_eq!(public.private_key(), None);
+        assert_eq!(public, AnyECKey::Public(private_key.public_key()));
+        assert_eq!(public.public_key(), key.public_key());
+    }
+
+    #[test]
+    fn test_cbor() {
+        let private_key = ECPrivateKey::new();
+        let private = AnyECKey::from(private_key.clone());
+        let public = AnyECKey::from(private_key.public_key());
+
+        // Each case encodes exactly like the key it holds.
+        assert_eq!(private.tagged_cbor(), private_key.tagged_cbor());
+        assert_eq!(public.tagged_cbor(), private_key.public_key().tagged_cbor());
+
+        for key in [private, public] {
+            let decoded = AnyECKey::from_tagged_cbor_data(key.tagged_cbor_data()).unwrap();
+            assert_eq!(decoded, key);
+            assert_eq!(decoded.is_private(), key.is_private());
+        }
+
+        // Private key bytes without the private flag aren't a public key.
+        let mut map = Map::new();
+        map.insert(3, CBOR::to_byte_string(private_key.data()));
+        assert!(AnyECKey::from_untagged_cbor(map.into()).is_err());
+        assert!(AnyECKey::try_from(CBOR::from(1)).is_err());
+    }
+}
@@ -0,0 +1,10 @@
+/// The kind of Bitcoin address to derive from a public key.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum BitcoinAddressKind {
+    /// Legacy pay-to-public-key-hash, encoded in Base58Check.
+    P2PKH,
+    /// Native SegWit version 0 pay-to-witness-public-key-hash, encoded in
+    /// bech32 (BIP173).
+    #[default]
+    P2WPKH,
+}
@@ -1,3 +1,4 @@
+use alloc::string::String;
 use bc_ur::UREncodable;
 use anyhow::Result;
 
@@ -5,8 +6,8 @@ use crate::ECPublicKey;
 
 /// A type that represents an elliptic curve digital signature algorithm (ECDSA) key.
 pub trait ECKeyBase:
-    std::fmt::Display +
-    std::fmt::Debug +
+    core::fmt::Display +
+    core::fmt::Debug +
     Clone +
     PartialEq + Eq +
     core::hash::Hash
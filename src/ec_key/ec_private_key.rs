@@ -1,15 +1,25 @@
-use anyhow::{bail, Result};
+use alloc::{ string::String, vec, vec::Vec };
+use anyhow::{anyhow, bail, Result};
 use bc_rand::RandomNumberGenerator;
 use bc_ur::prelude::*;
+use secp256k1::{KeyPair, Message, Scalar, Secp256k1, SecretKey};
+use subtle::ConstantTimeEq;
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
-use crate::{ECKeyBase, ECKey, tags, SchnorrPublicKey, ECPublicKey};
+use crate::{
+    ComponentError, ECKeyBase, ECKey, HDKey, tags, tagged_sha256, Network, SchnorrPublicKey,
+    ECPublicKey,
+};
 
 /// An elliptic curve digital signature algorithm (ECDSA) private key.
-#[derive(Clone, PartialEq, Eq, Hash)]
+///
+/// The key material is zeroized when the key is dropped.
+#[derive(Clone, PartialEq, Eq, Hash, Zeroize, ZeroizeOnDrop)]
 pub struct ECPrivateKey([u8; Self::KEY_SIZE]);
 
 impl ECPrivateKey {
     /// Creates a new random ECDSA private key.
+    #[cfg(feature = "std")]
     pub fn new() -> Self {
         let mut rng = bc_rand::SecureRandomNumberGenerator;
         Self::new_using(&mut rng)
@@ -31,7 +41,7 @@ impl ECPrivateKey {
     pub fn from_data_ref(data: impl AsRef<[u8]>) -> Result<Self> {
         let data = data.as_ref();
         if data.len() != Self::KEY_SIZE {
-            bail!("Invalid EC private key size");
+            bail!(ComponentError::InvalidLength { component: "EC private key" });
         }
         let mut arr = [0u8; Self::KEY_SIZE];
         arr.copy_from_slice(data);
@@ -42,6 +52,22 @@ impl ECPrivateKey {
     pub fn derive_from_key_material(key_material: impl AsRef<[u8]>) -> Self {
         Self::from_data(bc_crypto::x25519_derive_signing_private_key(key_material))
     }
+
+    /// Derives the BIP32 master private key from an English BIP39 mnemonic
+    /// and passphrase, which may be empty.
+    ///
+    /// See `HDKey::from_bip39`, which also keeps the master chain code for
+    /// deriving child keys.
+    pub fn from_bip39(mnemonic: &str, passphrase: &str) -> Result<Self> {
+        Ok(HDKey::from_bip39(mnemonic, passphrase)?.private_key().unwrap().clone())
+    }
+
+    /// Compare two keys in constant time.
+    ///
+    /// Unlike `==`, this does not short-circuit on the first differing byte.
+    pub fn ct_eq(&self, other: &ECPrivateKey) -> bool {
+        self.0.ct_eq(&other.0).into()
+    }
 }
 
 impl ECPrivateKey {
@@ -55,6 +81,40 @@ impl ECPrivateKey {
         bc_crypto::ecdsa_sign(&self.0, message.as_ref())
     }
 
+    /// ECDSA signs the given message using this ECDSA private key, returning
+    /// the compact signature and the recovery id needed to recover the public
+    /// key from the signature.
+    ///
+    /// The message is hashed exactly as by `ecdsa_sign`, so the compact
+    /// signature is identical to the one it produces.
+    pub fn ecdsa_sign_recoverable(
+        &self,
+        message: impl AsRef<[u8]>,
+    ) -> ([u8; bc_crypto::ECDSA_SIGNATURE_SIZE], u8) {
+        let secp = Secp256k1::signing_only();
+        let sk = SecretKey::from_slice(&self.0).expect("32 bytes, within curve order");
+        let hash = bc_crypto::hash::double_sha256(message.as_ref());
+        let msg = Message::from_slice(&hash).unwrap();
+        let (recovery_id, sig) = secp.sign_ecdsa_recoverable(&msg, &sk).serialize_compact();
+        (sig, recovery_id.to_i32() as u8)
+    }
+
+    /// Adds the given tweak to this key, giving `d' = d + t` modulo the curve
+    /// order, whose public key is the public key tweaked by `tweak_add`.
+    ///
+    /// Returns an error if the tweak is not less than the curve order or the
+    /// result is zero.
+    pub fn tweak_add(&self, tweak: &[u8; 32]) -> Result<Self> {
+        let Ok(tweak) = Scalar::from_be_bytes(*tweak) else {
+            bail!("Tweak is out of range");
+        };
+        let key = SecretKey::from_slice(&self.0)?;
+        let Ok(tweaked) = key.add_tweak(&tweak) else {
+            bail!("Tweaked private key is invalid");
+        };
+        Ok(Self::from_data(tweaked.secret_bytes()))
+    }
+
     /// Schnorr signs the given message using this ECDSA private key, the given
     /// tag, and the given random number generator.
     pub fn schnorr_sign_using(
@@ -67,6 +127,7 @@ impl ECPrivateKey {
     }
 
     /// Schnorr signs the given message using this ECDSA private key and the given tag.
+    #[cfg(feature = "std")]
     pub fn schnorr_sign(
         &self,
         message: impl AsRef<[u8]>,
@@ -75,6 +136,73 @@ impl ECPrivateKey {
         let mut rng = bc_rand::SecureRandomNumberGenerator;
         self.schnorr_sign_using(message, tag, &mut rng)
     }
+
+    /// Schnorr signs the given message using this ECDSA private key and the
+    /// given tag, without auxiliary randomness.
+    ///
+    /// The BIP-340 nonce is then derived from the key and message alone, so
+    /// signing the same message with the same key and tag always gives the
+    /// same signature.
+    pub fn schnorr_sign_deterministic(
+        &self,
+        message: impl AsRef<[u8]>,
+        tag: impl AsRef<[u8]>,
+    ) -> [u8; bc_crypto::SCHNORR_SIGNATURE_SIZE] {
+        let secp = Secp256k1::signing_only();
+        let sk = SecretKey::from_slice(&self.0).expect("32 bytes, within curve order");
+        let keypair = KeyPair::from_secret_key(&secp, &sk);
+        let msg = Message::from_slice(&tagged_sha256(message.as_ref(), tag.as_ref())).unwrap();
+        *secp.sign_schnorr_no_aux_rand(&msg, &keypair).as_ref()
+    }
+}
+
+impl ECPrivateKey {
+    /// Encodes this key in Wallet Import Format (WIF) for the given network.
+    ///
+    /// `compressed` marks that the key's public key should be used in
+    /// compressed form, which is almost always what modern wallets expect.
+    pub fn to_wif(&self, network: Network, compressed: bool) -> String {
+        let mut payload = Vec::with_capacity(34);
+        payload.push(network.wif_version());
+        payload.extend_from_slice(&self.0);
+        if compressed {
+            payload.push(0x01);
+        }
+        let wif = bs58::encode(&payload).with_check().into_string();
+        payload.zeroize();
+        wif
+    }
+
+    /// Decodes a key in Wallet Import Format (WIF), returning the key, its
+    /// network, and whether it is marked as compressed.
+    ///
+    /// Returns an error if the checksum doesn't match, or the version byte,
+    /// length, compression flag, or key itself is invalid.
+    pub fn from_wif(wif: &str) -> Result<(Self, Network, bool)> {
+        let mut payload = bs58::decode(wif.trim())
+            .with_check(None)
+            .into_vec()
+            .map_err(|e| anyhow!("Invalid WIF: {}", e))?;
+        let result = Self::from_wif_payload(&payload);
+        payload.zeroize();
+        result
+    }
+
+    fn from_wif_payload(payload: &[u8]) -> Result<(Self, Network, bool)> {
+        let compressed = match payload.len() {
+            33 => false,
+            34 if payload[33] == 0x01 => true,
+            34 => bail!("Invalid WIF compression flag"),
+            _ => bail!("Invalid WIF length"),
+        };
+        let Some(network) = Network::from_wif_version(payload[0]) else {
+            bail!("Invalid WIF version");
+        };
+        if SecretKey::from_slice(&payload[1..33]).is_err() {
+            bail!("Invalid WIF private key");
+        }
+        Ok((Self::from_data_ref(&payload[1..33])?, network, compressed))
+    }
 }
 
 impl From<[u8; 32]> for ECPrivateKey {
@@ -89,18 +217,19 @@ impl AsRef<[u8]> for ECPrivateKey {
     }
 }
 
-impl std::fmt::Display for ECPrivateKey {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for ECPrivateKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", self.hex())
     }
 }
 
-impl std::fmt::Debug for ECPrivateKey {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Debug for ECPrivateKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "ECPrivateKey({})", self.hex())
     }
 }
 
+#[cfg(feature = "std")]
 impl Default for ECPrivateKey {
     fn default() -> Self {
         Self::new()
@@ -125,7 +254,7 @@ impl ECKeyBase for ECPrivateKey {
     fn from_data_ref(data: impl AsRef<[u8]>) -> Result<Self> where Self: Sized {
         let data = data.as_ref();
         if data.len() != Self::KEY_SIZE {
-            bail!("Invalid EC private key size");
+            bail!(ComponentError::InvalidLength { component: "EC private key" });
         }
         let mut key = [0u8; Self::KEY_SIZE];
         key.copy_from_slice(data);
@@ -163,3 +292,68 @@ impl CBORTaggedEncodable for ECPrivateKey {
         m.into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use hex_literal::hex;
+
+    use crate::{ECKey, ECPrivateKey, Network};
+
+    const KEY: [u8; 32] = hex!("0c28fca386c7a227600b2fe50b7cae11ec86d3bf1fbe471be89827e19d72aa1d");
+
+    #[test]
+    fn test_wif() {
+        let key = ECPrivateKey::from_data(KEY);
+        for (network, compressed, wif) in [
+            (Network::Mainnet, false, "5HueCGU8rMjxEXxiPuD5BDku4MkFqeZyd4dZ1jvhTVqvbTLvyTJ"),
+            (Network::Mainnet, true, "KwdMAjGmerYanjeui5SHS7JkmpZvVipYvB2LJGU1ZxJwYvP98617"),
+            (Network::Testnet, false, "91gGn1HgSap6CbU12F6z3pJri26xzp7Ay1VW6NHCoEayNXwRpu2"),
+            (Network::Testnet, true, "cMzLdeGd5vEqxB8B6VFQoRopQ3sLAAvEzDAoQgvX54xwofSWj1fx"),
+        ] {
+            assert_eq!(key.to_wif(network, compressed), wif);
+            assert_eq!(ECPrivateKey::from_wif(wif).unwrap(), (key.clone(), network, compressed));
+        }
+    }
+
+    #[test]
+    fn test_tweak_add() {
+        let private_key = ECPrivateKey::from_data(KEY);
+        let public_key = private_key.public_key();
+        let tweak = hex!("a1b2c3d4e5f60718293a4b5c6d7e8f90a1b2c3d4e5f60718293a4b5c6d7e8f90");
+        let tweaked_private_key = private_key.tweak_add(&tweak).unwrap();
+        let tweaked_public_key = public_key.tweak_add(&tweak).unwrap();
+        assert_ne!(tweaked_public_key, public_key);
+        assert_eq!(tweaked_private_key.public_key(), tweaked_public_key);
+
+        // The curve order itself is out of range.
+        let order = hex!("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141");
+        assert_eq!(private_key.tweak_add(&order).unwrap_err().to_string(), "Tweak is out of range");
+        assert_eq!(public_key.tweak_add(&order).unwrap_err().to_string(), "Tweak is out of range");
+
+        // Tweaking by the negated key gives zero, or the point at infinity.
+        let negated = secp256k1::SecretKey::from_slice(&KEY).unwrap().negate().secret_bytes();
+        assert!(private_key.tweak_add(&negated).is_err());
+        assert!(public_key.tweak_add(&negated).is_err());
+    }
+
+    #[test]
+    fn test_invalid_wif() {
+        let wif = "KwdMAjGmerYanjeui5SHS7JkmpZvVipYvB2LJGU1ZxJwYvP98617";
+        let corrupted = wif.replacen('K', "L", 1);
+        let err = ECPrivateKey::from_wif(&corrupted).unwrap_err();
+        assert!(err.to_string().starts_with("Invalid WIF"), "{}", err);
+
+        let encode = |payload: &[u8]| bs58::encode(payload).with_check().into_string();
+        let error = |payload: &[u8]| ECPrivateKey::from_wif(&encode(payload)).unwrap_err().to_string();
+        let mut payload = vec![0x00];
+        payload.extend_from_slice(&KEY);
+        assert_eq!(error(&payload), "Invalid WIF version");
+        payload[0] = 0x80;
+        assert_eq!(error(&payload[..32]), "Invalid WIF length");
+        payload.push(0x02);
+        assert_eq!(error(&payload), "Invalid WIF compression flag");
+        let mut zero_key = vec![0x80];
+        zero_key.extend_from_slice(&[0u8; 32]);
+        assert_eq!(error(&zero_key), "Invalid WIF private key");
+    }
+}
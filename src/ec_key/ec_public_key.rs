@@ -1,8 +1,19 @@
+use alloc::{ format, string::String, vec, vec::Vec };
 use anyhow::{bail, Result};
 use bc_crypto::ECDSA_SIGNATURE_SIZE;
 use bc_ur::prelude::*;
+use secp256k1::{
+    ecdsa::{RecoverableSignature, RecoveryId},
+    Message, PublicKey, Scalar, Secp256k1,
+};
 
-use crate::{ECKeyBase, ECKey, ECPublicKeyBase, tags};
+use ripemd::{Digest as _, Ripemd160};
+use sha3::Keccak256;
+
+use crate::{
+    BitcoinAddressKind, ComponentError, ECKeyBase, ECKey, ECPublicKeyBase, ECUncompressedPublicKey,
+    Network, tags,
+};
 
 /// A compressed elliptic curve digital signature algorithm (ECDSA) compressed public key.
 #[derive(Clone, PartialEq, Eq, Hash)]
@@ -15,6 +26,89 @@ impl ECPublicKey {
     }
 }
 
+impl ECPublicKey {
+    /// Restores an ECDSA public key from a SEC1-encoded point, either the
+    /// 33-byte compressed form or the 65-byte uncompressed form.
+    ///
+    /// Returns an error if the data is any other length or the point is not
+    /// on the curve.
+    pub fn from_sec1(data: impl AsRef<[u8]>) -> Result<Self> {
+        let data = data.as_ref();
+        match (data.len(), data.first()) {
+            (33, Some(0x02 | 0x03)) | (65, Some(0x04)) => {}
+            _ => bail!("Invalid SEC1 public key encoding"),
+        }
+        let Ok(key) = PublicKey::from_slice(data) else {
+            bail!("SEC1 public key is not on the curve");
+        };
+        Ok(Self::from_data(key.serialize()))
+    }
+
+    /// Returns the 33-byte compressed SEC1 encoding of this key.
+    pub fn compressed(&self) -> [u8; Self::KEY_SIZE] {
+        self.0
+    }
+
+    /// Returns the 65-byte uncompressed SEC1 encoding of this key.
+    pub fn uncompressed(&self) -> [u8; ECUncompressedPublicKey::KEY_SIZE] {
+        bc_crypto::ecdsa_decompress_public_key(&self.0)
+    }
+}
+
+impl ECPublicKey {
+    /// Returns the `HASH160` of this key: RIPEMD-160 of SHA-256 of its
+    /// compressed encoding.
+    pub fn hash160(&self) -> [u8; 20] {
+        Ripemd160::digest(bc_crypto::hash::sha256(self.0)).into()
+    }
+
+    /// Returns the Bitcoin address of the given kind for this key on the
+    /// given network.
+    ///
+    /// Both kinds commit to the compressed encoding of the key.
+    pub fn bitcoin_address(&self, network: Network, kind: BitcoinAddressKind) -> String {
+        let hash = self.hash160();
+        match kind {
+            BitcoinAddressKind::P2PKH => {
+                let payload = [&[network.p2pkh_version()][..], &hash].concat();
+                bs58::encode(payload).with_check().into_string()
+            }
+            BitcoinAddressKind::P2WPKH => {
+                bech32::segwit::encode_v0(network.bech32_hrp(), &hash)
+                    .expect("a 20-byte witness program is always encodable")
+            }
+        }
+    }
+
+    /// Returns the Ethereum address of this key: the last 20 bytes of the
+    /// Keccak-256 hash of its uncompressed encoding, without the `04` prefix.
+    ///
+    /// The address is `0x`-prefixed hex with EIP-55 mixed-case checksum.
+    ///
+    /// <https://eips.ethereum.org/EIPS/eip-55>
+    pub fn ethereum_address(&self) -> String {
+        let hash = Keccak256::digest(&self.uncompressed()[1..]);
+        eip55_checksummed(hash[12..].try_into().unwrap())
+    }
+}
+
+/// Formats an Ethereum address with the EIP-55 checksum: each hex letter is
+/// uppercased if the corresponding nibble of the Keccak-256 hash of the
+/// lowercase hex is at least 8.
+fn eip55_checksummed(address: &[u8; 20]) -> String {
+    let hex = hex::encode(address);
+    let hash = Keccak256::digest(hex.as_bytes());
+    let checksummed: String = hex
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let nibble = (hash[i / 2] >> if i % 2 == 0 { 4 } else { 0 }) & 0x0f;
+            if nibble >= 8 { c.to_ascii_uppercase() } else { c }
+        })
+        .collect();
+    format!("0x{}", checksummed)
+}
+
 impl ECPublicKey {
     /// Verifies the given ECDSA signature for the given message using this ECDSA public key.
     pub fn verify<D>(&self, signature: &[u8; ECDSA_SIGNATURE_SIZE], message: D) -> bool
@@ -23,16 +117,77 @@ impl ECPublicKey {
     {
         bc_crypto::ecdsa_verify(&self.0, signature, message)
     }
+
+    /// Recovers the ECDSA public key that produced the given compact signature
+    /// and recovery id over the given message.
+    ///
+    /// The message is hashed with double SHA-256, as by
+    /// `ECPrivateKey::ecdsa_sign`. Use `ecdsa_recover_prehashed` for a
+    /// signature over a digest computed some other way, such as Ethereum's
+    /// Keccak-256 message hash.
+    ///
+    /// Returns `None` if the recovery id is out of range or no key can be
+    /// recovered.
+    pub fn ecdsa_recover<D>(
+        signature: &[u8; ECDSA_SIGNATURE_SIZE],
+        recovery_id: u8,
+        message: D,
+    ) -> Option<Self>
+    where
+        D: AsRef<[u8]>,
+    {
+        let digest = bc_crypto::hash::double_sha256(message.as_ref());
+        Self::ecdsa_recover_prehashed(signature, recovery_id, &digest)
+    }
+
+    /// Recovers the ECDSA public key that produced the given compact signature
+    /// and recovery id over the given 32-byte message digest, which is used
+    /// as is.
+    ///
+    /// Returns `None` if the recovery id is out of range or no key can be
+    /// recovered.
+    pub fn ecdsa_recover_prehashed(
+        signature: &[u8; ECDSA_SIGNATURE_SIZE],
+        recovery_id: u8,
+        digest: &[u8; 32],
+    ) -> Option<Self> {
+        let secp = Secp256k1::verification_only();
+        let recovery_id = RecoveryId::from_i32(recovery_id as i32).ok()?;
+        let sig = RecoverableSignature::from_compact(signature, recovery_id).ok()?;
+        let msg = Message::from_slice(digest).ok()?;
+        let key = secp.recover_ecdsa(&msg, &sig).ok()?;
+        Some(Self::from_data(key.serialize()))
+    }
+}
+
+impl ECPublicKey {
+    /// Adds the given tweak times the generator to this key, giving
+    /// `P' = P + t*G`, which is the public key of the private key tweaked by
+    /// `ECPrivateKey::tweak_add`.
+    ///
+    /// Returns an error if the tweak is not less than the curve order or the
+    /// result is the point at infinity.
+    pub fn tweak_add(&self, tweak: &[u8; 32]) -> Result<Self> {
+        let Ok(tweak) = Scalar::from_be_bytes(*tweak) else {
+            bail!("Tweak is out of range");
+        };
+        let secp = Secp256k1::verification_only();
+        let key = PublicKey::from_slice(&self.0)?;
+        let Ok(tweaked) = key.add_exp_tweak(&secp, &tweak) else {
+            bail!("Tweaked public key is invalid");
+        };
+        Ok(Self::from_data(tweaked.serialize()))
+    }
 }
 
-impl std::fmt::Display for ECPublicKey {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for ECPublicKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", self.hex())
     }
 }
 
-impl std::fmt::Debug for ECPublicKey {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Debug for ECPublicKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "ECPublicKey({})", self.hex())
     }
 }
@@ -43,7 +198,7 @@ impl ECKeyBase for ECPublicKey {
     fn from_data_ref(data: impl AsRef<[u8]>) -> Result<Self> where Self: Sized {
         let data = data.as_ref();
         if data.len() != Self::KEY_SIZE {
-            bail!("Invalid ECDSA public key size");
+            bail!(ComponentError::InvalidLength { component: "ECDSA public key" });
         }
         let mut key = [0u8; Self::KEY_SIZE];
         key.copy_from_slice(data);
@@ -62,8 +217,8 @@ impl ECKey for ECPublicKey {
 }
 
 impl ECPublicKeyBase for ECPublicKey {
-    fn uncompressed_public_key(&self) -> crate::ECUncompressedPublicKey {
-        bc_crypto::ecdsa_decompress_public_key(&self.0).into()
+    fn uncompressed_public_key(&self) -> ECUncompressedPublicKey {
+        self.uncompressed().into()
     }
 }
 
@@ -104,3 +259,86 @@ impl CBORTaggedEncodable for ECPublicKey {
         m.into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use hex_literal::hex;
+
+    use super::eip55_checksummed;
+    use crate::{BitcoinAddressKind, ECKey, ECPrivateKey, ECPublicKey, Network};
+
+    // The public key of the private key 1, the curve generator.
+    const COMPRESSED: [u8; 33] = hex!("0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798");
+    const UNCOMPRESSED: [u8; 65] = hex!("0479be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8");
+
+    #[test]
+    fn test_sec1_round_trip() {
+        let mut one = [0u8; 32];
+        one[31] = 1;
+        let key = ECPrivateKey::from_data(one).public_key();
+        assert_eq!(key.compressed(), COMPRESSED);
+        assert_eq!(key.uncompressed(), UNCOMPRESSED);
+
+        let from_uncompressed = ECPublicKey::from_sec1(key.uncompressed()).unwrap();
+        assert_eq!(from_uncompressed, key);
+        assert_eq!(from_uncompressed.compressed(), COMPRESSED);
+        assert_eq!(ECPublicKey::from_sec1(COMPRESSED).unwrap(), key);
+    }
+
+    #[test]
+    fn test_invalid_sec1() {
+        assert!(ECPublicKey::from_sec1(&UNCOMPRESSED[1..]).is_err());
+        assert!(ECPublicKey::from_sec1([]).is_err());
+
+        // The wrong prefix for the length.
+        let mut wrong_prefix = COMPRESSED;
+        wrong_prefix[0] = 0x04;
+        assert!(ECPublicKey::from_sec1(wrong_prefix).is_err());
+
+        // A point off the curve.
+        let mut off_curve = UNCOMPRESSED;
+        off_curve[64] ^= 1;
+        let err = ECPublicKey::from_sec1(off_curve).unwrap_err();
+        assert_eq!(err.to_string(), "SEC1 public key is not on the curve");
+    }
+
+    #[test]
+    fn test_addresses() {
+        let key = ECPublicKey::from_data(COMPRESSED);
+        assert_eq!(key.hash160(), hex!("751e76e8199196d454941c45d1b3a323f1433bd6"));
+
+        // BIP173 examples.
+        assert_eq!(
+            key.bitcoin_address(Network::Mainnet, BitcoinAddressKind::P2WPKH),
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"
+        );
+        assert_eq!(
+            key.bitcoin_address(Network::Testnet, BitcoinAddressKind::P2WPKH),
+            "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx"
+        );
+        assert_eq!(
+            key.bitcoin_address(Network::Mainnet, BitcoinAddressKind::P2PKH),
+            "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH"
+        );
+        assert_eq!(
+            key.bitcoin_address(Network::Testnet, BitcoinAddressKind::P2PKH),
+            "mrCDrCybB6J1vRfbwM5hemdJz73FwDBC8r"
+        );
+
+        assert_eq!(key.ethereum_address(), "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf");
+    }
+
+    #[test]
+    fn test_eip55() {
+        // EIP-55 examples.
+        for address in [
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+            "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+        ] {
+            let bytes: [u8; 20] = hex::decode(&address[2..]).unwrap().try_into().unwrap();
+            assert_eq!(eip55_checksummed(&bytes), address);
+        }
+    }
+}
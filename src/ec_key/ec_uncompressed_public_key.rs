@@ -1,7 +1,8 @@
+use alloc::{ vec, vec::Vec };
 use anyhow::{bail, Result};
 use bc_ur::prelude::*;
 
-use crate::{ECKeyBase, ECKey, tags, ECPublicKeyBase, ECPublicKey};
+use crate::{ECKeyBase, ECKey, tags, ComponentError, ECPublicKeyBase, ECPublicKey};
 
 /// A compressed elliptic curve digital signature algorithm (ECDSA) uncompressed public key.
 ///
@@ -15,14 +16,14 @@ impl ECUncompressedPublicKey {
     }
 }
 
-impl std::fmt::Display for ECUncompressedPublicKey {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for ECUncompressedPublicKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", self.hex())
     }
 }
 
-impl std::fmt::Debug for ECUncompressedPublicKey {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Debug for ECUncompressedPublicKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "ECUncompressedPublicKey({})", self.hex())
     }
 }
@@ -33,7 +34,7 @@ impl ECKeyBase for ECUncompressedPublicKey {
     fn from_data_ref(data: impl AsRef<[u8]>) -> Result<Self> where Self: Sized {
         let data = data.as_ref();
         if data.len() != Self::KEY_SIZE {
-            bail!("Invalid ECDSA uncompressed public key size");
+            bail!(ComponentError::InvalidLength { component: "ECDSA uncompressed public key" });
         }
         let mut key = [0u8; Self::KEY_SIZE];
         key.copy_from_slice(data);
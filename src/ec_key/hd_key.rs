@@ -0,0 +1,304 @@
+use alloc::vec::Vec;
+use anyhow::{anyhow, bail, Result};
+use bc_crypto::hash::hmac_sha512;
+use bip39::{Language, Mnemonic};
+use secp256k1::SecretKey;
+use zeroize::Zeroizing;
+
+use crate::{ECKey, ECKeyBase, ECPrivateKey, ECPublicKey};
+
+/// The index of a child key in BIP32 hierarchical derivation.
+///
+/// <https://github.com/bitcoin/bips/blob/master/bip-0032.mediawiki>
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ChildNumber {
+    /// A non-hardened child, derivable from the parent public key alone.
+    /// The index must be less than 2^31.
+    Normal(u32),
+    /// A hardened child, derivable only from the parent private key.
+    /// The index must be less than 2^31.
+    Hardened(u32),
+}
+
+impl ChildNumber {
+    const HARDENED_BIT: u32 = 1 << 31;
+
+    /// Returns `true` if this is a hardened child number.
+    pub fn is_hardened(&self) -> bool {
+        matches!(self, Self::Hardened(_))
+    }
+
+    /// The 32-bit index used in derivation, with the high bit set for
+    /// hardened children.
+    ///
+    /// Returns an error if the index doesn't fit in 31 bits.
+    fn raw_index(&self) -> Result<u32> {
+        let (Self::Normal(index) | Self::Hardened(index)) = *self;
+        if index >= Self::HARDENED_BIT {
+            bail!("BIP32 child index out of range");
+        }
+        Ok(if self.is_hardened() { index | Self::HARDENED_BIT } else { index })
+    }
+}
+
+impl From<u32> for ChildNumber {
+    /// Interprets a raw 32-bit index, which is hardened if its high bit is set.
+    fn from(index: u32) -> Self {
+        if index & Self::HARDENED_BIT != 0 {
+            Self::Hardened(index & !Self::HARDENED_BIT)
+        } else {
+            Self::Normal(index)
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum HDKeyMaterial {
+    Private(ECPrivateKey),
+    Public(ECPublicKey),
+}
+
+/// A BIP32 extended key: an `ECPrivateKey` or `ECPublicKey` together with a
+/// 32-byte chain code, from which child keys can be derived.
+///
+/// <https://github.com/bitcoin/bips/blob/master/bip-0032.mediawiki>
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HDKey {
+    key: HDKeyMaterial,
+    chain_code: [u8; Self::CHAIN_CODE_SIZE],
+}
+
+impl HDKey {
+    pub const CHAIN_CODE_SIZE: usize = 32;
+
+    /// Derives the master key from a seed, which should be between 16 and
+    /// 64 bytes long.
+    ///
+    /// Returns an error in the astronomically unlikely case that the seed
+    /// yields an invalid key.
+    pub fn new_master(seed: impl AsRef<[u8]>) -> Result<Self> {
+        let i = hmac_sha512(b"Bitcoin seed", seed);
+        let (il, ir) = i.split_at(32);
+        if SecretKey::from_slice(il).is_err() {
+            bail!("Seed yields an invalid BIP32 master key");
+        }
+        Ok(Self::from_private_key(
+            ECPrivateKey::from_data_ref(il)?,
+            ir.try_into().unwrap(),
+        ))
+    }
+
+    /// Derives the master key from an English BIP39 mnemonic and passphrase,
+    /// which may be empty.
+    ///
+    /// The mnemonic's words and checksum are validated, and the 64-byte
+    /// seed is derived with PBKDF2-HMAC-SHA512 (2048 rounds, salt
+    /// `"mnemonic" || passphrase`), both after NFKD normalization.
+    ///
+    /// Returns an error if a word is not in the BIP39 English word list, the
+    /// number of words is wrong, or the checksum doesn't match.
+    ///
+    /// <https://github.com/bitcoin/bips/blob/master/bip-0039.mediawiki>
+    pub fn from_bip39(mnemonic: &str, passphrase: &str) -> Result<Self> {
+        let mnemonic = Mnemonic::parse_in(Language::English, mnemonic)
+            .map_err(|e| anyhow!("Invalid BIP39 mnemonic: {}", e))?;
+        let seed = Zeroizing::new(mnemonic.to_seed(passphrase));
+        Self::new_master(seed.as_slice())
+    }
+
+    /// Creates an extended private key from a private key and chain code.
+    pub fn from_private_key(key: ECPrivateKey, chain_code: [u8; Self::CHAIN_CODE_SIZE]) -> Self {
+        Self { key: HDKeyMaterial::Private(key), chain_code }
+    }
+
+    /// Creates an extended public key from a public key and chain code.
+    pub fn from_public_key(key: ECPublicKey, chain_code: [u8; Self::CHAIN_CODE_SIZE]) -> Self {
+        Self { key: HDKeyMaterial::Public(key), chain_code }
+    }
+
+    /// Returns the private key, if this is an extended private key.
+    pub fn private_key(&self) -> Option<&ECPrivateKey> {
+        match &self.key {
+            HDKeyMaterial::Private(key) => Some(key),
+            HDKeyMaterial::Public(_) => None,
+        }
+    }
+
+    /// Returns `true` if this is an extended private key.
+    pub fn is_private(&self) -> bool {
+        self.private_key().is_some()
+    }
+
+    /// Returns the public key.
+    pub fn public_key(&self) -> ECPublicKey {
+        match &self.key {
+            HDKeyMaterial::Private(key) => key.public_key(),
+            HDKeyMaterial::Public(key) => key.clone(),
+        }
+    }
+
+    /// Returns the chain code.
+    pub fn chain_code(&self) -> &[u8; Self::CHAIN_CODE_SIZE] {
+        &self.chain_code
+    }
+
+    /// Returns the extended public key with the same public key and chain
+    /// code, from which only non-hardened children can be derived.
+    pub fn to_public(&self) -> Self {
+        Self::from_public_key(self.public_key(), self.chain_code)
+    }
+
+    /// Derives the child key with the given number.
+    ///
+    /// The child of an extended private key is an extended private key, and
+    /// the child of an extended public key is an extended public key.
+    ///
+    /// Returns an error if a hardened child of an extended public key is
+    /// requested, if the index doesn't fit in 31 bits, or in the
+    /// astronomically unlikely case that the index yields an invalid key, in
+    /// which case BIP32 says to proceed with the next index.
+    pub fn derive_child(&self, child: ChildNumber) -> Result<Self> {
+        let index = child.raw_index()?;
+        let mut data = Vec::with_capacity(37);
+        if child.is_hardened() {
+            let Some(private_key) = self.private_key() else {
+                bail!("Cannot derive a hardened child from a BIP32 public key");
+            };
+            data.push(0);
+            data.extend_from_slice(private_key.data());
+        } else {
+            data.extend_from_slice(self.public_key().data());
+        }
+        data.extend_from_slice(&index.to_be_bytes());
+
+        let i = hmac_sha512(self.chain_code, data);
+        let (il, ir) = i.split_at(32);
+        let tweak: &[u8; 32] = il.try_into().unwrap();
+        let key = match &self.key {
+            HDKeyMaterial::Private(key) => key.tweak_add(tweak).map(HDKeyMaterial::Private),
+            HDKeyMaterial::Public(key) => key.tweak_add(tweak).map(HDKeyMaterial::Public),
+        }
+        .map_err(|_| anyhow!("BIP32 child index yields an invalid key"))?;
+        Ok(Self { key, chain_code: ir.try_into().unwrap() })
+    }
+
+    /// Derives the descendant key along the given path of child numbers.
+    pub fn derive_path(&self, path: &[ChildNumber]) -> Result<Self> {
+        path.iter().try_fold(self.clone(), |key, child| key.derive_child(*child))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hex_literal::hex;
+
+    use super::{ChildNumber, HDKey};
+    use crate::ECKeyBase;
+
+    use ChildNumber::{Hardened, Normal};
+
+    /// The path, chain code, private key, and public key of a derived key.
+    type VectorKey = (&'static [ChildNumber], [u8; 32], [u8; 32], [u8; 33]);
+
+    /// BIP32 test vector 1.
+    const VECTOR_1: [VectorKey; 6] = [
+        (
+            &[],
+            hex!("873dff81c02f525623fd1fe5167eac3a55a049de3d314bb42ee227ffed37d508"),
+            hex!("e8f32e723decf4051aefac8e2c93c9c5b214313817cdb01a1494b917c8436b35"),
+            hex!("0339a36013301597daef41fbe593a02cc513d0b55527ec2df1050e2e8ff49c85c2"),
+        ),
+        (
+            &[Hardened(0)],
+            hex!("47fdacbd0f1097043b78c63c20c34ef4ed9a111d980047ad16282c7ae6236141"),
+            hex!("edb2e14f9ee77d26dd93b4ecede8d16ed408ce149b6cd80b0715a2d911a0afea"),
+            hex!("035a784662a4a20a65bf6aab9ae98a6c068a81c52e4b032c0fb5400c706cfccc56"),
+        ),
+        (
+            &[Hardened(0), Normal(1)],
+            hex!("2a7857631386ba23dacac34180dd1983734e444fdbf774041578e9b6adb37c19"),
+            hex!("3c6cb8d0f6a264c91ea8b5030fadaa8e538b020f0a387421a12de9319dc93368"),
+            hex!("03501e454bf00751f24b1b489aa925215d66af2234e3891c3b21a52bedb3cd711c"),
+        ),
+        (
+            &[Hardened(0), Normal(1), Hardened(2)],
+            hex!("04466b9cc8e161e966409ca52986c584f07e9dc81f735db683c3ff6ec7b1503f"),
+            hex!("cbce0d719ecf7431d88e6a89fa1483e02e35092af60c042b1df2ff59fa424dca"),
+            hex!("0357bfe1e341d01c69fe5654309956cbea516822fba8a601743a012a7896ee8dc2"),
+        ),
+        (
+            &[Hardened(0), Normal(1), Hardened(2), Normal(2)],
+            hex!("cfb71883f01676f587d023cc53a35bc7f88f724b1f8c2892ac1275ac822a3edd"),
+            hex!("0f479245fb19a38a1954c5c7c0ebab2f9bdfd96a17563ef28a6a4b1a2a764ef4"),
+            hex!("02e8445082a72f29b75ca48748a914df60622a609cacfce8ed0e35804560741d29"),
+        ),
+        (
+            &[Hardened(0), Normal(1), Hardened(2), Normal(2), Normal(1000000000)],
+            hex!("c783e67b921d2beb8f6b389cc646d7263b4145701dadd2161548a8b078e65e9e"),
+            hex!("471b76e389e528d6de6d816857e012c5455051cad6660850e58372a6c3e6e7c8"),
+            hex!("022a471424da5e657499d1ff51cb43c47481a03b1e77f951fe64cec9f5a48f7011"),
+        ),
+    ];
+
+    #[test]
+    fn test_bip32_vector_1() {
+        let master = HDKey::new_master(hex!("000102030405060708090a0b0c0d0e0f")).unwrap();
+        for (path, chain_code, private_key, public_key) in VECTOR_1 {
+            let key = master.derive_path(path).unwrap();
+            assert_eq!(key.chain_code(), &chain_code, "{:?}", path);
+            assert_eq!(key.private_key().unwrap().data(), private_key, "{:?}", path);
+            assert_eq!(key.public_key().data(), public_key, "{:?}", path);
+        }
+    }
+
+    #[test]
+    fn test_public_derivation() {
+        let master = HDKey::new_master(hex!("000102030405060708090a0b0c0d0e0f")).unwrap();
+        let parent = master.derive_path(&[Hardened(0), Normal(1), Hardened(2)]).unwrap();
+
+        // Public parent to public child agrees with private derivation.
+        let path = [Normal(2), Normal(1000000000)];
+        let public_child = parent.to_public().derive_path(&path).unwrap();
+        assert!(!public_child.is_private());
+        assert_eq!(public_child, parent.derive_path(&path).unwrap().to_public());
+
+        let err = parent.to_public().derive_child(Hardened(0)).unwrap_err();
+        assert_eq!(err.to_string(), "Cannot derive a hardened child from a BIP32 public key");
+    }
+
+    #[test]
+    fn test_child_number() {
+        assert_eq!(ChildNumber::from(0x8000_0002), Hardened(2));
+        assert_eq!(ChildNumber::from(2), Normal(2));
+        let master = HDKey::new_master([0u8; 16]).unwrap();
+        assert!(master.derive_child(Normal(1 << 31)).is_err());
+    }
+
+    #[test]
+    fn test_bip39() {
+        // BIP39 test vector, with its BIP32 root key
+        // xprv9s21ZrQH143K3h3fDYiay8mocZ3afhfULfb5GX8kCBdno77K4HiA15Tg23wpbeF1pLfs1c5SPmYHrEpTuuRhxMwvKDwqdKiGJS9XFKzUsAF.
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let master = HDKey::from_bip39(mnemonic, "TREZOR").unwrap();
+        assert_eq!(
+            master.chain_code(),
+            &hex!("a3fa8c983223306de0f0f65e74ebb1e98aba751633bf91d5fb56529aa5c132c1")
+        );
+        assert_eq!(
+            master.private_key().unwrap().data(),
+            &hex!("cbedc75b0d6412c85c79bc13875112ef912fd1e756631b5a00330866f22ff184")
+        );
+        assert_eq!(
+            crate::ECPrivateKey::from_bip39(mnemonic, "TREZOR").unwrap(),
+            *master.private_key().unwrap()
+        );
+        assert_ne!(HDKey::from_bip39(mnemonic, "").unwrap(), master);
+
+        // Bad checksum.
+        let bad_checksum = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon";
+        assert!(HDKey::from_bip39(bad_checksum, "").is_err());
+        // Unknown word.
+        let unknown_word = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abuot";
+        assert!(HDKey::from_bip39(unknown_word, "").is_err());
+    }
+}
@@ -10,8 +10,21 @@ pub use ec_private_key::ECPrivateKey;
 mod ec_public_key;
 pub use ec_public_key::ECPublicKey;
 
+mod any_ec_key;
+pub use any_ec_key::AnyECKey;
+
 mod ec_uncompressed_public_key;
 pub use ec_uncompressed_public_key::ECUncompressedPublicKey;
 
 mod schnorr_public_key;
 pub use schnorr_public_key::SchnorrPublicKey;
+pub(crate) use schnorr_public_key::tagged_sha256;
+
+mod hd_key;
+pub use hd_key::{ChildNumber, HDKey};
+
+mod network;
+pub use network::Network;
+
+mod bitcoin_address_kind;
+pub use bitcoin_address_kind::BitcoinAddressKind;
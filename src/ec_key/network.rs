@@ -0,0 +1,47 @@
+/// A Bitcoin network, which determines the version bytes of encodings such as
+/// WIF and addresses.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Network {
+    #[default]
+    Mainnet,
+    /// Testnet, whose WIF and P2PKH version bytes are also used by signet
+    /// and regtest.
+    Testnet,
+}
+
+impl Network {
+    /// The version byte of a WIF private key on this network.
+    pub(crate) fn wif_version(&self) -> u8 {
+        match self {
+            Self::Mainnet => 0x80,
+            Self::Testnet => 0xef,
+        }
+    }
+
+    /// The version byte of a P2PKH address on this network.
+    pub(crate) fn p2pkh_version(&self) -> u8 {
+        match self {
+            Self::Mainnet => 0x00,
+            Self::Testnet => 0x6f,
+        }
+    }
+
+    /// The human-readable part of a SegWit address on this network.
+    ///
+    /// Regtest uses `bcrt` instead, so testnet addresses are not valid on
+    /// regtest.
+    pub(crate) fn bech32_hrp(&self) -> bech32::Hrp {
+        match self {
+            Self::Mainnet => bech32::hrp::BC,
+            Self::Testnet => bech32::hrp::TB,
+        }
+    }
+
+    pub(crate) fn from_wif_version(version: u8) -> Option<Self> {
+        match version {
+            0x80 => Some(Self::Mainnet),
+            0xef => Some(Self::Testnet),
+            _ => None,
+        }
+    }
+}
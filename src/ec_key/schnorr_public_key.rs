@@ -1,7 +1,9 @@
+use alloc::vec::Vec;
 use anyhow::{bail, Result};
-use bc_crypto::SCHNORR_SIGNATURE_SIZE;
+use bc_crypto::{hash::sha256, SCHNORR_SIGNATURE_SIZE};
+use secp256k1::{schnorr::Signature, Message, Secp256k1, XOnlyPublicKey};
 
-use crate::ECKeyBase;
+use crate::{ ComponentError, ECKeyBase };
 
 
 /// A Schnorr (x-only) elliptic curve public key.
@@ -29,6 +31,40 @@ impl SchnorrPublicKey {
     {
         bc_crypto::schnorr_verify(self.into(), signature, message, tag)
     }
+
+    /// Verifies every `(signature, message, tag)` entry against this key,
+    /// returning `true` only if all of them are valid.
+    ///
+    /// Equivalent to calling `schnorr_verify` on each entry, stopping at the
+    /// first invalid one. The key is parsed only once, but each signature is
+    /// still verified on its own: this is not batch verification.
+    pub fn schnorr_verify_all<'a, I>(&self, entries: I) -> bool
+    where
+        I: IntoIterator<Item = (&'a [u8; SCHNORR_SIGNATURE_SIZE], &'a [u8], &'a [u8])>,
+    {
+        let Ok(pk) = XOnlyPublicKey::from_slice(&self.0) else {
+            return false;
+        };
+        let secp = Secp256k1::verification_only();
+        entries.into_iter().all(|(signature, message, tag)| {
+            let Ok(sig) = Signature::from_slice(signature) else {
+                return false;
+            };
+            let msg = Message::from_slice(&tagged_sha256(message, tag)).unwrap();
+            secp.verify_schnorr(&sig, &msg, &pk).is_ok()
+        })
+    }
+}
+
+/// The BIP-340 tagged hash `SHA256(SHA256(tag) || SHA256(tag) || msg)`, as
+/// used by `bc_crypto::schnorr_verify`.
+pub(crate) fn tagged_sha256(msg: &[u8], tag: &[u8]) -> [u8; 32] {
+    let tag_hash = sha256(tag);
+    let mut data = Vec::with_capacity(tag_hash.len() * 2 + msg.len());
+    data.extend_from_slice(&tag_hash);
+    data.extend_from_slice(&tag_hash);
+    data.extend_from_slice(msg);
+    sha256(data)
 }
 
 impl<'a> From<&'a SchnorrPublicKey> for &'a [u8; SchnorrPublicKey::KEY_SIZE] {
@@ -49,14 +85,14 @@ impl AsRef<[u8]> for SchnorrPublicKey {
     }
 }
 
-impl std::fmt::Display for SchnorrPublicKey {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for SchnorrPublicKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", self.hex())
     }
 }
 
-impl std::fmt::Debug for SchnorrPublicKey {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Debug for SchnorrPublicKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "SchnorrPublicKey({})", self.hex())
     }
 }
@@ -67,7 +103,7 @@ impl ECKeyBase for SchnorrPublicKey {
     fn from_data_ref(data: impl AsRef<[u8]>) -> Result<Self> where Self: Sized {
         let data = data.as_ref();
         if data.len() != Self::KEY_SIZE {
-            bail!("invalid Schnorr public key size");
+            bail!(ComponentError::InvalidLength { component: "Schnorr public key" });
         }
         let mut key = [0u8; Self::KEY_SIZE];
         key.copy_from_slice(data);
@@ -0,0 +1,108 @@
+use alloc::string::String;
+use anyhow::{bail, Result};
+use bc_rand::RandomNumberGenerator;
+use ed25519_dalek::{Signer as _, SigningKey};
+use subtle::ConstantTimeEq;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use crate::{ ComponentError, Ed25519PublicKey };
+
+/// An Ed25519 private key (the 32-byte seed) used for signing.
+///
+/// <https://datatracker.ietf.org/doc/html/rfc8032>
+///
+/// The key material is zeroized when the key is dropped.
+#[derive(Clone, PartialEq, Eq, Hash, Zeroize, ZeroizeOnDrop)]
+pub struct Ed25519PrivateKey([u8; Self::KEY_SIZE]);
+
+impl Ed25519PrivateKey {
+    pub const KEY_SIZE: usize = 32;
+    pub const SIGNATURE_SIZE: usize = 64;
+
+    /// Generate a new random `Ed25519PrivateKey`.
+    #[cfg(feature = "std")]
+    pub fn new() -> Self {
+        let mut rng = bc_rand::SecureRandomNumberGenerator;
+        Self::new_using(&mut rng)
+    }
+
+    /// Generate a new random `Ed25519PrivateKey` using the given random number generator.
+    pub fn new_using(rng: &mut impl RandomNumberGenerator) -> Self {
+        let mut key = [0u8; Self::KEY_SIZE];
+        rng.fill_random_data(&mut key);
+        Self::from_data(key)
+    }
+
+    /// Restore an `Ed25519PrivateKey` from a fixed-size array of bytes.
+    pub const fn from_data(data: [u8; Self::KEY_SIZE]) -> Self {
+        Self(data)
+    }
+
+    /// Restore an `Ed25519PrivateKey` from a reference to an array of bytes.
+    pub fn from_data_ref(data: impl AsRef<[u8]>) -> Result<Self> {
+        let data = data.as_ref();
+        if data.len() != Self::KEY_SIZE {
+            bail!(ComponentError::InvalidLength { component: "Ed25519 private key" });
+        }
+        let mut arr = [0u8; Self::KEY_SIZE];
+        arr.copy_from_slice(data);
+        Ok(Self::from_data(arr))
+    }
+
+    /// Get a reference to the fixed-size array of bytes.
+    pub fn data(&self) -> &[u8; Self::KEY_SIZE] {
+        &self.0
+    }
+
+    /// Restore an `Ed25519PrivateKey` from a hex string.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the hex string is invalid or the length is not 32 bytes.
+    pub fn from_hex(hex: impl AsRef<str>) -> Self {
+        Self::from_data_ref(hex::decode(hex.as_ref()).unwrap()).unwrap()
+    }
+
+    /// Get the hex string representation of the `Ed25519PrivateKey`.
+    pub fn hex(&self) -> String {
+        hex::encode(self.data())
+    }
+
+    /// Compare two keys in constant time.
+    ///
+    /// Unlike `==`, this does not short-circuit on the first differing byte.
+    pub fn ct_eq(&self, other: &Ed25519PrivateKey) -> bool {
+        self.0.ct_eq(&other.0).into()
+    }
+
+    /// Get the `Ed25519PublicKey` corresponding to this `Ed25519PrivateKey`.
+    pub fn public_key(&self) -> Ed25519PublicKey {
+        let signing_key = SigningKey::from_bytes(&self.0);
+        Ed25519PublicKey::from_data(signing_key.verifying_key().to_bytes())
+    }
+
+    /// Sign the given message with this key.
+    pub fn sign(&self, message: impl AsRef<[u8]>) -> [u8; Self::SIGNATURE_SIZE] {
+        let signing_key = SigningKey::from_bytes(&self.0);
+        signing_key.sign(message.as_ref()).to_bytes()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for Ed25519PrivateKey {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl core::fmt::Debug for Ed25519PrivateKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Ed25519PrivateKey")
+    }
+}
+
+impl From<[u8; Self::KEY_SIZE]> for Ed25519PrivateKey {
+    fn from(data: [u8; Self::KEY_SIZE]) -> Self {
+        Self::from_data(data)
+    }
+}
@@ -0,0 +1,84 @@
+use alloc::string::String;
+use anyhow::{bail, Result};
+use ed25519_dalek::{Signature as DalekSignature, VerifyingKey};
+
+use crate::{ ComponentError, Ed25519PrivateKey };
+
+/// An Ed25519 public key used for verifying signatures.
+///
+/// <https://datatracker.ietf.org/doc/html/rfc8032>
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Ed25519PublicKey([u8; Self::KEY_SIZE]);
+
+impl Ed25519PublicKey {
+    pub const KEY_SIZE: usize = 32;
+
+    /// Restore an `Ed25519PublicKey` from a fixed-size array of bytes.
+    pub const fn from_data(data: [u8; Self::KEY_SIZE]) -> Self {
+        Self(data)
+    }
+
+    /// Restore an `Ed25519PublicKey` from a reference to an array of bytes.
+    pub fn from_data_ref(data: impl AsRef<[u8]>) -> Result<Self> {
+        let data = data.as_ref();
+        if data.len() != Self::KEY_SIZE {
+            bail!(ComponentError::InvalidLength { component: "Ed25519 public key" });
+        }
+        let mut arr = [0u8; Self::KEY_SIZE];
+        arr.copy_from_slice(data);
+        Ok(Self::from_data(arr))
+    }
+
+    /// Get a reference to the fixed-size array of bytes.
+    pub fn data(&self) -> &[u8; Self::KEY_SIZE] {
+        &self.0
+    }
+
+    /// Restore an `Ed25519PublicKey` from a hex string.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the hex string is invalid or the length is not 32 bytes.
+    pub fn from_hex(hex: impl AsRef<str>) -> Self {
+        Self::from_data_ref(hex::decode(hex.as_ref()).unwrap()).unwrap()
+    }
+
+    /// Get the hex string representation of the `Ed25519PublicKey`.
+    pub fn hex(&self) -> String {
+        hex::encode(self.data())
+    }
+
+    /// Verify the given signature for the given message.
+    ///
+    /// Returns `false` if the key is not a valid curve point or the signature
+    /// does not verify.
+    pub fn verify(
+        &self,
+        signature: &[u8; Ed25519PrivateKey::SIGNATURE_SIZE],
+        message: impl AsRef<[u8]>,
+    ) -> bool {
+        let Ok(verifying_key) = VerifyingKey::from_bytes(&self.0) else {
+            return false;
+        };
+        let signature = DalekSignature::from_bytes(signature);
+        verifying_key.verify_strict(message.as_ref(), &signature).is_ok()
+    }
+}
+
+impl core::fmt::Debug for Ed25519PublicKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Ed25519PublicKey({})", self.hex())
+    }
+}
+
+impl From<[u8; Self::KEY_SIZE]> for Ed25519PublicKey {
+    fn from(data: [u8; Self::KEY_SIZE]) -> Self {
+        Self::from_data(data)
+    }
+}
+
+impl AsRef<[u8]> for Ed25519PublicKey {
+    fn as_ref(&self) -> &[u8] {
+        self.data()
+    }
+}
@@ -0,0 +1,152 @@
+use alloc::vec::Vec;
+use bc_rand::RandomNumberGenerator;
+
+use crate::{ AeadAlgorithm, EncryptedMessage, Nonce, SymmetricKey };
+
+/// Collects the options for encrypting a message under a `SymmetricKey`.
+///
+/// Obtained from `SymmetricKey::encrypt_builder`. With no options set, it
+/// encrypts as `SymmetricKey::encrypt` does with no AAD and a random nonce.
+/// Without the `std` feature, finish with `encrypt_using` and supply the
+/// random number generator.
+#[derive(Clone)]
+pub struct EncryptBuilder<'a> {
+    key: &'a SymmetricKey,
+    aad: Vec<u8>,
+    nonce: Option<Nonce>,
+    algorithm: AeadAlgorithm,
+    committing: bool,
+}
+
+impl<'a> EncryptBuilder<'a> {
+    pub(crate) fn new(key: &'a SymmetricKey) -> Self {
+        Self {
+            key,
+            aad: Vec::new(),
+            nonce: None,
+            algorithm: AeadAlgorithm::default(),
+            committing: false,
+        }
+    }
+
+    /// Sets the additional authenticated data bound to the message.
+    pub fn aad(mut self, aad: impl Into<Vec<u8>>) -> Self {
+        self.aad = aad.into();
+        self
+    }
+
+    /// Sets the nonce, instead of a random one.
+    ///
+    /// Reusing a nonce with the same key for different plaintexts destroys
+    /// confidentiality; see `SymmetricKey::encrypt_with_nonce`.
+    pub fn nonce(mut self, nonce: impl AsRef<Nonce>) -> Self {
+        self.nonce = Some(nonce.as_ref().clone());
+        self
+    }
+
+    /// Sets the AEAD algorithm.
+    pub fn algorithm(mut self, algorithm: AeadAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Sets whether the message carries a commitment to its key; see
+    /// `SymmetricKey::encrypt_committing`.
+    pub fn committing(mut self, committing: bool) -> Self {
+        self.committing = committing;
+        self
+    }
+
+    /// Encrypts `plaintext` with the options set.
+    #[cfg(feature = "std")]
+    pub fn encrypt(self, plaintext: impl Into<Vec<u8>>) -> EncryptedMessage {
+        self.encrypt_using(plaintext, &mut bc_rand::SecureRandomNumberGenerator)
+    }
+
+    /// Encrypts `plaintext` with the options set, drawing a nonce that wasn't
+    /// set from the given random number generator.
+    pub fn encrypt_using(
+        self,
+        plaintext: impl Into<Vec<u8>>,
+        rng: &mut impl RandomNumberGenerator
+    ) -> EncryptedMessage {
+        if self.committing {
+            self.key.encrypt_committing_using(plaintext, Some(self.aad), self.nonce, self.algorithm, rng)
+        } else {
+            self.key.encrypt_with_algorithm_using(plaintext, Some(self.aad), self.nonce, self.algorithm, rng)
+        }
+    }
+}
+
+/// Omits the key, so that logging a builder doesn't reveal it.
+impl core::fmt::Debug for EncryptBuilder<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("EncryptBuilder")
+            .field("aad", &hex::encode(&self.aad))
+            .field("nonce", &self.nonce)
+            .field("algorithm", &self.algorithm)
+            .field("committing", &self.committing)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bc_rand::make_fake_random_number_generator;
+
+    use crate::{ AeadAlgorithm, Nonce, SymmetricKey };
+
+    const PLAINTEXT: &[u8] = b"Wolf McNally";
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_defaults() {
+        let key = SymmetricKey::new();
+        let nonce = Nonce::new();
+        let built = key.encrypt_builder().nonce(&nonce).encrypt(PLAINTEXT);
+        assert_eq!(built, key.encrypt(PLAINTEXT, None::<Vec<u8>>, Some(&nonce)));
+        assert_eq!(built.algorithm(), AeadAlgorithm::default());
+        assert!(!built.is_key_committing());
+        assert!(built.aad().is_empty());
+
+        let a = key.encrypt_builder().encrypt(PLAINTEXT);
+        let b = key.encrypt_builder().encrypt(PLAINTEXT);
+        assert_ne!(a.nonce(), b.nonce());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_options() {
+        let key = SymmetricKey::new();
+        let message = key
+            .encrypt_builder()
+            .aad(b"header")
+            .algorithm(AeadAlgorithm::Aes256Gcm)
+            .committing(true)
+            .encrypt(PLAINTEXT);
+        assert_eq!(message.aad(), b"header");
+        assert_eq!(message.algorithm(), AeadAlgorithm::Aes256Gcm);
+        assert!(message.is_key_committing());
+        assert_eq!(key.decrypt_committing(&message).unwrap(), PLAINTEXT);
+        assert!(SymmetricKey::new().decrypt(&message).is_err());
+    }
+
+    #[test]
+    fn test_encrypt_using() {
+        let key = SymmetricKey::new_using(&mut make_fake_random_number_generator());
+        let built = key
+            .encrypt_builder()
+            .aad(b"header")
+            .committing(true)
+            .encrypt_using(PLAINTEXT, &mut make_fake_random_number_generator());
+        let expected = key.encrypt_committing_using(
+            PLAINTEXT,
+            Some(b"header"),
+            None::<Nonce>,
+            AeadAlgorithm::default(),
+            &mut make_fake_random_number_generator()
+        );
+        assert_eq!(built, expected);
+        assert_eq!(key.decrypt_committing(&built).unwrap(), PLAINTEXT);
+    }
+}
@@ -1,25 +1,97 @@
-use std::borrow::Cow;
+use alloc::{ borrow::Cow, string::String, vec, vec::Vec };
 use bc_ur::prelude::*;
-use crate::{ Nonce, Digest, DigestProvider, tags, AuthenticationTag };
+use crate::{
+    Nonce,
+    Digest,
+    DigestProvider,
+    tags,
+    AuthenticationTag,
+    ComponentError,
+    Signature,
+    SigningPublicKey,
+    SymmetricKey,
+    Verifier,
+};
 use anyhow::{ bail, Result, Error };
+use base64::{ engine::general_purpose::{ STANDARD, URL_SAFE_NO_PAD }, Engine as _ };
+use zeroize::Zeroizing;
+
+/// The AEAD construction used to produce an `EncryptedMessage`.
+///
+/// All algorithms use a 256-bit key, a 96-bit nonce, and a 128-bit
+/// authentication tag.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum AeadAlgorithm {
+    /// IETF ChaCha20-Poly1305 (<https://datatracker.ietf.org/doc/html/rfc8439>).
+    #[default]
+    ChaCha20Poly1305,
+    /// AES-256 in Galois/Counter Mode (NIST SP 800-38D).
+    Aes256Gcm,
+    /// AES-256-GCM-SIV (<https://datatracker.ietf.org/doc/html/rfc8452>),
+    /// which is nonce-misuse resistant.
+    ///
+    /// When no nonce is given, `SymmetricKey` derives it from the key, AAD,
+    /// and plaintext, making encryption deterministic: the same key,
+    /// plaintext, and AAD always give the same message. This allows
+    /// encrypted data to be deduplicated, but reveals to anyone who sees two
+    /// messages whether their plaintexts are equal.
+    Aes256GcmSiv,
+}
+
+impl AeadAlgorithm {
+    /// The discriminator written as the first element of the CBOR array.
+    ///
+    /// `ChaCha20Poly1305` is the original format and has no discriminator.
+    fn discriminator(&self) -> Option<u64> {
+        match self {
+            Self::ChaCha20Poly1305 => None,
+            Self::Aes256Gcm => Some(1),
+            Self::Aes256GcmSiv => Some(2),
+        }
+    }
+
+    fn from_discriminator(value: u64) -> Result<Self> {
+        match value {
+            1 => Ok(Self::Aes256Gcm),
+            2 => Ok(Self::Aes256GcmSiv),
+            _ => bail!(ComponentError::UnknownAlgorithm { component: "EncryptedMessage", value }),
+        }
+    }
+}
 
 /// A secure encrypted message.
 ///
-/// Implemented using the IETF ChaCha20-Poly1305 encryption.
+/// Implemented using the IETF ChaCha20-Poly1305 encryption by default, or
+/// optionally AES-256-GCM or AES-256-GCM-SIV. See `AeadAlgorithm`.
 ///
 /// <https://datatracker.ietf.org/doc/html/rfc8439>
 ///
 /// To facilitate decoding, it is recommended that the plaintext of an `EncryptedMessage` be
 /// tagged CBOR.
+///
+/// Neither AEAD is key-committing: a ciphertext can be crafted that decrypts
+/// under two different keys. A message made by `SymmetricKey::encrypt_committing`
+/// also carries a commitment to its key, which `SymmetricKey::decrypt` checks,
+/// so it decrypts under that key only.
+///
+/// A message may also carry a hint, such as the ARID of the key it was
+/// encrypted with, to help a recipient choose a key. The hint is not
+/// authenticated: anyone can change or remove it without detection, so it
+/// must only ever be used to guide trial decryption.
 #[derive(Clone, Eq, PartialEq)]
 pub struct EncryptedMessage {
+    algorithm: AeadAlgorithm,
     ciphertext: Vec<u8>,
     aad: Vec<u8>, // Additional authenticated data (AAD) per RFC8439
     nonce: Nonce,
     auth: AuthenticationTag,
+    key_commitment: Option<[u8; Self::KEY_COMMITMENT_SIZE]>,
+    hint: Option<Vec<u8>>,
 }
 
 impl EncryptedMessage {
+    pub const KEY_COMMITMENT_SIZE: usize = 32;
+
     /// Restores an EncryptedMessage from its CBOR representation.
     ///
     /// This is a low-level function that is not normally needed.
@@ -28,22 +100,83 @@ impl EncryptedMessage {
         aad: impl Into<Vec<u8>>,
         nonce: Nonce,
         auth: AuthenticationTag
+    ) -> Self {
+        Self::new_with_algorithm(AeadAlgorithm::default(), ciphertext, aad, nonce, auth)
+    }
+
+    /// Restores an EncryptedMessage produced by the given algorithm.
+    ///
+    /// This is a low-level function that is not normally needed.
+    pub fn new_with_algorithm(
+        algorithm: AeadAlgorithm,
+        ciphertext: impl Into<Vec<u8>>,
+        aad: impl Into<Vec<u8>>,
+        nonce: Nonce,
+        auth: AuthenticationTag
     ) -> Self {
         Self {
+            algorithm,
             ciphertext: ciphertext.into(),
             aad: aad.into(),
             nonce,
             auth,
+            key_commitment: None,
+            hint: None,
         }
     }
 
+    /// Returns this message with the given commitment to its key.
+    ///
+    /// This is a low-level function that is not normally needed.
+    pub fn with_key_commitment(mut self, key_commitment: [u8; Self::KEY_COMMITMENT_SIZE]) -> Self {
+        self.key_commitment = Some(key_commitment);
+        self
+    }
+
+    /// Returns the commitment to the key this message was encrypted with, if
+    /// it has one.
+    pub fn key_commitment(&self) -> Option<&[u8; Self::KEY_COMMITMENT_SIZE]> {
+        self.key_commitment.as_ref()
+    }
+
+    /// Returns `true` if this message carries a commitment to its key.
+    pub fn is_key_committing(&self) -> bool {
+        self.key_commitment.is_some()
+    }
+
+    /// Returns this message with the given unauthenticated hint, replacing
+    /// any it already has.
+    ///
+    /// The hint is not covered by the authentication tag, so changing it
+    /// doesn't affect decryption, and tampering with it can't be detected.
+    pub fn with_hint(mut self, hint: impl Into<Vec<u8>>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+
+    /// Returns the unauthenticated hint, if this message has one.
+    ///
+    /// The hint may have been altered by anyone, so use it only to choose
+    /// which key to try first.
+    pub fn hint(&self) -> Option<&[u8]> {
+        self.hint.as_deref()
+    }
+
+    /// Returns the AEAD algorithm used to produce this message.
+    pub fn algorithm(&self) -> AeadAlgorithm {
+        self.algorithm
+    }
+
     /// Returns a reference to the ciphertext data.
     pub fn ciphertext(&self) -> &Vec<u8> {
         &self.ciphertext
     }
 
     /// Returns a reference to the additional authenticated data (AAD).
-    pub fn aad(&self) -> &Vec<u8> {
+    ///
+    /// The AAD is not encrypted, but it is covered by the authentication tag,
+    /// so any modification to it causes decryption to fail.
+    pub fn aad(&self) -> &[u8] {
         &self.aad
     }
 
@@ -68,15 +201,172 @@ impl EncryptedMessage {
     pub fn has_digest(&self) -> bool {
         self.opt_digest().is_some()
     }
+
+    /// Tries to decrypt this message with each of `keys`, returning the index
+    /// of the first key that succeeds and the plaintext, or `None` if none
+    /// do.
+    ///
+    /// This does not short-circuit on the first success: every key is tried,
+    /// so the number of attempts doesn't depend on which key matched. It is
+    /// not constant time, though. A successful attempt also decrypts and
+    /// allocates the plaintext, which a failed one skips, so timing can still
+    /// hint at whether and roughly where a key matched.
+    pub fn decrypt_with_any(&self, keys: &[SymmetricKey]) -> Option<(usize, Vec<u8>)> {
+        let mut result = None;
+        for (index, key) in keys.iter().enumerate() {
+            if let Ok(plaintext) = key.decrypt(self) {
+                result.get_or_insert((index, plaintext));
+            }
+        }
+        result
+    }
+
+    /// Encodes the tagged CBOR of this encrypted message as standard Base64, with
+    /// padding.
+    pub fn to_base64(&self) -> String {
+        STANDARD.encode(self.tagged_cbor_data())
+    }
+
+    /// Decodes an encrypted message from standard Base64 of its tagged CBOR.
+    ///
+    /// Returns an error if the text is not valid Base64 or doesn't decode
+    /// as an encrypted message, including when its CBOR tag is for some other type.
+    pub fn from_base64(base64: &str) -> Result<Self> {
+        Self::from_tagged_cbor_data(STANDARD.decode(base64)?)
+    }
+
+    /// Encodes the tagged CBOR of this encrypted message as URL-safe Base64, without
+    /// padding, for use in URLs and file names.
+    pub fn to_base64url(&self) -> String {
+        URL_SAFE_NO_PAD.encode(self.tagged_cbor_data())
+    }
+
+    /// Decodes an encrypted message from unpadded URL-safe Base64 of its tagged CBOR.
+    ///
+    /// Returns an error if the text is not valid unpadded URL-safe Base64 or
+    /// doesn't decode as an encrypted message.
+    pub fn from_base64url(base64url: &str) -> Result<Self> {
+        Self::from_tagged_cbor_data(URL_SAFE_NO_PAD.decode(base64url)?)
+    }
+
+    /// Decrypts this message with `key` and returns the plaintext only if
+    /// `signature` is a valid signature over it by `signer`.
+    ///
+    /// Returns an error if decryption fails or the signature doesn't verify.
+    /// In the latter case the plaintext is zeroized before returning.
+    pub fn open_and_verify(
+        &self,
+        key: &SymmetricKey,
+        signature: &Signature,
+        signer: &SigningPublicKey
+    ) -> Result<Vec<u8>> {
+        let mut plaintext = Zeroizing::new(key.decrypt(self)?);
+        if !signer.verify(signature, &plaintext.as_slice()) {
+            bail!("Signature verification failed");
+        }
+        Ok(core::mem::take(&mut *plaintext))
+    }
+
+    /// Re-encrypts this message under `new`, for key rotation.
+    ///
+    /// The message is decrypted with `old` and encrypted again with `new`
+    /// under a new nonce chosen as `encrypt_with_algorithm` would, keeping the
+    /// same algorithm and AAD. If this message is key-committing, so is the
+    /// result. The hint is not carried over, since it usually identifies the
+    /// old key. The decrypted plaintext is zeroized before returning.
+    ///
+    /// Returns an error if the message doesn't decrypt under `old`.
+    #[cfg(feature = "std")]
+    pub fn rekey(&self, old: &SymmetricKey, new: &SymmetricKey) -> Result<EncryptedMessage> {
+        self.rekey_using(old, new, &mut bc_rand::SecureRandomNumberGenerator)
+    }
+
+    /// Re-encrypts this message under `new` like `rekey`, drawing the new
+    /// nonce from the given random number generator.
+    pub fn rekey_using(
+        &self,
+        old: &SymmetricKey,
+        new: &SymmetricKey,
+        rng: &mut impl bc_rand::RandomNumberGenerator
+    ) -> Result<EncryptedMessage> {
+        let plaintext = Zeroizing::new(old.decrypt(self)?);
+        let aad = Some(self.aad.as_slice());
+        Ok(if self.is_key_committing() {
+            new.encrypt_committing_using(plaintext.as_slice(), aad, None::<Nonce>, self.algorithm, rng)
+        } else {
+            new.encrypt_with_algorithm_using(plaintext.as_slice(), aad, None::<Nonce>, self.algorithm, rng)
+        })
+    }
+
+    /// Splits the UR of this message into multi-part UR fragments, each
+    /// carrying at most `max_fragment_len` bytes of the message, for
+    /// transport as an animated QR code.
+    ///
+    /// The fragments are the pure (non-mixed) parts of the message's fountain
+    /// encoding, so all of them are needed to reassemble it, in any order.
+    ///
+    /// Panics if `max_fragment_len` is zero.
+    pub fn ur_fragments(&self, max_fragment_len: usize) -> Vec<String> {
+        assert!(max_fragment_len > 0, "UR fragment length must be greater than zero");
+        let ur = self.ur();
+        let mut encoder = MultipartEncoder::new(&ur, max_fragment_len).unwrap();
+        (0..encoder.parts_count())
+            .map(|_| encoder.next_part().unwrap())
+            .collect()
+    }
+
+    /// Reassembles a message from the multi-part UR fragments produced by
+    /// `ur_fragments`, which may be in any order and may contain duplicates.
+    ///
+    /// Returns an error if the fragments are malformed, come from more than
+    /// one message, or don't include enough of the message to reassemble it.
+    pub fn from_ur_fragments(fragments: &[impl AsRef<str>]) -> Result<Self> {
+        let mut decoder = MultipartDecoder::new();
+        let mut message_header = None;
+        for fragment in fragments {
+            let fragment = fragment.as_ref();
+            // The decoder ignores fragments once it has the whole message, so
+            // check every fragment's header against the first.
+            let header = Self::fragment_header(fragment)?;
+            match &message_header {
+                Some(message_header) if message_header != &header => {
+                    bail!("UR fragments are from different messages");
+                }
+                Some(_) => {}
+                None => message_header = Some(header),
+            }
+            decoder.receive(fragment)?;
+        }
+        let Some(ur) = decoder.message()? else {
+            bail!("UR fragments are incomplete");
+        };
+        Self::from_ur(ur)
+    }
+
+    /// Returns the fragment count, message length, and checksum that every
+    /// fragment of a multi-part UR carries.
+    fn fragment_header(fragment: &str) -> Result<Vec<CBOR>> {
+        let Some(payload) = fragment.rsplit('/').next() else {
+            bail!("Invalid UR fragment");
+        };
+        let part = CBOR::try_from_data(bytewords::decode(payload, bytewords::Style::Minimal)?)?;
+        match part.as_case() {
+            CBORCase::Array(elements) if elements.len() == 5 => Ok(elements[1..4].to_vec()),
+            _ => bail!("Invalid UR fragment"),
+        }
+    }
 }
 
-impl std::fmt::Debug for EncryptedMessage {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Debug for EncryptedMessage {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("EncryptedMessage")
+            .field("algorithm", &self.algorithm)
             .field("ciphertext", &hex::encode(&self.ciphertext))
             .field("aad", &hex::encode(&self.aad))
             .field("nonce", &self.nonce)
             .field("auth", &self.auth)
+            .field("key_commitment", &self.key_commitment.map(hex::encode))
+            .field("hint", &self.hint.as_ref().map(hex::encode))
             .finish()
     }
 }
@@ -87,6 +377,14 @@ impl AsRef<EncryptedMessage> for EncryptedMessage {
     }
 }
 
+/// Unlike most components, an `EncryptedMessage` doesn't digest its own
+/// CBOR. It returns the digest of the plaintext recorded in its AAD (see
+/// `opt_digest`), so encrypting an element leaves its digest unchanged. Two
+/// encryptions of the same plaintext therefore have the same digest.
+///
+/// # Panics
+///
+/// Panics if the AAD isn't a CBOR-encoded `Digest`; check `has_digest` first.
 impl DigestProvider for EncryptedMessage {
     fn digest(&self) -> Cow<'_, Digest> {
         let a = self.opt_digest().unwrap();
@@ -116,26 +414,58 @@ impl TryFrom<CBOR> for EncryptedMessage {
 
 impl CBORTaggedEncodable for EncryptedMessage {
     fn untagged_cbor(&self) -> CBOR {
-        let mut a = vec![
+        let mut a = Vec::new();
+        if let Some(discriminator) = self.algorithm.discriminator() {
+            a.push(discriminator.into());
+        }
+        a.extend([
             CBOR::to_byte_string(&self.ciphertext),
             CBOR::to_byte_string(self.nonce.data()),
-            CBOR::to_byte_string(self.auth.data())
-        ];
+            CBOR::to_byte_string(self.auth.data()),
+        ]);
 
-        if !self.aad.is_empty() {
+        if !self.aad.is_empty() || self.key_commitment.is_some() || self.hint.is_some() {
             a.push(CBOR::to_byte_string(&self.aad));
         }
+        match (&self.key_commitment, &self.hint) {
+            (Some(key_commitment), _) => a.push(CBOR::to_byte_string(key_commitment)),
+            (None, Some(_)) => a.push(CBOR::null()),
+            (None, None) => {}
+        }
+        if let Some(hint) = &self.hint {
+            a.push(CBOR::to_byte_string(hint));
+        }
 
         a.into()
     }
 }
 
 impl CBORTaggedDecodable for EncryptedMessage {
+    fn from_tagged_cbor(cbor: CBOR) -> Result<Self> {
+        crate::component_error::decode_tagged(cbor, "EncryptedMessage")
+    }
+
     fn from_untagged_cbor(cbor: CBOR) -> Result<Self> {
         match cbor.as_case() {
             CBORCase::Array(elements) => {
-                if elements.len() < 3 {
-                    bail!("EncryptedMessage must have at least 3 elements");
+                let (algorithm, elements) = match elements.first().map(|e| e.as_case()) {
+                    Some(CBORCase::Unsigned(value)) => {
+                        (AeadAlgorithm::from_discriminator(*value)?, &elements[1..])
+                    }
+                    _ => (AeadAlgorithm::ChaCha20Poly1305, &elements[..]),
+                };
+                // Only the canonical encoding is accepted: the AAD is present
+                // only if it's non-empty or followed by a commitment or hint,
+                // a null commitment only ever precedes a hint, and nothing
+                // follows the hint.
+                let non_canonical = match elements.len() {
+                    3 | 6 => false,
+                    4 => matches!(elements[3].as_case(), CBORCase::ByteString(aad) if aad.is_empty()),
+                    5 => elements[4] == CBOR::null(),
+                    _ => true,
+                };
+                if non_canonical {
+                    bail!(ComponentError::InvalidFormat { component: "EncryptedMessage" });
                 }
                 let ciphertext = CBOR::try_into_byte_string(elements[0].clone())?;
                 let nonce_data = CBOR::try_into_byte_string(elements[1].clone())?;
@@ -147,21 +477,42 @@ impl CBORTaggedDecodable for EncryptedMessage {
                 } else {
                     Vec::new()
                 };
-                Ok(Self::new(ciphertext, aad, nonce, auth))
+                let mut message = Self::new_with_algorithm(algorithm, ciphertext, aad, nonce, auth);
+                if elements.len() > 4 && elements[4] != CBOR::null() {
+                    let key_commitment = CBOR::try_into_byte_string(elements[4].clone())?;
+                    let Ok(key_commitment) = key_commitment.try_into() else {
+                        bail!(ComponentError::InvalidLength { component: "EncryptedMessage key commitment" });
+                    };
+                    message = message.with_key_commitment(key_commitment);
+                }
+                if elements.len() > 5 {
+                    message = message.with_hint(CBOR::try_into_byte_string(elements[5].clone())?);
+                }
+                Ok(message)
             }
-            _ => bail!("EncryptedMessage must be an array"),
+            _ => bail!(ComponentError::InvalidFormat { component: "EncryptedMessage" }),
         }
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod test {
     use bc_ur::{ UREncodable, URDecodable };
     use dcbor::prelude::*;
     use hex_literal::hex;
     use indoc::indoc;
 
-    use crate::{ SymmetricKey, Nonce, EncryptedMessage, AuthenticationTag, with_tags };
+    use crate::{
+        SymmetricKey,
+        Nonce,
+        EncryptedMessage,
+        AuthenticationTag,
+        AeadAlgorithm,
+        ComponentError,
+        Digest,
+        DigestProvider,
+        with_tags,
+    };
 
     const PLAINTEXT: &[u8] = b"Ladies and Gentlemen of the class of '99: If I could offer you only one tip for the future, sunscreen would be it.";
     const AAD: [u8; 12] = hex!("50515253c0c1c2c3c4c5c6c7");
@@ -278,4 +629,282 @@ mod test {
         assert_eq!(encrypted_message, decoded);
         Ok(())
     }
+
+    #[test]
+    fn test_aes_256_gcm_test_vector() -> Result<(), Box<dyn std::error::Error>> {
+        // Test Case 16 from "The Galois/Counter Mode of Operation (GCM)", McGrew & Viega.
+        let key = SymmetricKey::from_data(
+            hex!("feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308")
+        );
+        let nonce = Nonce::from_data(hex!("cafebabefacedbaddecaf888"));
+        let plaintext = hex!(
+            "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a721c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39"
+        );
+        let aad = hex!("feedfacedeadbeeffeedfacedeadbeefabaddad2");
+        let encrypted_message = key.encrypt_with_algorithm(
+            plaintext,
+            Some(&aad),
+            Some(&nonce),
+            AeadAlgorithm::Aes256Gcm
+        );
+        assert_eq!(encrypted_message.algorithm(), AeadAlgorithm::Aes256Gcm);
+        assert_eq!(
+            encrypted_message.ciphertext(),
+            &hex!(
+                "522dc1f099567d07f47f37a32a84427d643a8cdcbfe5c0c97598a2bd2555d1aa8cb08e48590dbb3da7b08b1056828838c5f61e6393ba7a0abcc9f662"
+            )
+        );
+        assert_eq!(
+            encrypted_message.authentication_tag(),
+            &AuthenticationTag::from_data(hex!("76fc6ece0f4e1768cddf8853bb2d551b"))
+        );
+        assert_eq!(key.decrypt(&encrypted_message)?, plaintext);
+        Ok(())
+    }
+
+    #[test]
+    fn test_aes_256_gcm_cbor() -> Result<(), Box<dyn std::error::Error>> {
+        let encrypted_message = KEY.encrypt_with_algorithm(
+            PLAINTEXT,
+            Some(&AAD),
+            Some(NONCE),
+            AeadAlgorithm::Aes256Gcm
+        );
+        let untagged = encrypted_message.untagged_cbor();
+        let CBORCase::Array(elements) = untagged.as_case() else {
+            panic!("expected array");
+        };
+        assert_eq!(elements.len(), 5);
+        assert_eq!(elements[0], CBOR::from(1));
+
+        let data = encrypted_message.to_cbor_data();
+
+        let decoded = EncryptedMessage::from_tagged_cbor_data(&data)?;
+        assert_eq!(decoded, encrypted_message);
+        assert_eq!(decoded.algorithm(), AeadAlgorithm::Aes256Gcm);
+        assert_eq!(KEY.decrypt(&decoded)?, PLAINTEXT);
+        Ok(())
+    }
+
+    #[test]
+    fn test_wrong_algorithm_fails() {
+        let chacha = encrypted_message();
+        let aes = KEY.encrypt_with_algorithm(
+            PLAINTEXT,
+            Some(&AAD),
+            Some(NONCE),
+            AeadAlgorithm::Aes256Gcm
+        );
+
+        let relabeled_chacha = EncryptedMessage::new_with_algorithm(
+            AeadAlgorithm::Aes256Gcm,
+            chacha.ciphertext().clone(),
+            chacha.aad(),
+            chacha.nonce().clone(),
+            chacha.authentication_tag().clone()
+        );
+        assert!(KEY.decrypt(&relabeled_chacha).is_err());
+
+        let relabeled_aes = EncryptedMessage::new(
+            aes.ciphertext().clone(),
+            aes.aad(),
+            aes.nonce().clone(),
+            aes.authentication_tag().clone()
+        );
+        assert!(KEY.decrypt(&relabeled_aes).is_err());
+    }
+
+    #[test]
+    fn test_encrypt_with_aad() -> Result<(), Box<dyn std::error::Error>> {
+        let key = SymmetricKey::new();
+        let aad = b"header: not secret, but authenticated";
+        let encrypted_message = key.encrypt_with_aad(PLAINTEXT, aad);
+        assert_eq!(encrypted_message.aad(), aad);
+
+        let decoded = EncryptedMessage::from_tagged_cbor_data(encrypted_message.to_cbor_data())?;
+        assert_eq!(decoded.aad(), aad);
+        assert_eq!(key.decrypt(&decoded)?, PLAINTEXT);
+        Ok(())
+    }
+
+    #[test]
+    fn test_digest_is_plaintext_digest() {
+        let key = SymmetricKey::new();
+        let plaintext_digest = Digest::from_image(PLAINTEXT);
+        let aad = plaintext_digest.tagged_cbor().to_cbor_data();
+        let a = key.encrypt_with_aad(PLAINTEXT, aad.clone());
+        let b = key.encrypt_with_aad(PLAINTEXT, aad.clone());
+        assert_ne!(a, b);
+        assert_eq!(*a.digest(), plaintext_digest);
+        assert_eq!(a.digest(), b.digest());
+        assert_ne!(*a.digest(), Digest::from_cbor_encodable(&a));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_digest_without_digest_aad_panics() {
+        let _ = encrypted_message().digest();
+    }
+
+    #[test]
+    fn test_tampered_aad_fails() {
+        let key = SymmetricKey::new();
+        let encrypted_message = key.encrypt_with_aad(PLAINTEXT, AAD);
+        let mut aad = encrypted_message.aad().to_vec();
+        aad[0] ^= 0x01;
+        let tampered = EncryptedMessage::new(
+            encrypted_message.ciphertext().clone(),
+            aad,
+            encrypted_message.nonce().clone(),
+            encrypted_message.authentication_tag().clone()
+        );
+        assert!(key.decrypt(&tampered).is_err());
+    }
+
+    #[test]
+    fn test_ur_fragments() {
+        let plaintext: Vec<u8> = (0..4096).map(|i| i as u8).collect();
+        let encrypted_message = KEY.encrypt(plaintext.clone(), Some(&AAD), Some(NONCE));
+        let fragments = encrypted_message.ur_fragments(200);
+        assert_eq!(fragments.len(), 21);
+        assert!(fragments.iter().all(|f| f.starts_with("ur:encrypted/")));
+
+        // Out of order, with duplicates.
+        let mut shuffled: Vec<String> = fragments.iter().rev().cloned().collect();
+        shuffled.extend_from_slice(&fragments[..5]);
+        let decoded = EncryptedMessage::from_ur_fragments(&shuffled).unwrap();
+        assert_eq!(decoded, encrypted_message);
+        assert_eq!(KEY.decrypt(&decoded).unwrap(), plaintext);
+
+        let err = EncryptedMessage::from_ur_fragments(&fragments[1..]).unwrap_err();
+        assert_eq!(err.to_string(), "UR fragments are incomplete");
+        assert!(EncryptedMessage::from_ur_fragments(&[] as &[String]).is_err());
+
+        // A fragment from another message is rejected, even after the first
+        // message is complete.
+        let other = KEY.encrypt(plaintext.clone(), Some(&AAD), None::<Nonce>).ur_fragments(200);
+        let mut mixed = fragments.clone();
+        mixed.push(other[0].clone());
+        let err = EncryptedMessage::from_ur_fragments(&mixed).unwrap_err();
+        assert_eq!(err.to_string(), "UR fragments are from different messages");
+        assert!(EncryptedMessage::from_ur_fragments(&["ur:encrypted/1-2/zzzz"]).is_err());
+    }
+
+    #[test]
+    fn test_decrypt_with_any() {
+        let keys = [SymmetricKey::new(), KEY, SymmetricKey::new()];
+        let encrypted_message = encrypted_message();
+        assert_eq!(encrypted_message.decrypt_with_any(&keys), Some((1, PLAINTEXT.to_vec())));
+        assert_eq!(encrypted_message.decrypt_with_any(&[keys[0].clone(), keys[2].clone()]), None);
+        assert_eq!(encrypted_message.decrypt_with_any(&[]), None);
+    }
+
+    #[test]
+    fn test_rekey() {
+        let old = SymmetricKey::new();
+        let new = SymmetricKey::new();
+        for encrypted_message in [
+            old.encrypt(PLAINTEXT, Some(AAD), None::<Nonce>),
+            old.encrypt_committing(PLAINTEXT, Some(AAD), None::<Nonce>, AeadAlgorithm::Aes256Gcm),
+        ] {
+            let rekeyed = encrypted_message.rekey(&old, &new).unwrap();
+            assert_eq!(rekeyed.algorithm(), encrypted_message.algorithm());
+            assert_eq!(rekeyed.aad(), AAD);
+            assert_eq!(rekeyed.is_key_committing(), encrypted_message.is_key_committing());
+            assert_ne!(rekeyed.nonce(), encrypted_message.nonce());
+            assert!(old.decrypt(&rekeyed).is_err());
+            assert_eq!(new.decrypt(&rekeyed).unwrap(), PLAINTEXT);
+        }
+        let encrypted_message = old.encrypt(PLAINTEXT, None::<Vec<u8>>, None::<Nonce>);
+        assert!(encrypted_message.rekey(&new, &old).is_err());
+    }
+
+    #[test]
+    fn test_non_canonical_cbor_rejected() {
+        let message = KEY.encrypt(PLAINTEXT, None::<Vec<u8>>, Some(NONCE));
+        let CBORCase::Array(elements) = message.untagged_cbor().into_case() else {
+            panic!("expected array");
+        };
+        assert_eq!(elements.len(), 3);
+        let decode = |elements: Vec<CBOR>| EncryptedMessage::from_untagged_cbor(elements.into());
+        assert_eq!(decode(elements.clone()).unwrap(), message);
+
+        // An explicit empty AAD with nothing after it.
+        let mut empty_aad = elements.clone();
+        empty_aad.push(CBOR::to_byte_string(Vec::<u8>::new()));
+        // A null key commitment that isn't followed by a hint.
+        let mut null_commitment = empty_aad.clone();
+        null_commitment.push(CBOR::null());
+        // Anything after the hint.
+        let mut trailing = null_commitment.clone();
+        trailing.extend([CBOR::to_byte_string(b"hint"), CBOR::to_byte_string(b"extra")]);
+        for elements in [empty_aad, null_commitment, trailing] {
+            let err = decode(elements).unwrap_err();
+            assert_eq!(
+                err.downcast_ref::<ComponentError>(),
+                Some(&ComponentError::InvalidFormat { component: "EncryptedMessage" })
+            );
+        }
+
+        // Every encoding this crate produces still round-trips.
+        for message in [
+            message.clone(),
+            message.clone().with_hint(b"hint"),
+            KEY.encrypt_committing(PLAINTEXT, None::<Vec<u8>>, Some(NONCE), AeadAlgorithm::Aes256Gcm),
+        ] {
+            assert_eq!(EncryptedMessage::from_untagged_cbor(message.untagged_cbor()).unwrap(), message);
+        }
+    }
+
+    #[test]
+    fn test_hint() {
+        let key = SymmetricKey::new();
+        let key_id = crate::ARID::new();
+        for encrypted_message in [
+            key.encrypt(PLAINTEXT, None::<Vec<u8>>, None::<Nonce>),
+            key.encrypt(PLAINTEXT, Some(AAD), None::<Nonce>),
+            key.encrypt_committing(PLAINTEXT, Some(AAD), None::<Nonce>, AeadAlgorithm::Aes256Gcm),
+        ] {
+            assert_eq!(encrypted_message.hint(), None);
+            let hinted = encrypted_message.clone().with_hint(key_id.data());
+            let decoded = EncryptedMessage::from_tagged_cbor_data(hinted.tagged_cbor_data()).unwrap();
+            assert_eq!(decoded, hinted);
+            assert_eq!(crate::ARID::from_data_ref(decoded.hint().unwrap()).unwrap(), key_id);
+            assert_eq!(decoded.is_key_committing(), encrypted_message.is_key_committing());
+            assert_eq!(decoded.aad(), encrypted_message.aad());
+            assert_eq!(key.decrypt(&decoded).unwrap(), PLAINTEXT);
+
+            // The hint isn't authenticated, so changing it doesn't affect decryption.
+            let tampered = decoded.with_hint(b"other key");
+            assert_eq!(key.decrypt(&tampered).unwrap(), PLAINTEXT);
+        }
+    }
+
+    #[test]
+    fn test_open_and_verify() {
+        use crate::{ ECPrivateKey, Signer, SigningPrivateKey };
+
+        let key = SymmetricKey::new();
+        let signer = SigningPrivateKey::new_schnorr(ECPrivateKey::new());
+        let signature = signer.sign(&PLAINTEXT).unwrap();
+        let encrypted_message = key.encrypt(PLAINTEXT, Some(AAD), None::<Nonce>);
+
+        let plaintext = encrypted_message.open_and_verify(&key, &signature, &signer.public_key()).unwrap();
+        assert_eq!(plaintext, PLAINTEXT);
+
+        let mut ciphertext = encrypted_message.ciphertext().clone();
+        ciphertext[0] ^= 1;
+        let tampered = EncryptedMessage::new(
+            ciphertext,
+            encrypted_message.aad(),
+            encrypted_message.nonce().clone(),
+            encrypted_message.authentication_tag().clone()
+        );
+        assert!(tampered.open_and_verify(&key, &signature, &signer.public_key()).is_err());
+        assert!(encrypted_message.open_and_verify(&SymmetricKey::new(), &signature, &signer.public_key()).is_err());
+
+        let other = SigningPrivateKey::new_schnorr(ECPrivateKey::new()).public_key();
+        let err = encrypted_message.open_and_verify(&key, &signature, &other).unwrap_err();
+        assert_eq!(err.to_string(), "Signature verification failed");
+    }
 }
@@ -0,0 +1,326 @@
+use std::io::{ self, Read, Write };
+use bc_crypto::{ aead_chacha20_poly1305_encrypt_with_aad, aead_chacha20_poly1305_decrypt_with_aad };
+use anyhow::{ bail, Result };
+
+use crate::{ Nonce, SymmetricKey };
+
+const AUTH_SIZE: usize = 16;
+const HEADER_SIZE: usize = Nonce::NONCE_SIZE + 4;
+
+/// The largest frame size a stream may declare, which bounds the memory a
+/// `StreamDecryptor` allocates for an untrusted stream.
+pub const MAX_STREAM_FRAME_SIZE: usize = 16 * 1024 * 1024;
+
+/// The framing state shared by `StreamEncryptor` and `StreamDecryptor`.
+struct Framing {
+    key: SymmetricKey,
+    header: [u8; HEADER_SIZE],
+    frame_size: usize,
+    counter: u64,
+}
+
+impl Framing {
+    fn new(key: &SymmetricKey, nonce: Nonce, frame_size: usize) -> Result<Self> {
+        if frame_size == 0 || frame_size > MAX_STREAM_FRAME_SIZE {
+            bail!("Invalid stream frame size");
+        }
+        let mut header = [0u8; HEADER_SIZE];
+        header[..Nonce::NONCE_SIZE].copy_from_slice(nonce.data());
+        header[Nonce::NONCE_SIZE..].copy_from_slice(&(frame_size as u32).to_be_bytes());
+        Ok(Self { key: key.clone(), header, frame_size, counter: 0 })
+    }
+
+    fn from_header(key: &SymmetricKey, header: [u8; HEADER_SIZE]) -> Result<Self> {
+        let nonce = Nonce::from_data_ref(&header[..Nonce::NONCE_SIZE])?;
+        let frame_size = u32::from_be_bytes(header[Nonce::NONCE_SIZE..].try_into().unwrap());
+        Self::new(key, nonce, frame_size as usize)
+    }
+
+    fn frame_nonce(&self) -> [u8; Nonce::NONCE_SIZE] {
+        let mut nonce: [u8; Nonce::NONCE_SIZE] = self.header[..Nonce::NONCE_SIZE]
+            .try_into()
+            .unwrap();
+        for (n, c) in nonce[4..].iter_mut().zip(self.counter.to_be_bytes()) {
+            *n ^= c;
+        }
+        nonce
+    }
+
+    fn frame_aad(&self, is_final: bool) -> Vec<u8> {
+        let mut aad = self.header.to_vec();
+        aad.extend_from_slice(&self.counter.to_be_bytes());
+        aad.push(is_final as u8);
+        aad
+    }
+
+    fn advance(&mut self) -> io::Result<()> {
+        self.counter = self.counter
+            .checked_add(1)
+            .ok_or_else(|| invalid_data("encrypted stream frame counter overflow"))?;
+        Ok(())
+    }
+
+    fn seal(&mut self, plaintext: &[u8], is_final: bool) -> io::Result<Vec<u8>> {
+        let (mut frame, auth) = aead_chacha20_poly1305_encrypt_with_aad(
+            plaintext,
+            self.key.data(),
+            &self.frame_nonce(),
+            self.frame_aad(is_final)
+        );
+        frame.extend_from_slice(&auth);
+        self.advance()?;
+        Ok(frame)
+    }
+
+    fn open(&mut self, frame: &[u8], is_final: bool) -> io::Result<Vec<u8>> {
+        if frame.len() < AUTH_SIZE {
+            return Err(invalid_data("truncated encrypted stream"));
+        }
+        let (ciphertext, auth) = frame.split_at(frame.len() - AUTH_SIZE);
+        let plaintext = aead_chacha20_poly1305_decrypt_with_aad(
+            ciphertext,
+            self.key.data(),
+            &self.frame_nonce(),
+            self.frame_aad(is_final),
+            auth.try_into().unwrap()
+        ).map_err(|_| invalid_data("encrypted stream authentication failed"))?;
+        self.advance()?;
+        Ok(plaintext)
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Encrypts a stream of arbitrary length under a `SymmetricKey`, in frames of
+/// a fixed size, writing the result to the wrapped writer.
+///
+/// Plaintext written to the encryptor is buffered until a whole frame is
+/// available. `finish` must be called once all the plaintext has been
+/// written, to write the final frame; a stream that is never finished is
+/// rejected by `StreamDecryptor` as truncated.
+///
+/// # Format
+///
+/// A stream starts with a 16-byte header: a random 12-byte base nonce
+/// followed by the frame size as a big-endian `u32`. Then come the frames,
+/// each the ChaCha20-Poly1305 ciphertext of up to `frame_size` bytes of
+/// plaintext followed by its 16-byte authentication tag.
+///
+/// Frame `i` is encrypted with the base nonce whose last eight bytes are
+/// XORed with `i` as a big-endian `u64`. Its additional authenticated data is
+/// the header, then `i` as a big-endian `u64`, then `1` for the final frame
+/// or `0` otherwise. Every frame but the final one carries exactly
+/// `frame_size` bytes of plaintext, and the final frame carries fewer,
+/// possibly none. Reordering, dropping, or appending frames therefore fails
+/// authentication, as does cutting the stream after any frame but the final
+/// one.
+pub struct StreamEncryptor<W: Write> {
+    framing: Framing,
+    writer: W,
+    buffer: Vec<u8>,
+}
+
+impl<W: Write> StreamEncryptor<W> {
+    /// Starts a new stream with a random base nonce, writing its header to
+    /// `writer`.
+    ///
+    /// Returns an error if `frame_size` is zero or larger than
+    /// `MAX_STREAM_FRAME_SIZE`, or if the header can't be written.
+    pub fn new(key: &SymmetricKey, writer: W, frame_size: usize) -> Result<Self> {
+        let framing = Framing::new(key, Nonce::new(), frame_size)?;
+        let mut writer = writer;
+        writer.write_all(&framing.header)?;
+        Ok(Self { buffer: Vec::with_capacity(frame_size), framing, writer })
+    }
+
+    fn write_frame(&mut self, is_final: bool) -> io::Result<()> {
+        let frame = self.framing.seal(&self.buffer, is_final)?;
+        self.buffer.clear();
+        self.writer.write_all(&frame)
+    }
+
+    /// Writes the final frame and returns the wrapped writer.
+    pub fn finish(mut self) -> Result<W> {
+        self.write_frame(true)?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+impl<W: Write> Write for StreamEncryptor<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let count = buf.len().min(self.framing.frame_size - self.buffer.len());
+        self.buffer.extend_from_slice(&buf[..count]);
+        if self.buffer.len() == self.framing.frame_size {
+            self.write_frame(false)?;
+        }
+        Ok(count)
+    }
+
+    /// Flushes the wrapped writer. Buffered plaintext that doesn't yet fill a
+    /// frame is kept until the frame is full or the stream is finished.
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Decrypts a stream written by `StreamEncryptor`, reading from the wrapped
+/// reader.
+///
+/// Each frame is authenticated before any of its plaintext is returned. A
+/// stream that has been tampered with, reordered, or truncated makes `read`
+/// return an error of kind `InvalidData`.
+pub struct StreamDecryptor<R: Read> {
+    framing: Framing,
+    reader: R,
+    plaintext: Vec<u8>,
+    position: usize,
+    finished: bool,
+}
+
+impl<R: Read> StreamDecryptor<R> {
+    /// Starts decrypting a stream, reading its header from `reader`.
+    ///
+    /// Returns an error if the header can't be read or declares an invalid
+    /// frame size.
+    pub fn new(key: &SymmetricKey, reader: R) -> Result<Self> {
+        let mut reader = reader;
+        let mut header = [0u8; HEADER_SIZE];
+        reader.read_exact(&mut header)?;
+        let framing = Framing::from_header(key, header)?;
+        Ok(Self { framing, reader, plaintext: Vec::new(), position: 0, finished: false })
+    }
+
+    /// Reads until `buf` is full or the reader is exhausted, returning the
+    /// number of bytes read.
+    fn read_full(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut count = 0;
+        while count < buf.len() {
+            match self.reader.read(&mut buf[count..]) {
+                Ok(0) => break,
+                Ok(n) => count += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => (),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(count)
+    }
+
+    fn read_frame(&mut self) -> io::Result<()> {
+        let mut frame = vec![0u8; self.framing.frame_size + AUTH_SIZE];
+        let count = self.read_full(&mut frame)?;
+        let is_final = count < frame.len();
+        self.plaintext = self.framing.open(&frame[..count], is_final)?;
+        self.position = 0;
+        if is_final {
+            if self.read_full(&mut [0u8; 1])? != 0 {
+                return Err(invalid_data("unexpected data after encrypted stream"));
+            }
+            self.finished = true;
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for StreamDecryptor<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position == self.plaintext.len() && !self.finished {
+            self.read_frame()?;
+        }
+        let count = buf.len().min(self.plaintext.len() - self.position);
+        buf[..count].copy_from_slice(&self.plaintext[self.position..self.position + count]);
+        self.position += count;
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{ self, Read, Write };
+
+    use super::{ StreamDecryptor, StreamEncryptor, AUTH_SIZE, HEADER_SIZE };
+    use crate::SymmetricKey;
+
+    const FRAME_SIZE: usize = 64 * 1024;
+
+    fn encrypt(key: &SymmetricKey, plaintext: &[u8], frame_size: usize) -> Vec<u8> {
+        let mut encryptor = StreamEncryptor::new(key, Vec::new(), frame_size).unwrap();
+        encryptor.write_all(plaintext).unwrap();
+        encryptor.finish().unwrap()
+    }
+
+    fn decrypt(key: &SymmetricKey, stream: &[u8]) -> io::Result<Vec<u8>> {
+        let mut decryptor = StreamDecryptor::new(key, stream).unwrap();
+        let mut plaintext = Vec::new();
+        decryptor.read_to_end(&mut plaintext)?;
+        Ok(plaintext)
+    }
+
+    #[test]
+    fn test_stream_round_trip() {
+        let key = SymmetricKey::new();
+        // Three full frames and a one-byte final frame.
+        let plaintext = bc_rand::random_data(3 * FRAME_SIZE + 1);
+        let stream = encrypt(&key, &plaintext, FRAME_SIZE);
+        let frames = 4;
+        assert_eq!(stream.len(), HEADER_SIZE + plaintext.len() + frames * AUTH_SIZE);
+        assert_eq!(decrypt(&key, &stream).unwrap(), plaintext);
+
+        // Oddly sized writes buffer into the same frames.
+        let mut encryptor = StreamEncryptor::new(&key, Vec::new(), FRAME_SIZE).unwrap();
+        for chunk in plaintext.chunks(7_777) {
+            encryptor.write_all(chunk).unwrap();
+        }
+        let stream = encryptor.finish().unwrap();
+        assert_eq!(decrypt(&key, &stream).unwrap(), plaintext);
+
+        assert!(decrypt(&SymmetricKey::new(), &stream).is_err());
+    }
+
+    #[test]
+    fn test_empty_and_exact_streams() {
+        let key = SymmetricKey::new();
+        let stream = encrypt(&key, b"", 16);
+        assert_eq!(stream.len(), HEADER_SIZE + AUTH_SIZE);
+        assert!(decrypt(&key, &stream).unwrap().is_empty());
+
+        // An exact multiple of the frame size ends with an empty final frame.
+        let plaintext = [7u8; 48];
+        let stream = encrypt(&key, &plaintext, 16);
+        assert_eq!(stream.len(), HEADER_SIZE + 48 + 4 * AUTH_SIZE);
+        assert_eq!(decrypt(&key, &stream).unwrap(), plaintext);
+    }
+
+    #[test]
+    fn test_dropped_final_frame() {
+        let key = SymmetricKey::new();
+        let plaintext = bc_rand::random_data(3 * FRAME_SIZE + 1000);
+        let stream = encrypt(&key, &plaintext, FRAME_SIZE);
+        let truncated = &stream[..stream.len() - (1000 + AUTH_SIZE)];
+        let err = decrypt(&key, truncated).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        // The empty final frame of an exact multiple can't be dropped either.
+        let stream = encrypt(&key, &plaintext[..2 * FRAME_SIZE], FRAME_SIZE);
+        assert!(decrypt(&key, &stream[..stream.len() - AUTH_SIZE]).is_err());
+
+        // Nor can data be appended after it.
+        let mut extended = stream.clone();
+        extended.push(0);
+        assert!(decrypt(&key, &extended).is_err());
+    }
+
+    #[test]
+    fn test_reordered_frames() {
+        let key = SymmetricKey::new();
+        let plaintext = bc_rand::random_data(64);
+        let stream = encrypt(&key, &plaintext, 16);
+        let frame = 16 + AUTH_SIZE;
+        let mut reordered = stream.clone();
+        let (first, second) = (HEADER_SIZE, HEADER_SIZE + frame);
+        reordered[first..second + frame].rotate_left(frame);
+        assert!(decrypt(&key, &reordered).is_err());
+    }
+}
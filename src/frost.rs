@@ -0,0 +1,522 @@
+//! Threshold Schnorr signing in the style of FROST.
+//!
+//! <https://eprint.iacr.org/2020/852>
+//!
+//! A group key is split among `max_signers` participants so that any
+//! `threshold` of them can together produce an ordinary BIP-340 Schnorr
+//! `Signature` that verifies under the group's `SigningPublicKey`. Only
+//! trusted-dealer key generation is provided; distributed key generation is
+//! out of scope.
+//!
+//! Signing takes two rounds:
+//!
+//! 1. Each participant calls `FrostKeyShare::commit`, keeps the returned
+//!    `FrostNonces` secret, and sends the `FrostCommitment` to the aggregator.
+//! 2. The aggregator sends the collected commitments and the message to each
+//!    participant, who calls `FrostKeyShare::sign` and returns a `FrostShare`.
+//!    `FrostPublicKeyPackage::aggregate_shares` checks each share and
+//!    combines them into the signature.
+//!
+//! The message hash and challenge are those of this crate's Schnorr
+//! signatures with an empty tag. The binding factors are derived with the
+//! BIP-340 tagged hash under the tag `"FROST/bc-components/rho"`, so this is
+//! not interoperable with the RFC 9591 ciphersuites.
+
+use alloc::{ collections::BTreeMap, vec, vec::Vec };
+
+use anyhow::{ bail, Result };
+use bc_rand::RandomNumberGenerator;
+use k256::{
+    elliptic_curve::{
+        group::GroupEncoding,
+        ops::Reduce,
+        point::AffineCoordinates,
+        PrimeField,
+    },
+    AffinePoint,
+    ProjectivePoint,
+    Scalar,
+    U256,
+};
+use zeroize::{ Zeroize, ZeroizeOnDrop };
+
+use crate::{
+    ECKeyBase,
+    ECPublicKey,
+    SchnorrPublicKey,
+    Signature,
+    SigningPublicKey,
+    Verifier,
+    tagged_sha256,
+};
+
+const BINDING_FACTOR_TAG: &[u8] = b"FROST/bc-components/rho";
+const CHALLENGE_TAG: &[u8] = b"BIP0340/challenge";
+
+/// One participant's long-lived share of a FROST group signing key.
+///
+/// The share is zeroized when it is dropped.
+#[derive(Clone, PartialEq, Eq, Zeroize, ZeroizeOnDrop)]
+pub struct FrostKeyShare {
+    identifier: u16,
+    threshold: u16,
+    signing_share: [u8; 32],
+    #[zeroize(skip)]
+    group_public_key: ECPublicKey,
+}
+
+/// The public information about a FROST group: the group public key and the
+/// public key corresponding to each participant's share.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct FrostPublicKeyPackage {
+    threshold: u16,
+    group_public_key: ECPublicKey,
+    verifying_shares: BTreeMap<u16, ECPublicKey>,
+}
+
+/// The secret nonces a participant generates in the first round of signing.
+///
+/// The nonces must be used for at most one signature, so `FrostKeyShare::sign`
+/// consumes them. They are zeroized when they are dropped.
+#[derive(Zeroize, ZeroizeOnDrop)]
+pub struct FrostNonces {
+    identifier: u16,
+    hiding: [u8; 32],
+    binding: [u8; 32],
+}
+
+/// The public commitments to a participant's `FrostNonces`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct FrostCommitment {
+    identifier: u16,
+    hiding: ECPublicKey,
+    binding: ECPublicKey,
+}
+
+/// A participant's share of a FROST signature.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct FrostShare {
+    identifier: u16,
+    share: [u8; 32],
+}
+
+/// Splits a freshly generated group key into `max_signers` shares, any
+/// `threshold` of which can sign, using a trusted dealer.
+///
+/// Participants are identified by `1..=max_signers`. Returns an error unless
+/// `1 <= threshold <= max_signers`.
+#[cfg(feature = "std")]
+pub fn frost_generate_with_dealer(
+    threshold: u16,
+    max_signers: u16
+) -> Result<(Vec<FrostKeyShare>, FrostPublicKeyPackage)> {
+    let mut rng = bc_rand::SecureRandomNumberGenerator;
+    frost_generate_with_dealer_using(threshold, max_signers, &mut rng)
+}
+
+/// Splits a freshly generated group key into `max_signers` shares, any
+/// `threshold` of which can sign, using a trusted dealer and the given random
+/// number generator.
+///
+/// Participants are identified by `1..=max_signers`. Returns an error unless
+/// `1 <= threshold <= max_signers`.
+pub fn frost_generate_with_dealer_using(
+    threshold: u16,
+    max_signers: u16,
+    rng: &mut impl RandomNumberGenerator
+) -> Result<(Vec<FrostKeyShare>, FrostPublicKeyPackage)> {
+    if threshold == 0 || threshold > max_signers {
+        bail!("FROST threshold must be between 1 and the number of signers");
+    }
+
+    // BIP-340 public keys are x-only with even y, so choose the sign of the
+    // group secret to make the group public key's y even.
+    let mut secret = random_scalar(rng);
+    if bool::from((ProjectivePoint::GENERATOR * secret).to_affine().y_is_odd()) {
+        secret = -secret;
+    }
+    let mut coefficients = vec![secret];
+    coefficients.extend((1..threshold).map(|_| random_scalar(rng)));
+
+    let group_public_key = public_key_from_point(&(ProjectivePoint::GENERATOR * secret))?;
+    let mut key_shares = Vec::with_capacity(max_signers as usize);
+    let mut verifying_shares = BTreeMap::new();
+    for identifier in 1..=max_signers {
+        // Evaluate the polynomial at the identifier with Horner's method.
+        let x = Scalar::from(identifier as u64);
+        let share = coefficients.iter().rev().fold(Scalar::ZERO, |acc, c| acc * x + c);
+        verifying_shares.insert(
+            identifier,
+            public_key_from_point(&(ProjectivePoint::GENERATOR * share))?
+        );
+        key_shares.push(FrostKeyShare {
+            identifier,
+            threshold,
+            signing_share: share.to_bytes().into(),
+            group_public_key: group_public_key.clone(),
+        });
+    }
+    coefficients.zeroize();
+
+    let package = FrostPublicKeyPackage { threshold, group_public_key, verifying_shares };
+    Ok((key_shares, package))
+}
+
+impl FrostKeyShare {
+    /// The identifier of the participant holding this share.
+    pub fn identifier(&self) -> u16 {
+        self.identifier
+    }
+
+    /// The number of participants needed to sign.
+    pub fn threshold(&self) -> u16 {
+        self.threshold
+    }
+
+    /// The group public key that signatures made with this share verify under.
+    pub fn group_public_key(&self) -> SigningPublicKey {
+        signing_public_key(&self.group_public_key)
+    }
+
+    /// Generates the nonces and commitments for the first round of signing.
+    #[cfg(feature = "std")]
+    pub fn commit(&self) -> (FrostNonces, FrostCommitment) {
+        let mut rng = bc_rand::SecureRandomNumberGenerator;
+        self.commit_using(&mut rng)
+    }
+
+    /// Generates the nonces and commitments for the first round of signing
+    /// using the given random number generator.
+    pub fn commit_using(&self, rng: &mut impl RandomNumberGenerator) -> (FrostNonces, FrostCommitment) {
+        let hiding = random_scalar(rng);
+        let binding = random_scalar(rng);
+        let commitment = FrostCommitment {
+            identifier: self.identifier,
+            hiding: public_key_from_point(&(ProjectivePoint::GENERATOR * hiding)).unwrap(),
+            binding: public_key_from_point(&(ProjectivePoint::GENERATOR * binding)).unwrap(),
+        };
+        let nonces = FrostNonces {
+            identifier: self.identifier,
+            hiding: hiding.to_bytes().into(),
+            binding: binding.to_bytes().into(),
+        };
+        (nonces, commitment)
+    }
+
+    /// Produces this participant's share of the signature over `message`,
+    /// given the commitments of all the participants taking part.
+    ///
+    /// Returns an error if the nonces or commitments aren't this
+    /// participant's, or if fewer than `threshold` participants take part.
+    pub fn sign(
+        &self,
+        nonces: FrostNonces,
+        commitments: &[FrostCommitment],
+        message: impl AsRef<[u8]>
+    ) -> Result<FrostShare> {
+        if nonces.identifier != self.identifier {
+            bail!("FROST nonces belong to another participant");
+        }
+        let hiding = scalar_from_bytes(&nonces.hiding)?;
+        let binding = scalar_from_bytes(&nonces.binding)?;
+        let own_commitment = commitments.iter().find(|c| c.identifier == self.identifier);
+        let expected = (
+            public_key_from_point(&(ProjectivePoint::GENERATOR * hiding))?,
+            public_key_from_point(&(ProjectivePoint::GENERATOR * binding))?,
+        );
+        if own_commitment.map(|c| (c.hiding.clone(), c.binding.clone())) != Some(expected) {
+            bail!("FROST commitments don't include this participant's commitment");
+        }
+
+        let context = SigningContext::new(&self.group_public_key, self.threshold, commitments, message.as_ref())?;
+        let rho = context.binding_factors[&self.identifier];
+        let signing_share = scalar_from_bytes(&self.signing_share)?;
+        let mut nonce = hiding + binding * rho;
+        if context.negate_nonces {
+            nonce = -nonce;
+        }
+        let share = nonce + context.lagrange_coefficient(self.identifier) * signing_share * context.challenge;
+        Ok(FrostShare { identifier: self.identifier, share: share.to_bytes().into() })
+    }
+}
+
+impl core::fmt::Debug for FrostKeyShare {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "FrostKeyShare({})", self.identifier)
+    }
+}
+
+impl core::fmt::Debug for FrostNonces {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "FrostNonces({})", self.identifier)
+    }
+}
+
+impl FrostPublicKeyPackage {
+    /// The number of participants needed to sign.
+    pub fn threshold(&self) -> u16 {
+        self.threshold
+    }
+
+    /// The group public key that FROST signatures verify under.
+    pub fn group_public_key(&self) -> SigningPublicKey {
+        signing_public_key(&self.group_public_key)
+    }
+
+    /// The public key corresponding to the given participant's key share.
+    pub fn verifying_share(&self, identifier: u16) -> Option<&ECPublicKey> {
+        self.verifying_shares.get(&identifier)
+    }
+
+    /// Checks each signature share against the participant's verifying share
+    /// and combines them into a Schnorr signature over `message` that
+    /// verifies under the group public key.
+    ///
+    /// There must be exactly one share for each commitment. Returns an error
+    /// naming the first participant whose share is invalid.
+    pub fn aggregate_shares(
+        &self,
+        commitments: &[FrostCommitment],
+        shares: &[FrostShare],
+        message: impl AsRef<[u8]>
+    ) -> Result<Signature> {
+        let message = message.as_ref();
+        let context = SigningContext::new(&self.group_public_key, self.threshold, commitments, message)?;
+        if shares.len() != commitments.len() {
+            bail!("FROST requires one signature share per commitment");
+        }
+
+        let mut signature_scalar = Scalar::ZERO;
+        for share in shares {
+            let Some(commitment) = commitments.iter().find(|c| c.identifier == share.identifier) else {
+                bail!("FROST signature share from participant {} has no commitment", share.identifier);
+            };
+            let Some(verifying_share) = self.verifying_shares.get(&share.identifier) else {
+                bail!("Unknown FROST participant {}", share.identifier);
+            };
+            let z = scalar_from_bytes(&share.share)?;
+            let rho = context.binding_factors[&share.identifier];
+            let mut nonce_commitment = point_from_public_key(&commitment.hiding)? +
+                point_from_public_key(&commitment.binding)? * rho;
+            if context.negate_nonces {
+                nonce_commitment = -nonce_commitment;
+            }
+            let lambda = context.lagrange_coefficient(share.identifier);
+            let expected = nonce_commitment + point_from_public_key(verifying_share)? * (lambda * context.challenge);
+            if ProjectivePoint::GENERATOR * z != expected {
+                bail!("Invalid FROST signature share from participant {}", share.identifier);
+            }
+            signature_scalar += z;
+        }
+
+        let mut data = [0u8; 64];
+        data[..32].copy_from_slice(&context.group_commitment.x());
+        data[32..].copy_from_slice(&signature_scalar.to_bytes());
+        let signature = Signature::schnorr_from_data(data, []);
+        if !self.group_public_key().verify(&signature, &message) {
+            bail!("FROST signature does not verify");
+        }
+        Ok(signature)
+    }
+}
+
+impl FrostCommitment {
+    /// The identifier of the participant who made this commitment.
+    pub fn identifier(&self) -> u16 {
+        self.identifier
+    }
+}
+
+impl FrostShare {
+    /// The identifier of the participant who made this share.
+    pub fn identifier(&self) -> u16 {
+        self.identifier
+    }
+}
+
+/// The values every participant and the aggregator derive from the
+/// commitments and message.
+struct SigningContext {
+    binding_factors: BTreeMap<u16, Scalar>,
+    /// The group commitment, with even y.
+    group_commitment: AffinePoint,
+    /// Whether the nonces must be negated to give the group commitment an
+    /// even y.
+    negate_nonces: bool,
+    challenge: Scalar,
+}
+
+impl SigningContext {
+    fn new(
+        group_public_key: &ECPublicKey,
+        threshold: u16,
+        commitments: &[FrostCommitment],
+        message: &[u8]
+    ) -> Result<Self> {
+        let mut sorted: Vec<&FrostCommitment> = commitments.iter().collect();
+        sorted.sort_by_key(|c| c.identifier);
+        if sorted.windows(2).any(|w| w[0].identifier == w[1].identifier) {
+            bail!("Duplicate FROST participant");
+        }
+        if sorted.len() < threshold as usize {
+            bail!("FROST requires at least {} participants", threshold);
+        }
+
+        let message_hash = tagged_sha256(message, &[]);
+        let group_x = &group_public_key.data()[1..];
+        let mut encoded_commitments = Vec::with_capacity(sorted.len() * 68);
+        for c in &sorted {
+            encoded_commitments.extend_from_slice(&c.identifier.to_be_bytes());
+            encoded_commitments.extend_from_slice(c.hiding.data());
+            encoded_commitments.extend_from_slice(c.binding.data());
+        }
+
+        let mut binding_factors = BTreeMap::new();
+        let mut group_commitment = ProjectivePoint::IDENTITY;
+        for c in &sorted {
+            let preimage = [group_x, &message_hash, &encoded_commitments, &c.identifier.to_be_bytes()].concat();
+            let rho = reduce(tagged_sha256(&preimage, BINDING_FACTOR_TAG));
+            group_commitment += point_from_public_key(&c.hiding)? + point_from_public_key(&c.binding)? * rho;
+            binding_factors.insert(c.identifier, rho);
+        }
+        if group_commitment == ProjectivePoint::IDENTITY {
+            bail!("FROST group commitment is the point at infinity");
+        }
+        let mut group_commitment = group_commitment.to_affine();
+        let negate_nonces = bool::from(group_commitment.y_is_odd());
+        if negate_nonces {
+            group_commitment = -group_commitment;
+        }
+
+        let preimage = [&group_commitment.x()[..], group_x, &message_hash].concat();
+        let challenge = reduce(tagged_sha256(&preimage, CHALLENGE_TAG));
+        Ok(Self { binding_factors, group_commitment, negate_nonces, challenge })
+    }
+
+    /// The Lagrange coefficient at zero for the given participant among those
+    /// taking part.
+    fn lagrange_coefficient(&self, identifier: u16) -> Scalar {
+        let x_i = Scalar::from(identifier as u64);
+        let (numerator, denominator) = self.binding_factors
+            .keys()
+            .filter(|&&j| j != identifier)
+            .map(|&j| Scalar::from(j as u64))
+            .fold((Scalar::ONE, Scalar::ONE), |(num, den), x_j| (num * x_j, den * (x_j - x_i)));
+        // The identifiers are distinct and nonzero, so the denominator is too.
+        numerator * denominator.invert().unwrap()
+    }
+}
+
+fn signing_public_key(group_public_key: &ECPublicKey) -> SigningPublicKey {
+    let x_only: [u8; 32] = group_public_key.data()[1..].try_into().unwrap();
+    SigningPublicKey::from_schnorr(SchnorrPublicKey::from_data(x_only))
+}
+
+fn random_scalar(rng: &mut impl RandomNumberGenerator) -> Scalar {
+    loop {
+        let mut bytes = [0u8; 32];
+        rng.fill_random_data(&mut bytes);
+        if let Ok(scalar) = scalar_from_bytes(&bytes) {
+            if scalar != Scalar::ZERO {
+                return scalar;
+            }
+        }
+    }
+}
+
+fn scalar_from_bytes(bytes: &[u8; 32]) -> Result<Scalar> {
+    Option::from(Scalar::from_repr((*bytes).into())).ok_or_else(|| anyhow::anyhow!("Invalid FROST scalar"))
+}
+
+fn reduce(hash: [u8; 32]) -> Scalar {
+    <Scalar as Reduce<U256>>::reduce_bytes(&hash.into())
+}
+
+fn point_from_public_key(key: &ECPublicKey) -> Result<ProjectivePoint> {
+    let bytes: [u8; 33] = key.data().try_into().unwrap();
+    Option::from(ProjectivePoint::from_bytes(&bytes.into())).ok_or_else(|| anyhow::anyhow!("Invalid FROST public key"))
+}
+
+fn public_key_from_point(point: &ProjectivePoint) -> Result<ECPublicKey> {
+    if *point == ProjectivePoint::IDENTITY {
+        bail!("FROST public key is the point at infinity");
+    }
+    ECPublicKey::from_data_ref(point.to_affine().to_bytes())
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use bc_rand::make_fake_random_number_generator;
+
+    use super::frost_generate_with_dealer;
+    use super::{ frost_generate_with_dealer_using, FrostKeyShare };
+    use crate::{ FrostCommitment, FrostNonces, Verifier };
+
+    const MESSAGE: &[u8] = b"Wolf McNally";
+
+    fn round_1(signers: &[&FrostKeyShare]) -> (Vec<FrostNonces>, Vec<FrostCommitment>) {
+        signers.iter().map(|signer| signer.commit()).unzip()
+    }
+
+    #[test]
+    fn test_two_of_three() {
+        let mut rng = make_fake_random_number_generator();
+        let (key_shares, package) = frost_generate_with_dealer_using(2, 3, &mut rng).unwrap();
+        assert_eq!(key_shares.len(), 3);
+        let group_public_key = package.group_public_key();
+        assert_eq!(key_shares[0].group_public_key(), group_public_key);
+
+        for signers in [[0, 1], [0, 2], [2, 1]] {
+            let signers: Vec<_> = signers.iter().map(|&i| &key_shares[i]).collect();
+            let (nonces, commitments) = round_1(&signers);
+            let shares: Vec<_> = signers
+                .iter()
+                .zip(nonces)
+                .map(|(signer, nonces)| signer.sign(nonces, &commitments, MESSAGE).unwrap())
+                .collect();
+            let signature = package.aggregate_shares(&commitments, &shares, MESSAGE).unwrap();
+            assert!(group_public_key.verify(&signature, &MESSAGE));
+            assert!(!group_public_key.verify(&signature, &b"Other message"));
+        }
+    }
+
+    #[test]
+    fn test_below_threshold() {
+        let (key_shares, package) = frost_generate_with_dealer(2, 3).unwrap();
+        let (nonces, commitments) = round_1(&[&key_shares[0]]);
+        let err = key_shares[0].sign(nonces.into_iter().next().unwrap(), &commitments, MESSAGE).unwrap_err();
+        assert_eq!(err.to_string(), "FROST requires at least 2 participants");
+        assert!(package.aggregate_shares(&commitments, &[], MESSAGE).is_err());
+        assert!(frost_generate_with_dealer(4, 3).is_err());
+        assert!(frost_generate_with_dealer(0, 3).is_err());
+    }
+
+    #[test]
+    fn test_invalid_share() {
+        let (key_shares, package) = frost_generate_with_dealer(2, 3).unwrap();
+        let signers = [&key_shares[0], &key_shares[1]];
+        let (nonces, commitments) = round_1(&signers);
+        let mut nonces = nonces.into_iter();
+        let good = signers[0].sign(nonces.next().unwrap(), &commitments, MESSAGE).unwrap();
+        // The second participant signs a different message.
+        let bad = signers[1].sign(nonces.next().unwrap(), &commitments, b"Other message").unwrap();
+        let err = package.aggregate_shares(&commitments, &[good.clone(), bad], MESSAGE).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid FROST signature share from participant 2");
+
+        // A share is required for every commitment.
+        assert!(package.aggregate_shares(&commitments, &[good], MESSAGE).is_err());
+    }
+
+    #[test]
+    fn test_mismatched_nonces() {
+        let (key_shares, _) = frost_generate_with_dealer(2, 3).unwrap();
+        let (nonces, commitments) = round_1(&[&key_shares[0], &key_shares[1]]);
+        let mut nonces = nonces.into_iter();
+        let first = nonces.next().unwrap();
+        assert!(key_shares[1].sign(first, &commitments, MESSAGE).is_err());
+
+        // Commitments that omit the signer are rejected.
+        let second = nonces.next().unwrap();
+        assert!(key_shares[1].sign(second, &commitments[..1], MESSAGE).is_err());
+    }
+}
@@ -1,3 +1,4 @@
+use alloc::{ format, string::{ String, ToString }, vec::Vec };
 use bc_crypto::hash::hkdf_hmac_sha256;
 use rand_core::{ CryptoRng, RngCore };
 use zeroize::ZeroizeOnDrop;
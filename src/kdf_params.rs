@@ -0,0 +1,136 @@
+use alloc::{ vec, vec::Vec };
+use bc_ur::prelude::*;
+use crate::{ tags, ComponentError };
+use anyhow::{ anyhow, bail, Result, Error };
+
+/// Parameters for deriving a `SymmetricKey` from a password.
+///
+/// The parameters are not secret, and are meant to be stored alongside the
+/// ciphertext and the `Salt` so that the recipient can re-derive the key. See
+/// `SymmetricKey::from_password`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum KdfParams {
+    /// Argon2id (RFC 9106).
+    Argon2id {
+        /// Memory cost in KiB.
+        memory_kib: u32,
+        /// Number of passes over the memory.
+        iterations: u32,
+        /// Degree of parallelism.
+        parallelism: u32,
+    },
+    /// scrypt (RFC 7914).
+    Scrypt {
+        /// Base-2 logarithm of the CPU/memory cost `N`.
+        log_n: u8,
+        /// Block size.
+        r: u32,
+        /// Parallelization.
+        p: u32,
+    },
+}
+
+impl KdfParams {
+    /// Argon2id with the OWASP-recommended minimum of 19 MiB of memory, two
+    /// iterations, and a parallelism of one.
+    pub const fn argon2id() -> Self {
+        Self::Argon2id { memory_kib: 19 * 1024, iterations: 2, parallelism: 1 }
+    }
+
+    /// scrypt with the RFC 7914 interactive-login parameters `N = 2^15`,
+    /// `r = 8`, `p = 1`.
+    pub const fn scrypt() -> Self {
+        Self::Scrypt { log_n: 15, r: 8, p: 1 }
+    }
+
+    /// Derive `out.len()` bytes of key material from the password and salt.
+    pub(crate) fn derive_into(&self, password: &[u8], salt: &[u8], out: &mut [u8]) -> Result<()> {
+        match *self {
+            Self::Argon2id { memory_kib, iterations, parallelism } => {
+                let params = argon2::Params::new(memory_kib, iterations, parallelism, Some(out.len()))
+                    .map_err(|e| anyhow!("Invalid Argon2id parameters: {}", e))?;
+                argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params)
+                    .hash_password_into(password, salt, out)
+                    .map_err(|e| anyhow!("Argon2id derivation failed: {}", e))
+            }
+            Self::Scrypt { log_n, r, p } => {
+                let params = scrypt::Params::new(log_n, r, p, out.len())
+                    .map_err(|e| anyhow!("Invalid scrypt parameters: {}", e))?;
+                scrypt::scrypt(password, salt, &params, out)
+                    .map_err(|e| anyhow!("scrypt derivation failed: {}", e))
+            }
+        }
+    }
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self::argon2id()
+    }
+}
+
+impl AsRef<KdfParams> for KdfParams {
+    fn as_ref(&self) -> &KdfParams {
+        self
+    }
+}
+
+impl CBORTagged for KdfParams {
+    fn cbor_tags() -> Vec<Tag> {
+        vec![tags::KDF_PARAMS]
+    }
+}
+
+impl From<KdfParams> for CBOR {
+    fn from(value: KdfParams) -> Self {
+        value.tagged_cbor()
+    }
+}
+
+impl CBORTaggedEncodable for KdfParams {
+    fn untagged_cbor(&self) -> CBOR {
+        match *self {
+            Self::Argon2id { memory_kib, iterations, parallelism } => {
+                vec![CBOR::from(1), memory_kib.into(), iterations.into(), parallelism.into()].into()
+            }
+            Self::Scrypt { log_n, r, p } => {
+                vec![CBOR::from(2), log_n.into(), r.into(), p.into()].into()
+            }
+        }
+    }
+}
+
+impl TryFrom<CBOR> for KdfParams {
+    type Error = Error;
+
+    fn try_from(cbor: CBOR) -> Result<Self, Self::Error> {
+        Self::from_tagged_cbor(cbor)
+    }
+}
+
+impl CBORTaggedDecodable for KdfParams {
+    fn from_tagged_cbor(cbor: CBOR) -> Result<Self> {
+        crate::component_error::decode_tagged(cbor, "KdfParams")
+    }
+
+    fn from_untagged_cbor(untagged_cbor: CBOR) -> Result<Self> {
+        let elements = untagged_cbor.try_into_array()?;
+        if elements.len() != 4 {
+            bail!(ComponentError::InvalidFormat { component: "KdfParams" });
+        }
+        let algorithm = u64::try_from(elements[0].clone())?;
+        match algorithm {
+            1 => Ok(Self::Argon2id {
+                memory_kib: u32::try_from(elements[1].clone())?,
+                iterations: u32::try_from(elements[2].clone())?,
+                parallelism: u32::try_from(elements[3].clone())?,
+            }),
+            2 => Ok(Self::Scrypt {
+                log_n: u8::try_from(elements[1].clone())?,
+                r: u32::try_from(elements[2].clone())?,
+                p: u32::try_from(elements[3].clone())?,
+            }),
+            _ => bail!(ComponentError::UnknownAlgorithm { component: "KdfParams", value: algorithm }),
+        }
+    }
+}
@@ -1,20 +1,28 @@
-use std::rc::Rc;
-use bc_rand::fill_random_data;
+use alloc::{ collections::BTreeSet, rc::Rc, string::String, vec, vec::Vec };
+use core::str::FromStr;
+use bc_rand::RandomNumberGenerator;
 use bc_ur::prelude::*;
-use crate::tags;
-use anyhow::{ bail, Error, Result };
+use crate::{ tags, ComponentError };
+use anyhow::{ anyhow, bail, Error, Result };
 
 /// A random nonce ("number used once").
-#[derive(Clone, Eq, PartialEq)]
+#[derive(Clone, Eq, PartialEq, Hash)]
 pub struct Nonce([u8; Self::NONCE_SIZE]);
 
 impl Nonce {
     pub const NONCE_SIZE: usize = 12;
 
     /// Create a new random nonce.
+    #[cfg(feature = "std")]
     pub fn new() -> Self {
+        let mut rng = bc_rand::SecureRandomNumberGenerator;
+        Self::new_using(&mut rng)
+    }
+
+    /// Create a new random nonce using the given random number generator.
+    pub fn new_using(rng: &mut impl RandomNumberGenerator) -> Self {
         let mut data = [0u8; Self::NONCE_SIZE];
-        fill_random_data(&mut data);
+        rng.fill_random_data(&mut data);
         Self(data)
     }
 
@@ -27,7 +35,7 @@ impl Nonce {
     pub fn from_data_ref(data: impl AsRef<[u8]>) -> Result<Self> {
         let data = data.as_ref();
         if data.len() != Self::NONCE_SIZE {
-            bail!("Invalid nonce size");
+            bail!(ComponentError::InvalidLength { component: "nonce" });
         }
         let mut arr = [0u8; Self::NONCE_SIZE];
         arr.copy_from_slice(data);
@@ -39,6 +47,14 @@ impl Nonce {
         self.into()
     }
 
+    /// Returns `true` if every byte of the nonce is zero.
+    ///
+    /// An all-zero nonce is the one most likely to be reused by mistake, for
+    /// example from an uninitialized buffer.
+    pub fn is_zero(&self) -> bool {
+        self.0.iter().all(|&byte| byte == 0)
+    }
+
     /// Create a new nonce from the given hexadecimal string.
     ///
     /// # Panics
@@ -51,8 +67,100 @@ impl Nonce {
     pub fn hex(&self) -> String {
         hex::encode(self.data())
     }
+
+    /// Create a nonce from a counter, with a zero prefix.
+    ///
+    /// See `NonceSequence` for the byte layout. Counter zero gives the
+    /// all-zero nonce, which `SymmetricKey::encrypt_with_nonce` rejects.
+    pub fn from_counter(counter: u64) -> Self {
+        Self::from_prefix_and_counter([0u8; NonceSequence::PREFIX_SIZE], counter)
+    }
+
+    /// Create a nonce from a fixed prefix and a counter.
+    ///
+    /// See `NonceSequence` for the byte layout.
+    pub fn from_prefix_and_counter(prefix: [u8; NonceSequence::PREFIX_SIZE], counter: u64) -> Self {
+        let mut data = [0u8; Self::NONCE_SIZE];
+        data[..NonceSequence::PREFIX_SIZE].copy_from_slice(&prefix);
+        data[NonceSequence::PREFIX_SIZE..].copy_from_slice(&counter.to_be_bytes());
+        Self(data)
+    }
+
+    /// The counter of a nonce made by `from_counter`, `from_prefix_and_counter`,
+    /// or a `NonceSequence`: the last eight bytes as a big-endian integer.
+    pub fn counter_value(&self) -> u64 {
+        u64::from_be_bytes(self.0[NonceSequence::PREFIX_SIZE..].try_into().unwrap())
+    }
+}
+
+/// A sequence of nonces made from a monotonically increasing 64-bit counter,
+/// which never repeats under a single key and needs no random number
+/// generator.
+///
+/// Each 12-byte nonce is a fixed 4-byte prefix followed by the counter as an
+/// 8-byte big-endian integer:
+///
+/// ```text
+/// | prefix (4 bytes) | counter (8 bytes, big-endian) |
+/// ```
+///
+/// The prefix is zero unless one is given, and can tell apart several senders
+/// that share a key. With a zero prefix the sequence skips counter zero, so it
+/// never produces the all-zero nonce. The sequence ends, rather than wrapping
+/// around, after the nonce with counter `u64::MAX`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NonceSequence {
+    prefix: [u8; Self::PREFIX_SIZE],
+    next_counter: Option<u64>,
 }
 
+impl NonceSequence {
+    pub const PREFIX_SIZE: usize = 4;
+
+    /// Create a sequence with a zero prefix, starting at counter one.
+    pub fn new() -> Self {
+        Self::with_prefix([0u8; Self::PREFIX_SIZE])
+    }
+
+    /// Create a sequence with the given prefix, starting at counter zero, or
+    /// at counter one if the prefix is zero.
+    pub fn with_prefix(prefix: [u8; Self::PREFIX_SIZE]) -> Self {
+        Self::starting_at(prefix, 0)
+    }
+
+    /// Create a sequence with the given prefix, starting at the given counter,
+    /// for example to resume a sequence after the last counter used.
+    ///
+    /// A zero prefix with counter zero starts at counter one instead.
+    pub fn starting_at(prefix: [u8; Self::PREFIX_SIZE], counter: u64) -> Self {
+        let counter = if prefix == [0u8; Self::PREFIX_SIZE] { counter.max(1) } else { counter };
+        Self { prefix, next_counter: Some(counter) }
+    }
+
+    /// The counter of the next nonce, or `None` if the sequence is exhausted.
+    pub fn next_counter(&self) -> Option<u64> {
+        self.next_counter
+    }
+}
+
+impl Default for NonceSequence {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Iterator for NonceSequence {
+    type Item = Nonce;
+
+    /// Returns the next nonce, or `None` once the counter is exhausted.
+    fn next(&mut self) -> Option<Nonce> {
+        let counter = self.next_counter?;
+        self.next_counter = counter.checked_add(1);
+        Some(Nonce::from_prefix_and_counter(self.prefix, counter))
+    }
+}
+
+#[cfg(feature = "std")]
 impl Default for Nonce {
     fn default() -> Self {
         Self::new()
@@ -71,6 +179,26 @@ impl<'a> From<&'a Nonce> for &'a [u8; Nonce::NONCE_SIZE] {
     }
 }
 
+impl AsRef<[u8]> for Nonce {
+    fn as_ref(&self) -> &[u8] {
+        self.data()
+    }
+}
+
+impl TryFrom<&[u8]> for Nonce {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_data_ref(data)
+    }
+}
+
+impl From<[u8; Self::NONCE_SIZE]> for Nonce {
+    fn from(data: [u8; Self::NONCE_SIZE]) -> Self {
+        Self::from_data(data)
+    }
+}
+
 impl AsRef<Nonce> for Nonce {
     fn as_ref(&self) -> &Self {
         self
@@ -104,18 +232,46 @@ impl TryFrom<CBOR> for Nonce {
 }
 
 impl CBORTaggedDecodable for Nonce {
+    fn from_tagged_cbor(cbor: CBOR) -> Result<Self> {
+        crate::component_error::decode_tagged(cbor, "nonce")
+    }
+
     fn from_untagged_cbor(untagged_cbor: CBOR) -> Result<Self> {
         let data = CBOR::try_into_byte_string(untagged_cbor)?;
         Self::from_data_ref(data)
     }
 }
 
-impl std::fmt::Debug for Nonce {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Debug for Nonce {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "Nonce({})", self.hex())
     }
 }
 
+impl core::fmt::Display for Nonce {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.hex())
+    }
+}
+
+/// Parses a 24-digit hexadecimal string, as produced by `Display`.
+impl FromStr for Nonce {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let data = hex::decode(s.trim()).map_err(|e| anyhow!("Invalid Nonce hex: {}", e))?;
+        Self::from_data_ref(data)
+    }
+}
+
+impl TryFrom<&str> for Nonce {
+    type Error = Error;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
 // Convert from a reference to a byte vector to a Nonce.
 impl From<&Nonce> for Nonce {
     fn from(nonce: &Nonce) -> Self {
@@ -137,9 +293,56 @@ impl From<&Nonce> for Vec<u8> {
     }
 }
 
+/// Records the nonces used with a single key, so that reusing one is caught
+/// before it can compromise confidentiality.
+///
+/// Use one tracker per key: the same nonce may safely be used once with each
+/// of several different keys.
+#[derive(Clone, Debug, Default)]
+pub struct NonceTracker(BTreeSet<[u8; Nonce::NONCE_SIZE]>);
+
+impl NonceTracker {
+    /// Create a new tracker that has seen no nonces.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the use of the given nonce.
+    ///
+    /// Returns an error, and records nothing, if the nonce has been recorded
+    /// before.
+    pub fn record(&mut self, nonce: &Nonce) -> Result<()> {
+        if !self.0.insert(*nonce.data()) {
+            bail!(ComponentError::NonceReuse);
+        }
+        Ok(())
+    }
+
+    /// Returns `true` if the given nonce has been recorded.
+    pub fn contains(&self, nonce: &Nonce) -> bool {
+        self.0.contains(nonce.data())
+    }
+
+    /// The number of nonces recorded.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if no nonces have been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use super::Nonce;
+    #[cfg(feature = "std")]
+    use core::str::FromStr;
+
+    use super::{ Nonce, NonceSequence };
+    #[cfg(feature = "std")]
+    use super::NonceTracker;
+    #[cfg(feature = "std")]
     use dcbor::prelude::*;
 
     #[test]
@@ -163,6 +366,16 @@ mod test {
         assert!(nonce.is_err());
     }
 
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_nonce_is_zero() {
+        assert!(Nonce::from_data([0u8; Nonce::NONCE_SIZE]).is_zero());
+        assert!(Nonce::from_counter(0).is_zero());
+        assert!(!Nonce::from_counter(1).is_zero());
+        assert!(!Nonce::new().is_zero());
+    }
+
+    #[cfg(feature = "std")]
     #[test]
     fn test_nonce_new() {
         let nonce1 = Nonce::new();
@@ -170,6 +383,7 @@ mod test {
         assert_ne!(nonce1.data(), nonce2.data());
     }
 
+    #[cfg(feature = "std")]
     #[test]
     fn test_nonce_hex_roundtrip() {
         let nonce = Nonce::new();
@@ -178,6 +392,7 @@ mod test {
         assert_eq!(nonce, nonce_from_hex);
     }
 
+    #[cfg(feature = "std")]
     #[test]
     fn test_nonce_cbor_roundtrip() {
         let nonce = Nonce::new();
@@ -185,4 +400,52 @@ mod test {
         let decoded_nonce = Nonce::try_from(cbor).unwrap();
         assert_eq!(nonce, decoded_nonce);
     }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_nonce_tracker() {
+        let mut tracker = NonceTracker::new();
+        assert!(tracker.is_empty());
+        let nonce = Nonce::new();
+        tracker.record(&nonce).unwrap();
+        assert!(tracker.contains(&nonce));
+        assert_eq!(tracker.record(&nonce).unwrap_err().to_string(), "Nonce reuse detected");
+        tracker.record(&Nonce::new()).unwrap();
+        assert_eq!(tracker.len(), 2);
+    }
+
+    #[test]
+    fn test_nonce_sequence() {
+        let nonces: Vec<Nonce> = NonceSequence::new().take(300).collect();
+        assert!(nonces.windows(2).all(|w| w[0].data() < w[1].data()));
+        for (i, nonce) in nonces.iter().enumerate() {
+            assert_eq!(nonce.counter_value(), i as u64 + 1);
+            assert_eq!(nonce, &Nonce::from_counter(i as u64 + 1));
+        }
+        assert_eq!(nonces[257].hex(), "000000000000000000000102");
+        assert!(!nonces[0].is_zero());
+        assert_eq!(NonceSequence::starting_at([0; 4], 0).next_counter(), Some(1));
+
+        let mut sequence = NonceSequence::with_prefix([0xde, 0xad, 0xbe, 0xef]);
+        let nonce = sequence.next().unwrap();
+        assert_eq!(nonce.hex(), "deadbeef0000000000000000");
+        assert_eq!(sequence.next_counter(), Some(1));
+
+        let mut sequence = NonceSequence::starting_at([0; 4], u64::MAX - 1);
+        assert_eq!(sequence.next().unwrap().counter_value(), u64::MAX - 1);
+        assert_eq!(sequence.next().unwrap().hex(), "00000000ffffffffffffffff");
+        assert_eq!(sequence.next_counter(), None);
+        assert!(sequence.next().is_none());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_nonce_string() {
+        let nonce = Nonce::new();
+        assert_eq!(nonce.to_string(), nonce.hex());
+        assert_eq!(Nonce::from_str(&nonce.to_string()).unwrap(), nonce);
+        assert_eq!(Nonce::try_from("deadbeef0000000000000000").unwrap().hex(), "deadbeef0000000000000000");
+        assert!(Nonce::from_str("deadbeef").is_err());
+        assert!(Nonce::from_str("deadbeef00000000000000zz").is_err());
+    }
 }
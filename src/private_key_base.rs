@@ -1,5 +1,6 @@
+use alloc::{ vec, vec::Vec };
 use anyhow::{Error, Result};
-use bc_rand::{rng_random_data, RandomNumberGenerator, SecureRandomNumberGenerator};
+use bc_rand::{rng_random_data, RandomNumberGenerator};
 use bc_ur::prelude::*;
 #[cfg(feature = "ssh")]
 use ssh_key::{
@@ -40,8 +41,9 @@ impl Verifier for PrivateKeyBase {
 
 impl PrivateKeyBase {
     /// Generate a new random `PrivateKeyBase`.
+    #[cfg(feature = "std")]
     pub fn new() -> Self {
-        let mut rng = SecureRandomNumberGenerator;
+        let mut rng = bc_rand::SecureRandomNumberGenerator;
         Self::new_using(&mut rng)
     }
 
@@ -53,6 +55,7 @@ impl PrivateKeyBase {
     /// Restores a `PrivateKeyBase` from an optional reference to a vector of bytes.
     ///
     /// If the data is `None`, a new random `PrivateKeyBase` is generated.
+    #[cfg(feature = "std")]
     pub fn from_optional_data(data: Option<impl Into<Vec<u8>>>) -> Self {
         match data {
             Some(data) => Self::from_data(data),
@@ -153,14 +156,15 @@ impl PrivateKeyBase {
     }
 }
 
+#[cfg(feature = "std")]
 impl Default for PrivateKeyBase {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl std::fmt::Debug for PrivateKeyBase {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Debug for PrivateKeyBase {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "PrivateKeyBase")
     }
 }
@@ -210,6 +214,10 @@ impl TryFrom<CBOR> for PrivateKeyBase {
 }
 
 impl CBORTaggedDecodable for PrivateKeyBase {
+    fn from_tagged_cbor(cbor: CBOR) -> Result<Self> {
+        crate::component_error::decode_tagged(cbor, "PrivateKeyBase")
+    }
+
     fn from_untagged_cbor(untagged_cbor: CBOR) -> Result<Self> {
         let data = CBOR::try_into_byte_string(untagged_cbor)?;
         let instance = Self::from_data(data);
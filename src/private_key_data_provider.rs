@@ -1,3 +1,4 @@
+use alloc::vec::Vec;
 /// Types can implement to `PrivateKeyDataProvider` to indicate that they will provide
 /// unique data from which keys for signing and encryption can be derived.
 pub trait PrivateKeyDataProvider {
@@ -1,5 +1,6 @@
+use alloc::{ vec, vec::Vec };
 use bc_ur::prelude::*;
-use crate::{ tags, AgreementPublicKey, Signature, SigningPublicKey, Verifier };
+use crate::{ tags, AgreementPublicKey, ComponentError, Signature, SigningPublicKey, Verifier };
 use anyhow::{ bail, Error, Result };
 
 /// Holds information used to communicate cryptographically with a remote entity.
@@ -88,18 +89,22 @@ impl TryFrom<CBOR> for PublicKeyBase {
 }
 
 impl CBORTaggedDecodable for PublicKeyBase {
+    fn from_tagged_cbor(cbor: CBOR) -> Result<Self> {
+        crate::component_error::decode_tagged(cbor, "PublicKeyBase")
+    }
+
     fn from_untagged_cbor(untagged_cbor: CBOR) -> Result<Self> {
         match untagged_cbor.as_case() {
             CBORCase::Array(elements) => {
                 if elements.len() != 2 {
-                    bail!("PublicKeyBase must have two elements");
+                    bail!(ComponentError::InvalidFormat { component: "PublicKeyBase" });
                 }
 
                 let signing_public_key = SigningPublicKey::try_from(elements[0].clone())?;
                 let agreement_public_key = AgreementPublicKey::try_from(elements[1].clone())?;
                 Ok(Self::new(signing_public_key, agreement_public_key))
             }
-            _ => bail!("PublicKeyBase must be an array"),
+            _ => bail!(ComponentError::InvalidFormat { component: "PublicKeyBase" }),
         }
     }
 }
@@ -0,0 +1,110 @@
+use alloc::string::String;
+use crate::{
+    AgreementPublicKey,
+    Digest,
+    DigestProvider,
+    ECPublicKey,
+    EncryptedMessage,
+    PublicKeyBase,
+    SigningPublicKey,
+    ARID,
+};
+
+/// A type that can provide a short, stable reference to itself, such as for
+/// logging.
+///
+/// The reference is a `Digest` that identifies the component, and
+/// `reference_hex` gives its first four bytes as hex, matching the
+/// `short_description` of `ARID` and `Digest`. An `ARID` or `Digest` is its
+/// own reference; other components use the digest of their tagged CBOR.
+pub trait ReferenceProvider {
+    /// Returns the digest that identifies this component.
+    fn reference(&self) -> Digest;
+
+    /// Returns the first four bytes of the reference as hex.
+    fn reference_hex(&self) -> String {
+        self.reference_hex_prefix(4)
+    }
+
+    /// Returns the first `byte_count` bytes of the reference as hex, or the
+    /// whole reference if it is shorter.
+    fn reference_hex_prefix(&self, byte_count: usize) -> String {
+        let reference = self.reference();
+        let data = reference.data();
+        hex::encode(&data[..byte_count.min(data.len())])
+    }
+}
+
+impl ReferenceProvider for ARID {
+    fn reference(&self) -> Digest {
+        Digest::from_data(self.data().try_into().unwrap())
+    }
+}
+
+impl ReferenceProvider for Digest {
+    fn reference(&self) -> Digest {
+        self.clone()
+    }
+}
+
+impl ReferenceProvider for EncryptedMessage {
+    /// The digest of the message's tagged CBOR, which, unlike its
+    /// `DigestProvider` digest, doesn't require the message to carry a
+    /// digest of its plaintext.
+    fn reference(&self) -> Digest {
+        Digest::from_cbor_encodable(self)
+    }
+}
+
+macro_rules! impl_reference_provider_via_digest_provider {
+    ($($type:ty),* $(,)?) => {
+        $(
+            impl ReferenceProvider for $type {
+                fn reference(&self) -> Digest {
+                    self.digest().into_owned()
+                }
+            }
+        )*
+    };
+}
+
+impl_reference_provider_via_digest_provider!(
+    AgreementPublicKey,
+    ECPublicKey,
+    PublicKeyBase,
+    SigningPublicKey,
+);
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use core::str::FromStr;
+
+    use super::ReferenceProvider;
+    use crate::{ Digest, ECKey, ECPrivateKey, Nonce, SigningPrivateKey, SymmetricKey, ARID };
+
+    #[test]
+    fn test_reference() {
+        let arid = ARID::from_str("dec7e82893c32f7a4fcec633c02c0ec32a4361ca3ee3bc8758ae07742e940550").unwrap();
+        assert_eq!(arid.reference_hex(), arid.short_description());
+        assert_eq!(arid.reference_hex(), "dec7e828");
+        assert_eq!(arid.reference_hex_prefix(2), "dec7");
+        assert_eq!(arid.reference_hex_prefix(100), arid.hex());
+
+        let digest = Digest::from_image(b"hello world");
+        assert_eq!(digest.reference(), digest);
+        assert_eq!(digest.reference_hex(), digest.short_description());
+
+        let private_key = ECPrivateKey::new();
+        let signing_public_key = SigningPrivateKey::new_schnorr(private_key.clone()).public_key();
+        assert_eq!(signing_public_key.reference(), signing_public_key.fingerprint());
+        assert_ne!(private_key.public_key().reference(), signing_public_key.reference());
+
+        let message = SymmetricKey::new().encrypt(b"Wolf McNally", None::<Vec<u8>>, None::<Nonce>);
+        assert_eq!(message.reference(), message.clone().reference());
+
+        let components: Vec<&dyn ReferenceProvider> = vec![&arid, &digest, &signing_public_key, &message];
+        for component in components {
+            assert_eq!(component.reference_hex().len(), 8);
+        }
+    }
+}
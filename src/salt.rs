@@ -1,8 +1,9 @@
-use std::ops::RangeInclusive;
+use alloc::{ string::String, vec, vec::Vec };
+use core::{ ops::RangeInclusive, str::FromStr };
 use bc_ur::prelude::*;
-use bc_rand::{ rng_next_in_closed_range, rng_random_data, RandomNumberGenerator, SecureRandomNumberGenerator };
-use crate::tags;
-use anyhow::{ bail, Result, Error };
+use bc_rand::{ rng_next_in_closed_range, rng_random_data, RandomNumberGenerator };
+use crate::{ tags, ComponentError };
+use anyhow::{ anyhow, bail, Result, Error };
 
 /// Random salt used to decorrelate other information.
 #[derive(Clone, Eq, PartialEq)]
@@ -19,60 +20,78 @@ impl Salt {
         &self.0
     }
 
+    /// Return the number of bytes of salt.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Return `true` if the salt has no bytes, which is possible only for
+    /// salt made with `from_data`.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
     /// Create a specific number of bytes of salt.
     ///
-    /// If the number of bytes is less than 8, this will return `None`.
+    /// Returns an error if the number of bytes is less than 8.
+    #[cfg(feature = "std")]
     pub fn new_with_len(count: usize) -> Result<Self> {
-        let mut rng = SecureRandomNumberGenerator;
+        let mut rng = bc_rand::SecureRandomNumberGenerator;
         Self::new_with_len_using(count, &mut rng)
     }
 
     /// Create a specific number of bytes of salt.
     ///
-    /// If the number of bytes is less than 8, this will return `None`.
+    /// Returns an error if the number of bytes is less than 8.
     pub fn new_with_len_using(count: usize, rng: &mut impl RandomNumberGenerator) -> Result<Self> {
         if count < 8 {
-            bail!("Salt length is too short");
+            bail!(ComponentError::InvalidLength { component: "salt" });
         }
         Ok(Self::from_data(rng_random_data(rng, count)))
     }
 
     /// Create a number of bytes of salt chosen randomly from the given range.
     ///
-    /// If the minimum number of bytes is less than 8, this will return `None`.
+    /// Returns an error if the minimum number of bytes is less than 8.
+    #[cfg(feature = "std")]
     pub fn new_in_range(range: RangeInclusive<usize>) -> Result<Self> {
         if range.start() < &8 {
-            bail!("Salt length is too short");
+            bail!(ComponentError::InvalidLength { component: "salt" });
         }
-        let mut rng = SecureRandomNumberGenerator;
+        let mut rng = bc_rand::SecureRandomNumberGenerator;
         Self::new_in_range_using(&range, &mut rng)
     }
 
     /// Create a number of bytes of salt chosen randomly from the given range.
     ///
-    /// If the minimum number of bytes is less than 8, this will return `None`.
+    /// Returns an error if the minimum number of bytes is less than 8.
     pub fn new_in_range_using(
         range: &RangeInclusive<usize>,
         rng: &mut impl RandomNumberGenerator
     ) -> Result<Self> {
         if range.start() < &8 {
-            bail!("Salt length is too short");
+            bail!(ComponentError::InvalidLength { component: "salt" });
         }
         let count = rng_next_in_closed_range(rng, range);
         Self::new_with_len_using(count, rng)
     }
 
     /// Create a number of bytes of salt generally proportionate to the size of the object being salted.
+    ///
+    /// The length is chosen uniformly between a minimum of 5% of `size`, but
+    /// at least 8 bytes, and a maximum of 25% of `size`, but at least 8 bytes
+    /// more than the minimum. Both bounds are inclusive and rounded up.
+    #[cfg(feature = "std")]
     pub fn new_for_size(size: usize) -> Self {
-        let mut rng = SecureRandomNumberGenerator;
+        let mut rng = bc_rand::SecureRandomNumberGenerator;
         Self::new_for_size_using(size, &mut rng)
     }
 
     /// Create a number of bytes of salt generally proportionate to the size of the object being salted.
     pub fn new_for_size_using(size: usize, rng: &mut impl RandomNumberGenerator) -> Self {
         let count = size as f64;
-        let min_size = std::cmp::max(8, (count * 0.05).ceil() as usize);
-        let max_size = std::cmp::max(min_size + 8, (count * 0.25).ceil() as usize);
+        let min_size = core::cmp::max(8, (count * 0.05).ceil() as usize);
+        let max_size = core::cmp::max(min_size + 8, (count * 0.25).ceil() as usize);
         Self::new_in_range_using(&(min_size..=max_size), rng).unwrap()
     }
 
@@ -99,6 +118,19 @@ impl AsRef<[u8]> for Salt {
     }
 }
 
+/// Returns an error if there are fewer than 8 bytes, the minimum length of a
+/// generated salt.
+impl TryFrom<&[u8]> for Salt {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() < 8 {
+            bail!(ComponentError::InvalidLength { component: "salt" });
+        }
+        Ok(Self::from_data(data))
+    }
+}
+
 impl AsRef<Salt> for Salt {
     fn as_ref(&self) -> &Salt {
         self
@@ -132,6 +164,10 @@ impl TryFrom<CBOR> for Salt {
 }
 
 impl CBORTaggedDecodable for Salt {
+    fn from_tagged_cbor(cbor: CBOR) -> Result<Self> {
+        crate::component_error::decode_tagged(cbor, "salt")
+    }
+
     fn from_untagged_cbor(untagged_cbor: CBOR) -> Result<Self> {
         let data = CBOR::try_into_byte_string(untagged_cbor)?;
         let instance = Self::from_data(data);
@@ -139,12 +175,36 @@ impl CBORTaggedDecodable for Salt {
     }
 }
 
-impl std::fmt::Debug for Salt {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Debug for Salt {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "Salt({})", self.hex())
     }
 }
 
+impl core::fmt::Display for Salt {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.hex())
+    }
+}
+
+/// Parses a hexadecimal string, as produced by `Display`, of at least 8 bytes.
+impl FromStr for Salt {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let data = hex::decode(s.trim()).map_err(|e| anyhow!("Invalid Salt hex: {}", e))?;
+        Self::try_from(data.as_slice())
+    }
+}
+
+impl TryFrom<&str> for Salt {
+    type Error = Error;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
 // Convert from a reference to a byte vector to a Salt.
 impl From<&Salt> for Salt {
     fn from(salt: &Salt) -> Self {
@@ -165,3 +225,49 @@ impl From<&Salt> for Vec<u8> {
         salt.0.to_vec()
     }
 }
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use core::str::FromStr;
+
+    use super::Salt;
+    use crate::ComponentError;
+
+    #[test]
+    fn test_new_with_len() {
+        let salt = Salt::new_with_len(16).unwrap();
+        assert_eq!(salt.data().len(), 16);
+        assert_ne!(salt, Salt::new_with_len(16).unwrap());
+        assert!(Salt::new_with_len(8).is_ok());
+        let err = Salt::new_with_len(7).unwrap_err();
+        assert_eq!(err.downcast_ref(), Some(&ComponentError::InvalidLength { component: "salt" }));
+        assert!(Salt::new_in_range(7..=16).is_err());
+    }
+
+    #[test]
+    fn test_new_for_size_bounds() {
+        for (size, min, max) in [(0, 8, 16), (100, 8, 25), (1000, 50, 250), (1001, 51, 251)] {
+            let lengths: Vec<usize> = (0..200).map(|_| Salt::new_for_size(size).data().len()).collect();
+            assert!(lengths.iter().all(|len| (min..=max).contains(len)), "size {}", size);
+        }
+    }
+
+    #[test]
+    fn test_salt_string() {
+        let salt = Salt::new_with_len(16).unwrap();
+        assert_eq!(salt.to_string(), salt.hex());
+        assert_eq!(Salt::from_str(&salt.to_string()).unwrap(), salt);
+        assert_eq!(Salt::try_from("0001020304050607").unwrap().data().len(), 8);
+        assert!(Salt::from_str("00010203040506").is_err());
+        assert!(Salt::from_str("000102030405060z").is_err());
+    }
+
+    #[test]
+    fn test_len() {
+        assert_eq!(Salt::new_with_len(20).unwrap().len(), 20);
+        let salt = Salt::new_for_size(1000);
+        assert_eq!(salt.len(), salt.data().len());
+        assert!(!salt.is_empty());
+        assert!(Salt::from_data([]).is_empty());
+    }
+}
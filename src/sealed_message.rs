@@ -1,4 +1,6 @@
-use crate::{ EncryptedMessage, AgreementPublicKey, PublicKeyBase, PrivateKeyBase, Nonce, tags };
+use alloc::{ vec, vec::Vec };
+use crate::{ ComponentError, EncryptedMessage, AgreementPrivateKey, AgreementPublicKey, Nonce, PrivateKeyBase, PublicKeyBase, tags };
+use bc_rand::RandomNumberGenerator;
 use bc_ur::prelude::*;
 use anyhow::{ bail, Result, Error };
 
@@ -12,12 +14,14 @@ pub struct SealedMessage {
 
 impl SealedMessage {
     /// Creates a new `SealedMessage` from the given plaintext and recipient.
+    #[cfg(feature = "std")]
     pub fn new(plaintext: impl Into<Vec<u8>>, recipient: &PublicKeyBase) -> Self {
         Self::new_with_aad(plaintext, recipient, None::<Vec<u8>>)
     }
 
     /// Creates a new `SealedMessage` from the given plaintext, recipient, and
     /// additional authenticated data.
+    #[cfg(feature = "std")]
     pub fn new_with_aad(
         plaintext: impl Into<Vec<u8>>,
         recipient: &PublicKeyBase,
@@ -29,6 +33,11 @@ impl SealedMessage {
     /// Creates a new `SealedMessage` from the given plaintext, recipient, and
     /// additional authenticated data. Also accepts optional test key material
     /// and test nonce.
+    ///
+    /// The ephemeral key is on the same curve as the recipient's agreement
+    /// key. The test key material only determines an X25519 ephemeral key; an
+    /// X448 one is always random.
+    #[cfg(feature = "std")]
     pub fn new_opt(
         plaintext: impl Into<Vec<u8>>,
         recipient: &PublicKeyBase,
@@ -37,27 +46,88 @@ impl SealedMessage {
         test_nonce: Option<impl AsRef<Nonce>>
     ) -> Self {
         let ephemeral_sender = PrivateKeyBase::from_optional_data(test_key_material);
+        Self::seal(
+            plaintext,
+            recipient,
+            aad,
+            &ephemeral_sender,
+            test_nonce,
+            &mut bc_rand::SecureRandomNumberGenerator
+        )
+    }
+
+    /// Creates a new `SealedMessage` from the given plaintext, recipient, and
+    /// additional authenticated data, drawing the ephemeral key and the nonce
+    /// from the given random number generator.
+    pub fn new_using(
+        plaintext: impl Into<Vec<u8>>,
+        recipient: &PublicKeyBase,
+        aad: Option<impl Into<Vec<u8>>>,
+        rng: &mut impl RandomNumberGenerator
+    ) -> Self {
+        let ephemeral_sender = PrivateKeyBase::new_using(rng);
+        Self::seal(plaintext, recipient, aad, &ephemeral_sender, None::<Nonce>, rng)
+    }
+
+    fn seal(
+        plaintext: impl Into<Vec<u8>>,
+        recipient: &PublicKeyBase,
+        aad: Option<impl Into<Vec<u8>>>,
+        ephemeral_sender: &PrivateKeyBase,
+        nonce: Option<impl AsRef<Nonce>>,
+        rng: &mut impl RandomNumberGenerator
+    ) -> Self {
         let recipient_public_key = recipient.agreement_public_key();
-        let shared_key = ephemeral_sender
-            .agreement_private_key()
-            .shared_key_with(recipient_public_key);
-        let message = shared_key.encrypt(plaintext, aad, test_nonce);
-        let ephemeral_public_key = ephemeral_sender.agreement_private_key().public_key();
+        let ephemeral_private_key = if recipient_public_key.is_x448() {
+            AgreementPrivateKey::new_x448_using(rng)
+        } else {
+            ephemeral_sender.agreement_private_key()
+        };
+        let shared_key = ephemeral_private_key
+            .shared_key_with(recipient_public_key)
+            .expect("Ephemeral key is on the recipient's curve");
+        let message = shared_key.encrypt_using(plaintext, aad, nonce, rng);
+        let ephemeral_public_key = ephemeral_private_key.public_key();
         Self {
             message,
             ephemeral_public_key,
         }
     }
 
+    /// Returns the encrypted message.
+    pub fn encrypted_message(&self) -> &EncryptedMessage {
+        &self.message
+    }
+
+    /// Returns the ephemeral public key the message was sealed with.
+    pub fn ephemeral_public_key(&self) -> &AgreementPublicKey {
+        &self.ephemeral_public_key
+    }
+
     /// Decrypts the message using the recipient's private key.
+    ///
+    /// A `PrivateKeyBase` only derives an X25519 agreement key, so a message
+    /// sealed to an X448 recipient fails to decrypt here and is opened with
+    /// `AgreementPrivateKey::open` instead.
     pub fn decrypt(&self, private_key: &PrivateKeyBase) -> Result<Vec<u8>, bc_crypto::Error> {
         let shared_key = private_key
             .agreement_private_key()
-            .shared_key_with(&self.ephemeral_public_key);
+            .shared_key_with(&self.ephemeral_public_key)
+            .map_err(|_| bc_crypto::Error::DecryptFailed)?;
         shared_key.decrypt(&self.message)
     }
 }
 
+/// Combines the output of `AgreementPublicKey::seal` into a `SealedMessage`.
+impl From<(EncryptedMessage, AgreementPublicKey)> for SealedMessage {
+    fn from((message, ephemeral_public_key): (EncryptedMessage, AgreementPublicKey)) -> Self {
+        Self {
+            message,
+            ephemeral_public_key,
+        }
+    }
+}
+
 impl AsRef<SealedMessage> for SealedMessage {
     fn as_ref(&self) -> &SealedMessage {
         self
@@ -93,11 +163,15 @@ impl CBORTaggedEncodable for SealedMessage {
 }
 
 impl CBORTaggedDecodable for SealedMessage {
+    fn from_tagged_cbor(cbor: CBOR) -> Result<Self> {
+        crate::component_error::decode_tagged(cbor, "SealedMessage")
+    }
+
     fn from_untagged_cbor(cbor: CBOR) -> Result<Self> {
         match cbor.as_case() {
             CBORCase::Array(elements) => {
                 if elements.len() != 2 {
-                    bail!("SealedMessage must have two elements");
+                    bail!(ComponentError::InvalidFormat { component: "SealedMessage" });
                 }
                 let message = elements[0].clone().try_into()?;
                 let ephemeral_public_key = elements[1].clone().try_into()?;
@@ -106,14 +180,15 @@ impl CBORTaggedDecodable for SealedMessage {
                     ephemeral_public_key,
                 })
             }
-            _ => bail!("SealedMessage must be an array"),
+            _ => bail!(ComponentError::InvalidFormat { component: "SealedMessage" }),
         }
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests {
-    use crate::{ SealedMessage, PrivateKeyBase };
+    use crate::{ AgreementPrivateKey, SealedMessage, PrivateKeyBase, PublicKeyBase };
+    use dcbor::prelude::*;
     use hex_literal::hex;
 
     #[test]
@@ -142,4 +217,47 @@ mod tests {
         assert!(sealed_message.decrypt(&alice_private_key).is_err());
         assert!(sealed_message.decrypt(&carol_private_key).is_err());
     }
+
+    #[test]
+    fn test_x448_sealed_message() {
+        let plaintext = b"Some mysteries aren't meant to be solved.";
+
+        let bob_private_key = PrivateKeyBase::from_data(hex!("187a5973c64d359c836eba466a44db7b"));
+        let bob_agreement_key = AgreementPrivateKey::new_x448();
+        let bob_public_key = PublicKeyBase::new(
+            bob_private_key.schnorr_signing_private_key().public_key(),
+            bob_agreement_key.public_key()
+        );
+
+        let sealed_message = SealedMessage::new(plaintext, &bob_public_key);
+        let ephemeral_public_key = sealed_message.ephemeral_public_key();
+        assert!(ephemeral_public_key.is_x448());
+        assert_eq!(
+            bob_agreement_key.open(sealed_message.encrypted_message(), ephemeral_public_key).unwrap(),
+            plaintext
+        );
+        assert!(sealed_message.decrypt(&bob_private_key).is_err());
+
+        let carol_agreement_key = AgreementPrivateKey::new_x448();
+        assert!(carol_agreement_key.open(sealed_message.encrypted_message(), ephemeral_public_key).is_err());
+    }
+
+    #[test]
+    fn test_non_canonical_cbor_rejected() {
+        let bob_private_key = PrivateKeyBase::from_data(hex!("187a5973c64d359c836eba466a44db7b"));
+        let sealed_message = SealedMessage::new(b"plaintext", &bob_private_key.schnorr_public_key_base());
+        let elements = sealed_message.untagged_cbor().try_into_array().unwrap();
+        assert_eq!(SealedMessage::from_untagged_cbor(elements.clone().into()).unwrap(), sealed_message);
+
+        let mut trailing = elements.clone();
+        trailing.push(CBOR::null());
+        assert!(SealedMessage::from_untagged_cbor(trailing.into()).is_err());
+
+        // The inner message is held to its own canonical encoding.
+        let mut inner = sealed_message.encrypted_message().untagged_cbor().try_into_array().unwrap();
+        inner.push(CBOR::to_byte_string(Vec::<u8>::new()));
+        let mut non_canonical_inner = elements.clone();
+        non_canonical_inner[0] = CBOR::to_tagged_value(crate::tags::ENCRYPTED, inner);
+        assert!(SealedMessage::from_untagged_cbor(non_canonical_inner.into()).is_err());
+    }
 }
@@ -1,6 +1,7 @@
+use alloc::{ string::{ String, ToString }, vec, vec::Vec };
 use bc_rand::{rng_random_data, RandomNumberGenerator};
 use bc_ur::prelude::*;
-use crate::{ tags, PrivateKeyDataProvider };
+use crate::{ tags, ComponentError, PrivateKeyDataProvider };
 use anyhow::{ bail, Result, Error };
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -17,13 +18,22 @@ impl Seed {
     /// Create a new random seed.
     ///
     /// The length of the seed will be 16 bytes.
+    #[cfg(feature = "std")]
     pub fn new() -> Self {
         Self::new_with_len(Self::MIN_SEED_LENGTH).unwrap()
     }
 
+    /// Create a new random seed using the given random number generator.
+    ///
+    /// The length of the seed will be 16 bytes.
+    pub fn new_using(rng: &mut impl RandomNumberGenerator) -> Self {
+        Self::new_with_len_using(Self::MIN_SEED_LENGTH, rng).unwrap()
+    }
+
     /// Create a new random seed with a specified length.
     ///
     /// If the number of bytes is less than 16, this will return `None`.
+    #[cfg(feature = "std")]
     pub fn new_with_len(count: usize) -> Result<Self> {
         let mut rng = bc_rand::SecureRandomNumberGenerator;
         Self::new_with_len_using(count, &mut rng)
@@ -97,6 +107,7 @@ impl Seed {
     }
 }
 
+#[cfg(feature = "std")]
 impl Default for Seed {
     fn default() -> Self {
         Self::new()
@@ -159,11 +170,15 @@ impl TryFrom<CBOR> for Seed {
 }
 
 impl CBORTaggedDecodable for Seed {
+    fn from_tagged_cbor(cbor: CBOR) -> Result<Self> {
+        crate::component_error::decode_tagged(cbor, "seed")
+    }
+
     fn from_untagged_cbor(cbor: CBOR) -> Result<Self> {
         let map = cbor.try_into_map()?;
         let data = map.extract::<i32, CBOR>(1)?.try_into_byte_string()?.to_vec();
         if data.is_empty() {
-            bail!("Seed data is empty");
+            bail!(ComponentError::InvalidLength { component: "seed" });
         }
         let creation_date = map.get::<i32, dcbor::Date>(2);
         let name = map.get::<i32, String>(3);
@@ -0,0 +1,238 @@
+//! `serde` support for the public component types.
+//!
+//! Human-readable formats (JSON, YAML, ...) get the hex form of each value,
+//! binary formats get the compact bytes. Values without a fixed byte layout
+//! (`Signature`, `SigningPublicKey`, `PublicKeyBase`) use their tagged CBOR
+//! encoding as the bytes. `URI` is always a string and `UUID` uses its
+//! canonical hyphenated form when human-readable. A SHA-256 `Digest` is its
+//! bare bytes, and a `Digest` computed with another algorithm is its untagged
+//! CBOR encoding, which is never 32 bytes long.
+
+use core::fmt;
+
+use alloc::{ string::String, vec::Vec };
+
+use bc_ur::prelude::*;
+use serde::{
+    de::{self, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::{
+    AgreementPublicKey, ECKeyBase, ECPublicKey, ECUncompressedPublicKey, Ed25519PublicKey,
+    Digest, DigestAlgorithm, Nonce, PublicKeyBase, Salt, SchnorrPublicKey, Signature, SigningPublicKey, ARID,
+    UUID,
+};
+#[cfg(feature = "std")]
+use crate::URI;
+
+struct BytesVisitor;
+
+impl<'de> Visitor<'de> for BytesVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a byte string")
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(v.to_vec())
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(v)
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(byte) = seq.next_element()? {
+            bytes.push(byte);
+        }
+        Ok(bytes)
+    }
+}
+
+fn serialize_bytes<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    if serializer.is_human_readable() {
+        serializer.serialize_str(&hex::encode(bytes))
+    } else {
+        serializer.serialize_bytes(bytes)
+    }
+}
+
+fn deserialize_bytes<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    if deserializer.is_human_readable() {
+        let string = String::deserialize(deserializer)?;
+        hex::decode(string).map_err(de::Error::custom)
+    } else {
+        deserializer.deserialize_byte_buf(BytesVisitor)
+    }
+}
+
+macro_rules! impl_serde_via_bytes {
+    ($type:ty, $to_bytes:expr, $from_bytes:expr) => {
+        impl Serialize for $type {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                let to_bytes: fn(&$type) -> Vec<u8> = $to_bytes;
+                serialize_bytes(&to_bytes(self), serializer)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $type {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let from_bytes: fn(Vec<u8>) -> anyhow::Result<$type> = $from_bytes;
+                from_bytes(deserialize_bytes(deserializer)?).map_err(de::Error::custom)
+            }
+        }
+    };
+}
+
+impl_serde_via_bytes!(
+    Digest,
+    |v| match v.algorithm() {
+        DigestAlgorithm::Sha256 => v.data().to_vec(),
+        _ => v.untagged_cbor().to_cbor_data(),
+    },
+    |b| if b.len() == Digest::DIGEST_SIZE {
+        Digest::from_data_ref(b)
+    } else {
+        Digest::from_untagged_cbor(CBOR::try_from_data(b)?)
+    }
+);
+impl_serde_via_bytes!(ARID, |v| v.data().to_vec(), ARID::from_data_ref);
+impl_serde_via_bytes!(Nonce, |v| v.data().to_vec(), Nonce::from_data_ref);
+impl_serde_via_bytes!(Salt, |v| v.data().clone(), |b| Ok(Salt::from_data(b)));
+impl_serde_via_bytes!(AgreementPublicKey, |v| v.as_bytes().to_vec(), AgreementPublicKey::from_data_ref);
+impl_serde_via_bytes!(ECPublicKey, |v| v.data().to_vec(), ECPublicKey::from_data_ref);
+impl_serde_via_bytes!(
+    ECUncompressedPublicKey,
+    |v| v.data().to_vec(),
+    ECUncompressedPublicKey::from_data_ref
+);
+impl_serde_via_bytes!(SchnorrPublicKey, |v| v.data().to_vec(), SchnorrPublicKey::from_data_ref);
+impl_serde_via_bytes!(Ed25519PublicKey, |v| v.data().to_vec(), Ed25519PublicKey::from_data_ref);
+impl_serde_via_bytes!(Signature, |v| v.to_cbor_data(), Signature::from_tagged_cbor_data);
+impl_serde_via_bytes!(
+    SigningPublicKey,
+    |v| v.to_cbor_data(),
+    SigningPublicKey::from_tagged_cbor_data
+);
+impl_serde_via_bytes!(PublicKeyBase, |v| v.to_cbor_data(), PublicKeyBase::from_tagged_cbor_data);
+
+impl Serialize for UUID {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            serializer.serialize_bytes(self.data())
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for UUID {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let string = String::deserialize(deserializer)?;
+            string.parse().map_err(de::Error::custom)
+        } else {
+            let bytes = deserializer.deserialize_byte_buf(BytesVisitor)?;
+            UUID::from_data_ref(bytes).ok_or_else(|| de::Error::custom("invalid UUID size"))
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Serialize for URI {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'de> Deserialize<'de> for URI {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let string = String::deserialize(deserializer)?;
+        URI::new(string).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::fmt::Debug;
+
+    use serde::{de::DeserializeOwned, Serialize};
+
+    use crate::{
+        AgreementPrivateKey, Digest, ECKey, ECPrivateKey, ECPublicKeyBase, Ed25519PrivateKey, Nonce, PrivateKeyBase,
+        Salt, Signer, SigningPrivateKey, ARID, URI, UUID,
+    };
+
+    fn round_trip<T>(value: &T) -> String
+    where
+        T: Serialize + DeserializeOwned + PartialEq + Debug,
+    {
+        let json = serde_json::to_string(value).unwrap();
+        assert_eq!(&serde_json::from_str::<T>(&json).unwrap(), value);
+
+        let mut cbor = Vec::new();
+        ciborium::into_writer(value, &mut cbor).unwrap();
+        assert_eq!(&ciborium::from_reader::<T, _>(cbor.as_slice()).unwrap(), value);
+        json
+    }
+
+    #[test]
+    fn test_human_readable_forms() {
+        let digest = Digest::from_image(b"Hello");
+        assert_eq!(round_trip(&digest), format!("\"{}\"", digest.hex()));
+
+        let uuid = UUID::new();
+        assert_eq!(round_trip(&uuid), format!("\"{}\"", uuid));
+
+        let uri = URI::new("https://example.com/a?b=c").unwrap();
+        assert_eq!(round_trip(&uri), "\"https://example.com/a?b=c\"");
+    }
+
+    #[test]
+    fn test_binary_form_is_compact() {
+        let digest = Digest::from_image(b"Hello");
+        let mut cbor = Vec::new();
+        ciborium::into_writer(&digest, &mut cbor).unwrap();
+        // A 32-byte CBOR byte string: 0x58 0x20 followed by the digest.
+        assert_eq!(cbor.len(), 34);
+        assert_eq!(&cbor[2..], digest.data());
+    }
+
+    #[test]
+    fn test_round_trips() {
+        round_trip(&ARID::new());
+        round_trip(&Digest::sha512(b"Hello"));
+        round_trip(&Digest::blake3(b"Hello"));
+        round_trip(&Nonce::new());
+        round_trip(&Salt::new_with_len(16).unwrap());
+        round_trip(&AgreementPrivateKey::new().public_key());
+
+        let ec_key = ECPrivateKey::new();
+        round_trip(&ec_key.public_key());
+        round_trip(&ec_key.public_key().uncompressed_public_key());
+        round_trip(&ec_key.schnorr_public_key());
+        round_trip(&Ed25519PrivateKey::new().public_key());
+
+        for signing_key in [
+            SigningPrivateKey::new_schnorr(ec_key.clone()),
+            SigningPrivateKey::new_ecdsa(ec_key.clone()),
+            SigningPrivateKey::new_ed25519(Ed25519PrivateKey::new()),
+        ] {
+            round_trip(&signing_key.public_key());
+            round_trip(&signing_key.sign(b"message").unwrap());
+        }
+        round_trip(&PrivateKeyBase::new().schnorr_public_key_base());
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        assert!(serde_json::from_str::<Digest>("\"00\"").is_err());
+        assert!(serde_json::from_str::<Digest>("\"not hex\"").is_err());
+        assert!(serde_json::from_str::<URI>("\"not a uri\"").is_err());
+        assert!(serde_json::from_str::<UUID>("\"1234\"").is_err());
+    }
+}
@@ -0,0 +1,119 @@
+use alloc::{ string::String, vec::Vec };
+use bc_ur::prelude::*;
+use anyhow::{ bail, Error, Result };
+
+use crate::{ ComponentError, Digest, DigestAlgorithm };
+
+/// The first few bytes of a `Digest`, for use as a compact content address.
+///
+/// Created with `Digest::truncated`. A short digest is never equal to a
+/// full `Digest`; use `is_prefix_of` to match one against a digest.
+///
+/// Truncation weakens collision resistance: among `n`-byte short digests, a
+/// collision is expected after about `2^(4n)` items, so 4 bytes collide
+/// after some 65,000 items and 16 bytes after some 2^64. Choose at least 16
+/// bytes where an attacker might try to forge a collision.
+///
+/// Encoded in CBOR as an untagged byte string, whose length gives the
+/// length of the truncation.
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ShortDigest(Vec<u8>);
+
+impl ShortDigest {
+    /// Restores a short digest from its bytes.
+    ///
+    /// Returns an error unless there are between 1 and 64 bytes, the sizes a
+    /// truncated digest can have.
+    pub fn from_data_ref(data: impl AsRef<[u8]>) -> Result<Self> {
+        let data = data.as_ref();
+        if data.is_empty() || data.len() > DigestAlgorithm::Sha512.digest_size() {
+            bail!(ComponentError::InvalidLength { component: "short digest" });
+        }
+        Ok(Self(data.to_vec()))
+    }
+
+    /// Returns the bytes of the short digest.
+    pub fn data(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Returns `true` if this is a truncation of `digest`.
+    ///
+    /// Only the bytes are compared, so the algorithm of `digest` is not
+    /// checked.
+    pub fn is_prefix_of(&self, digest: &Digest) -> bool {
+        digest.as_bytes().starts_with(&self.0)
+    }
+
+    /// The bytes as a hexadecimal string.
+    pub fn hex(&self) -> String {
+        hex::encode(&self.0)
+    }
+}
+
+impl AsRef<[u8]> for ShortDigest {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl core::fmt::Debug for ShortDigest {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl core::fmt::Display for ShortDigest {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "ShortDigest({})", self.hex())
+    }
+}
+
+impl From<ShortDigest> for CBOR {
+    fn from(value: ShortDigest) -> Self {
+        CBOR::to_byte_string(value.0)
+    }
+}
+
+impl TryFrom<CBOR> for ShortDigest {
+    type Error = Error;
+
+    fn try_from(cbor: CBOR) -> Result<Self, Self::Error> {
+        Self::from_data_ref(CBOR::try_into_byte_string(cbor)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bc_ur::prelude::*;
+
+    use super::ShortDigest;
+    use crate::Digest;
+
+    #[test]
+    fn test_truncated() {
+        let digest = Digest::from_image(b"hello world");
+        let short = digest.truncated(16).unwrap();
+        assert_eq!(short.data(), &digest.data()[..16]);
+        assert_eq!(short.hex(), "b94d27b9934d3e08a52e52d7da7dabfa");
+        assert!(short.is_prefix_of(&digest));
+        assert!(!short.is_prefix_of(&Digest::from_image(b"hello")));
+        assert_ne!(short, digest.truncated(8).unwrap());
+        assert!(digest.truncated(8).unwrap().is_prefix_of(&digest));
+
+        assert!(digest.truncated(0).is_err());
+        assert!(digest.truncated(33).is_err());
+        assert_eq!(Digest::sha512(b"hello world").truncated(64).unwrap().data().len(), 64);
+    }
+
+    #[test]
+    fn test_cbor() {
+        let short = Digest::from_image(b"hello world").truncated(16).unwrap();
+        let cbor_data = CBOR::from(short.clone()).to_cbor_data();
+        assert_eq!(cbor_data[0], 0x50); // byte string of length 16
+        let decoded = ShortDigest::try_from(CBOR::try_from_data(cbor_data).unwrap()).unwrap();
+        assert_eq!(decoded, short);
+        assert!(ShortDigest::try_from(CBOR::to_byte_string([])).is_err());
+        assert!(ShortDigest::try_from(CBOR::to_byte_string([0u8; 65])).is_err());
+    }
+}
@@ -1,11 +1,28 @@
-use crate::tags;
-use anyhow::{bail, Error, Result};
+use alloc::{ string::String, vec, vec::Vec };
+use crate::{tags, ComponentError, ECPublicKey, Ed25519PrivateKey};
+use anyhow::{anyhow, bail, Error, Result};
 use bc_crypto::{ECDSA_SIGNATURE_SIZE, SCHNORR_SIGNATURE_SIZE};
+use base64::{engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD}, Engine as _};
 use bc_ur::prelude::*;
+use subtle::ConstantTimeEq;
 #[cfg(feature = "ssh")]
 use ssh_key::{LineEnding, SshSig};
 
-/// A cryptographic signature. Supports ECDSA and Schnorr.
+/// The algorithm that produced a `Signature`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SignatureAlgorithm {
+    /// BIP-340 Schnorr over secp256k1.
+    Schnorr,
+    /// ECDSA over secp256k1, recoverable or not.
+    ECDSA,
+    /// Ed25519 (RFC 8032).
+    Ed25519,
+    /// An SSH signature (SSHSIG), made with any key type SSH supports.
+    #[cfg(feature = "ssh")]
+    SSH,
+}
+
+/// A cryptographic signature. Supports ECDSA, Schnorr, and Ed25519.
 #[derive(Clone, PartialEq, Eq)]
 pub enum Signature {
     Schnorr {
@@ -13,6 +30,11 @@ pub enum Signature {
         tag: Vec<u8>,
     },
     ECDSA([u8; ECDSA_SIGNATURE_SIZE]),
+    ECDSARecoverable {
+        sig: [u8; ECDSA_SIGNATURE_SIZE],
+        recovery_id: u8,
+    },
+    Ed25519([u8; Ed25519PrivateKey::SIGNATURE_SIZE]),
     #[cfg(feature = "ssh")]
     SSH(SshSig),
 }
@@ -30,7 +52,7 @@ impl Signature {
     pub fn schnorr_from_data_ref(data: impl AsRef<[u8]>, tag: impl Into<Vec<u8>>) -> Result<Self> {
         let data = data.as_ref();
         if data.len() != SCHNORR_SIGNATURE_SIZE {
-            bail!("Invalid Schnorr signature size");
+            bail!(ComponentError::InvalidLength { component: "Schnorr signature" });
         }
         let mut arr = [0u8; SCHNORR_SIGNATURE_SIZE];
         arr.copy_from_slice(data);
@@ -46,19 +68,85 @@ impl Signature {
     pub fn ecdsa_from_data_ref(data: impl AsRef<[u8]>) -> Result<Self> {
         let data = data.as_ref();
         if data.len() != ECDSA_SIGNATURE_SIZE {
-            bail!("Invalid ECDSA signature size");
+            bail!(ComponentError::InvalidLength { component: "ECDSA signature" });
         }
         let mut arr = [0u8; ECDSA_SIGNATURE_SIZE];
         arr.copy_from_slice(data);
         Ok(Self::ecdsa_from_data(arr))
     }
 
+    /// Restores an ECDSA signature from its DER encoding, an ASN.1 `SEQUENCE`
+    /// of the integers `r` and `s`, as produced by OpenSSL and X.509 tooling.
+    ///
+    /// A high `s` value is normalized to its low form, which is the only form
+    /// secp256k1 accepts and which is equally valid for the same message.
+    pub fn ecdsa_from_der(der: impl AsRef<[u8]>) -> Result<Self> {
+        let mut sig = secp256k1::ecdsa::Signature::from_der(der.as_ref())
+            .map_err(|_| anyhow!("Invalid DER ECDSA signature"))?;
+        sig.normalize_s();
+        Ok(Self::ecdsa_from_data(sig.serialize_compact()))
+    }
+
+    /// Restores a recoverable ECDSA signature from an array of bytes and a
+    /// recovery id.
+    pub fn ecdsa_recoverable_from_data(data: [u8; ECDSA_SIGNATURE_SIZE], recovery_id: u8) -> Self {
+        Self::ECDSARecoverable {
+            sig: data,
+            recovery_id,
+        }
+    }
+
+    /// Restores a recoverable ECDSA signature from a vector of bytes and a
+    /// recovery id.
+    pub fn ecdsa_recoverable_from_data_ref(
+        data: impl AsRef<[u8]>,
+        recovery_id: u8,
+    ) -> Result<Self> {
+        if recovery_id > 3 {
+            bail!("Invalid ECDSA recovery id");
+        }
+        let data = data.as_ref();
+        if data.len() != ECDSA_SIGNATURE_SIZE {
+            bail!(ComponentError::InvalidLength { component: "ECDSA signature" });
+        }
+        let mut arr = [0u8; ECDSA_SIGNATURE_SIZE];
+        arr.copy_from_slice(data);
+        Ok(Self::ecdsa_recoverable_from_data(arr, recovery_id))
+    }
+
+    /// Restores an Ed25519 signature from an array of bytes.
+    pub fn ed25519_from_data(data: [u8; Ed25519PrivateKey::SIGNATURE_SIZE]) -> Self {
+        Self::Ed25519(data)
+    }
+
+    /// Restores an Ed25519 signature from a vector of bytes.
+    pub fn ed25519_from_data_ref(data: impl AsRef<[u8]>) -> Result<Self> {
+        let data = data.as_ref();
+        if data.len() != Ed25519PrivateKey::SIGNATURE_SIZE {
+            bail!(ComponentError::InvalidLength { component: "Ed25519 signature" });
+        }
+        let mut arr = [0u8; Ed25519PrivateKey::SIGNATURE_SIZE];
+        arr.copy_from_slice(data);
+        Ok(Self::ed25519_from_data(arr))
+    }
+
     /// Restores an SSH signature from a `SshSig`.
     #[cfg(feature = "ssh")]
     pub fn from_ssh(sig: SshSig) -> Self {
         Self::SSH(sig)
     }
 
+    /// Returns the algorithm that produced this signature.
+    pub fn algorithm(&self) -> SignatureAlgorithm {
+        match self {
+            Self::Schnorr { .. } => SignatureAlgorithm::Schnorr,
+            Self::ECDSA(_) | Self::ECDSARecoverable { .. } => SignatureAlgorithm::ECDSA,
+            Self::Ed25519(_) => SignatureAlgorithm::Ed25519,
+            #[cfg(feature = "ssh")]
+            Self::SSH(_) => SignatureAlgorithm::SSH,
+        }
+    }
+
     pub fn to_schnorr(&self) -> Option<&[u8; SCHNORR_SIGNATURE_SIZE]> {
         match self {
             Self::Schnorr { sig, .. } => Some(sig),
@@ -66,9 +154,64 @@ impl Signature {
         }
     }
 
+    /// Returns the compact ECDSA signature, whether or not it is recoverable.
     pub fn to_ecdsa(&self) -> Option<&[u8; ECDSA_SIGNATURE_SIZE]> {
         match self {
             Self::ECDSA(sig) => Some(sig),
+            Self::ECDSARecoverable { sig, .. } => Some(sig),
+            _ => None,
+        }
+    }
+
+    /// Returns the DER encoding of the ECDSA signature, whether or not it is
+    /// recoverable, or `None` if this is not an ECDSA signature.
+    pub fn to_der(&self) -> Option<Vec<u8>> {
+        let sig = secp256k1::ecdsa::Signature::from_compact(self.to_ecdsa()?).ok()?;
+        Some(sig.serialize_der().to_vec())
+    }
+
+    /// Returns the recovery id, if this is a recoverable ECDSA signature.
+    pub fn ecdsa_recovery_id(&self) -> Option<u8> {
+        match self {
+            Self::ECDSARecoverable { recovery_id, .. } => Some(*recovery_id),
+            _ => None,
+        }
+    }
+
+    /// Recovers the public key that produced this signature over the given
+    /// message.
+    ///
+    /// The message is hashed with double SHA-256, as it is when signing with
+    /// `SigningPrivateKey::ecdsa_sign_recoverable`.
+    ///
+    /// Returns `None` unless this is a recoverable ECDSA signature.
+    pub fn recover_public_key(&self, message: impl AsRef<[u8]>) -> Option<ECPublicKey> {
+        match self {
+            Self::ECDSARecoverable { sig, recovery_id } => {
+                ECPublicKey::ecdsa_recover(sig, *recovery_id, message)
+            }
+            _ => None,
+        }
+    }
+
+    /// Recovers the public key that produced this signature over the given
+    /// 32-byte message digest, which is used as is. This recovers signers
+    /// from signatures over digests this crate doesn't compute, such as
+    /// Ethereum's Keccak-256 message hash.
+    ///
+    /// Returns `None` unless this is a recoverable ECDSA signature.
+    pub fn recover_public_key_prehashed(&self, digest: &[u8; 32]) -> Option<ECPublicKey> {
+        match self {
+            Self::ECDSARecoverable { sig, recovery_id } => {
+                ECPublicKey::ecdsa_recover_prehashed(sig, *recovery_id, digest)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn to_ed25519(&self) -> Option<&[u8; Ed25519PrivateKey::SIGNATURE_SIZE]> {
+        match self {
+            Self::Ed25519(sig) => Some(sig),
             _ => None,
         }
     }
@@ -80,10 +223,64 @@ impl Signature {
             _ => None,
         }
     }
+
+    /// Encodes the tagged CBOR of this signature as standard Base64, with
+    /// padding.
+    pub fn to_base64(&self) -> String {
+        STANDARD.encode(self.tagged_cbor_data())
+    }
+
+    /// Decodes a signature from standard Base64 of its tagged CBOR.
+    ///
+    /// Returns an error if the text is not valid Base64 or doesn't decode
+    /// as a signature, including when its CBOR tag is for some other type.
+    pub fn from_base64(base64: &str) -> Result<Self> {
+        Self::from_tagged_cbor_data(STANDARD.decode(base64)?)
+    }
+
+    /// Encodes the tagged CBOR of this signature as URL-safe Base64, without
+    /// padding, for use in URLs and file names.
+    pub fn to_base64url(&self) -> String {
+        URL_SAFE_NO_PAD.encode(self.tagged_cbor_data())
+    }
+
+    /// Decodes a signature from unpadded URL-safe Base64 of its tagged CBOR.
+    ///
+    /// Returns an error if the text is not valid unpadded URL-safe Base64 or
+    /// doesn't decode as a signature.
+    pub fn from_base64url(base64url: &str) -> Result<Self> {
+        Self::from_tagged_cbor_data(URL_SAFE_NO_PAD.decode(base64url)?)
+    }
+
+    /// Compare two signatures in constant time.
+    ///
+    /// Unlike `==`, this does not short-circuit on the first differing byte.
+    /// Signatures of different kinds are never equal, and SSH signatures are
+    /// compared by their PEM encoding.
+    pub fn ct_eq(&self, other: &Signature) -> bool {
+        match (self, other) {
+            (Self::Schnorr { sig: a, tag: a_tag }, Self::Schnorr { sig: b, tag: b_tag }) => {
+                (a.ct_eq(b) & a_tag.as_slice().ct_eq(b_tag.as_slice())).into()
+            }
+            (Self::ECDSA(a), Self::ECDSA(b)) => a.ct_eq(b).into(),
+            (
+                Self::ECDSARecoverable { sig: a, recovery_id: a_id },
+                Self::ECDSARecoverable { sig: b, recovery_id: b_id },
+            ) => (a.ct_eq(b) & a_id.ct_eq(b_id)).into(),
+            (Self::Ed25519(a), Self::Ed25519(b)) => a.ct_eq(b).into(),
+            #[cfg(feature = "ssh")]
+            (Self::SSH(a), Self::SSH(b)) => {
+                let a = a.to_pem(LineEnding::LF).unwrap();
+                let b = b.to_pem(LineEnding::LF).unwrap();
+                a.as_bytes().ct_eq(b.as_bytes()).into()
+            }
+            _ => false,
+        }
+    }
 }
 
-impl std::fmt::Debug for Signature {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Debug for Signature {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             Signature::Schnorr { sig: data, tag } => f
                 .debug_struct("Schnorr")
@@ -94,12 +291,43 @@ impl std::fmt::Debug for Signature {
                 .debug_struct("ECDSA")
                 .field("data", &hex::encode(data))
                 .finish(),
+            Signature::ECDSARecoverable { sig: data, recovery_id } => f
+                .debug_struct("ECDSARecoverable")
+                .field("data", &hex::encode(data))
+                .field("recovery_id", recovery_id)
+                .finish(),
+            Signature::Ed25519(data) => f
+                .debug_struct("Ed25519")
+                .field("data", &hex::encode(data))
+                .finish(),
             #[cfg(feature = "ssh")]
             Signature::SSH(sig) => f.debug_struct("SSH").field("sig", sig).finish(),
         }
     }
 }
 
+/// SSH signatures are hashed by their PEM encoding, which distinguishes them
+/// exactly as `PartialEq` does.
+impl core::hash::Hash for Signature {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        core::mem::discriminant(self).hash(state);
+        match self {
+            Signature::Schnorr { sig, tag } => {
+                sig.hash(state);
+                tag.hash(state);
+            }
+            Signature::ECDSA(sig) => sig.hash(state),
+            Signature::ECDSARecoverable { sig, recovery_id } => {
+                sig.hash(state);
+                recovery_id.hash(state);
+            }
+            Signature::Ed25519(sig) => sig.hash(state),
+            #[cfg(feature = "ssh")]
+            Signature::SSH(sig) => sig.to_pem(LineEnding::LF).unwrap().hash(state),
+        }
+    }
+}
+
 impl AsRef<Signature> for Signature {
     fn as_ref(&self) -> &Signature {
         self
@@ -129,6 +357,13 @@ impl CBORTaggedEncodable for Signature {
                 }
             }
             Signature::ECDSA(data) => vec![(1).into(), CBOR::to_byte_string(data)].into(),
+            Signature::ECDSARecoverable { sig: data, recovery_id } => vec![
+                (1).into(),
+                CBOR::to_byte_string(data),
+                (*recovery_id).into(),
+            ]
+            .into(),
+            Signature::Ed25519(data) => vec![(2).into(), CBOR::to_byte_string(data)].into(),
             #[cfg(feature = "ssh")]
             Signature::SSH(sig) => {
                 let pem = sig.to_pem(LineEnding::LF).unwrap();
@@ -147,6 +382,10 @@ impl TryFrom<CBOR> for Signature {
 }
 
 impl CBORTaggedDecodable for Signature {
+    fn from_tagged_cbor(cbor: CBOR) -> Result<Self> {
+        crate::component_error::decode_tagged(cbor, "signature")
+    }
+
     fn from_untagged_cbor(cbor: CBOR) -> Result<Self> {
         match cbor.into_case() {
             CBORCase::ByteString(bytes) => Self::schnorr_from_data_ref(bytes, Vec::new()),
@@ -157,8 +396,11 @@ impl CBORTaggedDecodable for Signature {
                     let ele_1 = drain.next().unwrap().into_case();
                     match ele_0 {
                         CBORCase::ByteString(data) => {
+                            // An empty tag is encoded as the bare signature.
                             if let CBORCase::ByteString(tag) = ele_1 {
-                                return Self::schnorr_from_data_ref(data, tag);
+                                if !tag.is_empty() {
+                                    return Self::schnorr_from_data_ref(data, tag);
+                                }
                             }
                         }
                         CBORCase::Unsigned(1) => {
@@ -166,21 +408,42 @@ impl CBORTaggedDecodable for Signature {
                                 return Self::ecdsa_from_data_ref(data);
                             }
                         }
+                        CBORCase::Unsigned(2) => {
+                            if let CBORCase::ByteString(data) = ele_1 {
+                                return Self::ed25519_from_data_ref(data);
+                            }
+                        }
                         _ => (),
                     }
+                } else if elements.len() == 3 {
+                    let mut drain = elements.drain(0..);
+                    let ele_0 = drain.next().unwrap().into_case();
+                    let ele_1 = drain.next().unwrap().into_case();
+                    let ele_2 = drain.next().unwrap().into_case();
+                    if let (
+                        CBORCase::Unsigned(1),
+                        CBORCase::ByteString(data),
+                        CBORCase::Unsigned(recovery_id),
+                    ) = (ele_0, ele_1, ele_2)
+                    {
+                        let recovery_id = u8::try_from(recovery_id)?;
+                        return Self::ecdsa_recoverable_from_data_ref(data, recovery_id);
+                    }
                 }
-                bail!("Invalid signature format");
+                bail!(ComponentError::InvalidFormat { component: "signature" });
             }
             #[cfg(feature = "ssh")]
             CBORCase::Tagged(tag, item) => {
                 if tag == tags::SSH_TEXT_SIGNATURE {
                     let string = item.try_into_text()?;
-                    let pem = SshSig::from_pem(string)?;
+                    let Ok(pem) = SshSig::from_pem(string) else {
+                        bail!(ComponentError::MalformedBytes { component: "SSH signature" });
+                    };
                     return Ok(Self::SSH(pem));
                 }
-                bail!("Invalid signature format");
+                bail!(ComponentError::WrongTag { component: "signature", tag: tag.value() });
             }
-            _ => bail!("Invalid signature format"),
+            _ => bail!(ComponentError::InvalidFormat { component: "signature" }),
         }
     }
 }
@@ -189,11 +452,15 @@ impl CBORTaggedDecodable for Signature {
 mod tests {
     use std::{cell::RefCell, rc::Rc};
 
-    use crate::{ECPrivateKey, Signature, Signer, SigningOptions, SigningPrivateKey, Verifier};
+    use crate::{
+        ECKeyBase, ECPrivateKey, Ed25519PrivateKey, Signature, Signer, SigningOptions, SigningPrivateKey,
+        SigningPublicKey, Verifier,
+    };
     use bc_rand::make_fake_random_number_generator;
     use dcbor::prelude::*;
     use hex_literal::hex;
     use indoc::indoc;
+    use sha3::{ Digest as _, Keccak256 };
 
     const ECDSA_SIGNING_PRIVATE_KEY: SigningPrivateKey =
         SigningPrivateKey::new_ecdsa(ECPrivateKey::from_data(hex!(
@@ -205,6 +472,7 @@ mod tests {
         )));
     const MESSAGE: &dyn AsRef<[u8]> = b"Wolf McNally";
 
+    #[cfg(feature = "std")]
     #[test]
     fn test_schnorr_signing() {
         let public_key = SCHNORR_SIGNING_PRIVATE_KEY.public_key();
@@ -218,6 +486,21 @@ mod tests {
         assert!(public_key.verify(&another_signature, MESSAGE));
     }
 
+    #[test]
+    fn test_schnorr_deterministic_signing() {
+        let public_key = SCHNORR_SIGNING_PRIVATE_KEY.public_key();
+        let options = || Some(SigningOptions::SchnorrDeterministic { tag: b"tagged".to_vec() });
+        let signature = SCHNORR_SIGNING_PRIVATE_KEY.sign_with_options(MESSAGE, options()).unwrap();
+        let another_signature = SCHNORR_SIGNING_PRIVATE_KEY.sign_with_options(MESSAGE, options()).unwrap();
+        assert_eq!(signature, another_signature);
+        assert!(public_key.verify(&signature, MESSAGE));
+        assert!(!public_key.verify(&signature, b"Other message"));
+
+        let other = SCHNORR_SIGNING_PRIVATE_KEY.sign_with_options(b"Other message", options()).unwrap();
+        assert_ne!(signature, other);
+        assert!(public_key.verify(&other, b"Other message"));
+    }
+
     #[test]
     fn test_schnorr_cbor() {
         let rng = Rc::new(RefCell::new(make_fake_random_number_generator()));
@@ -256,6 +539,20 @@ mod tests {
         assert!(public_key.verify(&another_signature, MESSAGE));
     }
 
+    #[test]
+    fn test_non_canonical_cbor_rejected() {
+        let sig = CBOR::to_byte_string([0u8; 64]);
+        assert!(Signature::from_untagged_cbor(sig.clone()).is_ok());
+
+        // A Schnorr signature with an empty tag is the bare signature.
+        let empty_tag: CBOR = vec![sig.clone(), CBOR::to_byte_string(Vec::<u8>::new())].into();
+        assert!(Signature::from_untagged_cbor(empty_tag).is_err());
+        let trailing: CBOR = vec![CBOR::from(2), sig.clone(), CBOR::to_byte_string([0u8; 1])].into();
+        assert!(Signature::from_untagged_cbor(trailing).is_err());
+        let too_long: CBOR = vec![CBOR::from(1), sig.clone(), CBOR::from(0), CBOR::from(0)].into();
+        assert!(Signature::from_untagged_cbor(too_long).is_err());
+    }
+
     #[test]
     fn test_ecdsa_cbor() {
         let signature = ECDSA_SIGNING_PRIVATE_KEY.sign(MESSAGE).unwrap();
@@ -279,4 +576,309 @@ mod tests {
         let received_signature = Signature::from_tagged_cbor_data(&tagged_cbor_data).unwrap();
         assert_eq!(signature, received_signature);
     }
+
+    #[test]
+    fn test_ed25519_test_vector() {
+        // RFC 8032 Section 7.1, TEST 2
+        let private_key = Ed25519PrivateKey::from_data(hex!(
+            "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb"
+        ));
+        let signing_key = SigningPrivateKey::new_ed25519(private_key);
+        let public_key = signing_key.public_key();
+        assert_eq!(
+            public_key.to_ed25519().unwrap().data(),
+            &hex!("3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c")
+        );
+        let message = hex!("72");
+        let signature = signing_key.sign(&message).unwrap();
+        assert_eq!(
+            signature.to_ed25519().unwrap(),
+            &hex!("92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00")
+        );
+        assert!(public_key.verify(&signature, &message));
+        assert!(!public_key.verify(&signature, b"Wolf McNally"));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_ed25519_signing() {
+        let signing_key = SigningPrivateKey::new_ed25519(Ed25519PrivateKey::new());
+        let public_key = signing_key.public_key();
+        let signature = signing_key.sign(MESSAGE).unwrap();
+        assert!(public_key.verify(&signature, MESSAGE));
+        assert!(signing_key.verify(&signature, MESSAGE));
+        assert!(!public_key.verify(&signature, b"Wolf Mcnally"));
+
+        // Signatures from other algorithms are never accepted by an Ed25519 key.
+        let ecdsa_signature = ECDSA_SIGNING_PRIVATE_KEY.sign(MESSAGE).unwrap();
+        assert!(!public_key.verify(&ecdsa_signature, MESSAGE));
+    }
+
+    #[test]
+    fn test_ed25519_cbor() {
+        let signing_key = SigningPrivateKey::new_ed25519(Ed25519PrivateKey::from_data(hex!(
+            "322b5c1dd5a17c3481c2297990c85c232ed3c17b52ce9905c6ec5193ad132c36"
+        )));
+        let signature = signing_key.sign(MESSAGE).unwrap();
+        let tagged_cbor_data = signature.to_cbor_data();
+        let diagnostic = CBOR::try_from_data(&tagged_cbor_data).unwrap().diagnostic();
+        assert!(diagnostic.starts_with("40020(\n   [\n      2,"));
+        let received_signature = Signature::from_tagged_cbor_data(&tagged_cbor_data).unwrap();
+        assert_eq!(signature, received_signature);
+
+        let private_key_data = signing_key.to_cbor_data();
+        assert_eq!(SigningPrivateKey::from_tagged_cbor_data(private_key_data).unwrap(), signing_key);
+
+        let public_key = signing_key.public_key();
+        let public_key_data = public_key.to_cbor_data();
+        let received_public_key = SigningPublicKey::from_tagged_cbor_data(public_key_data).unwrap();
+        assert_eq!(received_public_key, public_key);
+        assert!(received_public_key.verify(&received_signature, MESSAGE));
+    }
+
+    #[test]
+    fn test_ecdsa_recovery() {
+        let signature = ECDSA_SIGNING_PRIVATE_KEY.ecdsa_sign_recoverable(MESSAGE).unwrap();
+        let recovered = signature.recover_public_key(MESSAGE).unwrap();
+        assert_eq!(
+            SigningPublicKey::from_ecdsa(recovered.clone()),
+            ECDSA_SIGNING_PRIVATE_KEY.public_key()
+        );
+        assert_ne!(signature.recover_public_key(b"Wolf Mcnally"), Some(recovered));
+
+        // The compact signature matches the non-recoverable one and verifies the same way.
+        let plain = ECDSA_SIGNING_PRIVATE_KEY.sign(MESSAGE).unwrap();
+        assert_eq!(signature.to_ecdsa(), plain.to_ecdsa());
+        assert!(ECDSA_SIGNING_PRIVATE_KEY.public_key().verify(&signature, MESSAGE));
+        assert_eq!(plain.recover_public_key(MESSAGE), None);
+    }
+
+    #[test]
+    fn test_ecdsa_recovery_prehashed() {
+        // The message variant hashes with double SHA-256.
+        let signature = ECDSA_SIGNING_PRIVATE_KEY.ecdsa_sign_recoverable(MESSAGE).unwrap();
+        let digest = bc_crypto::hash::double_sha256(MESSAGE.as_ref());
+        assert_eq!(signature.recover_public_key_prehashed(&digest), signature.recover_public_key(MESSAGE));
+
+        // A signature over a Keccak-256 digest, as made by an Ethereum wallet.
+        let digest: [u8; 32] = Keccak256::digest(MESSAGE.as_ref()).into();
+        let secp = secp256k1::Secp256k1::signing_only();
+        let secret_key = secp256k1::SecretKey::from_slice(
+            ECDSA_SIGNING_PRIVATE_KEY.to_ecdsa().unwrap().data()
+        ).unwrap();
+        let (recovery_id, sig) = secp
+            .sign_ecdsa_recoverable(&secp256k1::Message::from_slice(&digest).unwrap(), &secret_key)
+            .serialize_compact();
+        let signature = Signature::ecdsa_recoverable_from_data(sig, recovery_id.to_i32() as u8);
+        let recovered = signature.recover_public_key_prehashed(&digest).unwrap();
+        assert_eq!(SigningPublicKey::from_ecdsa(recovered.clone()), ECDSA_SIGNING_PRIVATE_KEY.public_key());
+        assert_ne!(signature.recover_public_key(MESSAGE), Some(recovered));
+        assert_eq!(ECDSA_SIGNING_PRIVATE_KEY.sign(MESSAGE).unwrap().recover_public_key_prehashed(&digest), None);
+    }
+
+    #[test]
+    fn test_ecdsa_recoverable_cbor() {
+        let signature = ECDSA_SIGNING_PRIVATE_KEY.ecdsa_sign_recoverable(MESSAGE).unwrap();
+        let tagged_cbor_data = signature.to_cbor_data();
+        let untagged = signature.untagged_cbor();
+        let CBORCase::Array(elements) = untagged.as_case() else {
+            panic!("expected array");
+        };
+        assert_eq!(elements.len(), 3);
+        assert_eq!(elements[0], CBOR::from(1));
+        assert_eq!(elements[2], CBOR::from(signature.ecdsa_recovery_id().unwrap()));
+        let received_signature = Signature::from_tagged_cbor_data(&tagged_cbor_data).unwrap();
+        assert_eq!(signature, received_signature);
+        assert_eq!(
+            received_signature.recover_public_key(MESSAGE).map(SigningPublicKey::from_ecdsa),
+            Some(ECDSA_SIGNING_PRIVATE_KEY.public_key())
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_schnorr_verify_all() {
+        let public_key = SCHNORR_SIGNING_PRIVATE_KEY.public_key();
+        let messages: Vec<Vec<u8>> = (0..20).map(|i| format!("message {}", i).into_bytes()).collect();
+        let mut entries: Vec<(Signature, &[u8])> = messages
+            .iter()
+            .map(|m| (SCHNORR_SIGNING_PRIVATE_KEY.sign(m).unwrap(), m.as_slice()))
+            .collect();
+        let rng = Rc::new(RefCell::new(make_fake_random_number_generator()));
+        let options = SigningOptions::Schnorr { tag: b"tagged".to_vec(), rng };
+        let tagged = SCHNORR_SIGNING_PRIVATE_KEY
+            .sign_with_options(&messages[0], Some(options))
+            .unwrap();
+        entries.push((tagged, &messages[0]));
+        assert!(public_key.verify_all(&entries));
+        assert!(public_key.verify_all(&[]));
+
+        // One signature over the wrong message fails the whole list.
+        let mut bad_entries = entries.clone();
+        bad_entries[7].1 = &messages[8];
+        assert!(!public_key.verify_all(&bad_entries));
+
+        // A signature of a different type fails the list.
+        let mut mixed_entries = entries.clone();
+        mixed_entries.push((ECDSA_SIGNING_PRIVATE_KEY.sign(MESSAGE).unwrap(), b"Wolf McNally"));
+        assert!(!public_key.verify_all(&mixed_entries));
+
+        // The default implementation agrees.
+        assert!(SCHNORR_SIGNING_PRIVATE_KEY.verify_all(&entries));
+        assert!(!SCHNORR_SIGNING_PRIVATE_KEY.verify_all(&bad_entries));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_ecdsa_der() {
+        let public_key = ECDSA_SIGNING_PRIVATE_KEY.public_key();
+        let signature = ECDSA_SIGNING_PRIVATE_KEY.sign(MESSAGE).unwrap();
+        let der = signature.to_der().unwrap();
+        assert_eq!(der[0], 0x30);
+        let decoded = Signature::ecdsa_from_der(&der).unwrap();
+        assert_eq!(decoded, signature);
+        assert_eq!(decoded.to_ecdsa(), signature.to_ecdsa());
+
+        let recoverable = ECDSA_SIGNING_PRIVATE_KEY.ecdsa_sign_recoverable(MESSAGE).unwrap();
+        assert_eq!(recoverable.to_der().unwrap(), der);
+
+        // Produced by `openssl dgst -sha256 -sign` over the SHA-256 of the
+        // message, so OpenSSL hashed it a second time as secp256k1 signing
+        // here does. This signature has a high `s`, which is normalized.
+        let openssl_der = hex!(
+            "30460221008b3f7feefb8388d8e5b5ad8ada38d21a189729f5df35e6a7ec83b1d2d2c5c71b0221009b37ec0cb2e1eeba8a0f1e7397bbebde5bf3d8fd27373f0db5772722603d908c"
+        );
+        let openssl_signature = Signature::ecdsa_from_der(openssl_der).unwrap();
+        assert!(public_key.verify(&openssl_signature, MESSAGE));
+        assert!(!public_key.verify(&openssl_signature, b"Wolf Mcnally"));
+        let normalized_der = openssl_signature.to_der().unwrap();
+        assert_ne!(normalized_der, openssl_der);
+        // `r` is unchanged.
+        assert_eq!(&normalized_der[4..37], &openssl_der[4..37]);
+
+        // This one already has a low `s`, so it round-trips exactly.
+        let openssl_der = hex!(
+            "30440220127a9bc3a1910ac651290800d7a7c60767227253516e71947cfd63308c52dbc502207314d57b3f3499d3b514604c81bcc8f3f96754d4d50e89462f09a60635fb2f48"
+        );
+        let openssl_signature = Signature::ecdsa_from_der(openssl_der).unwrap();
+        assert!(public_key.verify(&openssl_signature, MESSAGE));
+        assert_eq!(openssl_signature.to_der().unwrap(), openssl_der);
+
+        assert!(Signature::ecdsa_from_der(&der[..der.len() - 1]).is_err());
+        let ed25519 = SigningPrivateKey::new_ed25519(Ed25519PrivateKey::new());
+        assert!(ed25519.sign(MESSAGE).unwrap().to_der().is_none());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_hash() {
+        use std::collections::{ HashMap, HashSet };
+
+        let ed25519 = SigningPrivateKey::new_ed25519(Ed25519PrivateKey::new());
+        let ecdsa_signature = ECDSA_SIGNING_PRIVATE_KEY.sign(MESSAGE).unwrap();
+        let ed25519_signature = ed25519.sign(MESSAGE).unwrap();
+
+        // ECDSA and Ed25519 signing is deterministic, so these are duplicates.
+        let mut signatures = HashSet::new();
+        assert!(signatures.insert(ecdsa_signature.clone()));
+        assert!(signatures.insert(ed25519_signature.clone()));
+        assert!(!signatures.insert(ECDSA_SIGNING_PRIVATE_KEY.sign(MESSAGE).unwrap()));
+        assert!(!signatures.insert(ed25519.sign(MESSAGE).unwrap()));
+        assert!(signatures.insert(ECDSA_SIGNING_PRIVATE_KEY.sign(b"Other message").unwrap()));
+        assert_eq!(signatures.len(), 3);
+        assert!(signatures.contains(&ecdsa_signature));
+
+        let verified: HashMap<(SigningPublicKey, Signature), bool> = [
+            ((ECDSA_SIGNING_PRIVATE_KEY.public_key(), ecdsa_signature.clone()), true),
+            ((ed25519.public_key(), ecdsa_signature.clone()), false),
+        ].into_iter().collect();
+        assert!(!verified[&(ed25519.public_key(), ecdsa_signature)]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_ct_eq() {
+        let schnorr = SigningPrivateKey::new_schnorr(ECPrivateKey::new());
+        let ed25519 = SigningPrivateKey::new_ed25519(Ed25519PrivateKey::new());
+        let mut signatures = vec![
+            schnorr.sign(MESSAGE).unwrap(),
+            schnorr.sign(MESSAGE).unwrap(),
+            ECDSA_SIGNING_PRIVATE_KEY.sign(MESSAGE).unwrap(),
+            ECDSA_SIGNING_PRIVATE_KEY.sign(b"Other message").unwrap(),
+            ed25519.sign(MESSAGE).unwrap(),
+        ];
+        let sig = *signatures[0].to_schnorr().unwrap();
+        signatures.push(Signature::schnorr_from_data(sig, b"tag".to_vec()));
+        let sig = *signatures[2].to_ecdsa().unwrap();
+        signatures.push(Signature::ecdsa_recoverable_from_data(sig, 0));
+        signatures.push(Signature::ecdsa_recoverable_from_data(sig, 1));
+        #[cfg(feature = "ssh")]
+        {
+            use crate::PrivateKeyBase;
+            use ssh_key::{ Algorithm, HashAlg };
+
+            let ssh = PrivateKeyBase::new().ssh_signing_private_key(Algorithm::Ed25519, "").unwrap();
+            for namespace in ["file", "email"] {
+                let options = SigningOptions::Ssh { namespace: namespace.to_string(), hash_alg: HashAlg::Sha256 };
+                signatures.push(ssh.sign_with_options(MESSAGE, Some(options)).unwrap());
+            }
+        }
+
+        for a in &signatures {
+            assert!(a.ct_eq(&a.clone()));
+            for b in &signatures {
+                assert_eq!(a.ct_eq(b), a == b, "{:?} {:?}", a, b);
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_algorithm() {
+        use crate::SignatureAlgorithm;
+
+        let schnorr = SCHNORR_SIGNING_PRIVATE_KEY.sign(MESSAGE).unwrap();
+        assert_eq!(schnorr.algorithm(), SignatureAlgorithm::Schnorr);
+        let ecdsa = ECDSA_SIGNING_PRIVATE_KEY.sign(MESSAGE).unwrap();
+        assert_eq!(ecdsa.algorithm(), SignatureAlgorithm::ECDSA);
+        let recoverable = Signature::ecdsa_recoverable_from_data(*ecdsa.to_ecdsa().unwrap(), 0);
+        assert_eq!(recoverable.algorithm(), SignatureAlgorithm::ECDSA);
+        let ed25519 = SigningPrivateKey::new_ed25519(Ed25519PrivateKey::new()).sign(MESSAGE).unwrap();
+        assert_eq!(ed25519.algorithm(), SignatureAlgorithm::Ed25519);
+        #[cfg(feature = "ssh")]
+        {
+            use crate::PrivateKeyBase;
+            use ssh_key::{ Algorithm, HashAlg };
+
+            let ssh = PrivateKeyBase::new().ssh_signing_private_key(Algorithm::Ed25519, "").unwrap();
+            let options = SigningOptions::Ssh { namespace: "file".to_string(), hash_alg: HashAlg::Sha256 };
+            let signature = ssh.sign_with_options(MESSAGE, Some(options)).unwrap();
+            assert_eq!(signature.algorithm(), SignatureAlgorithm::SSH);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_base64() {
+        use base64::{engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD}, Engine as _};
+        use crate::{EncryptedMessage, Nonce, SymmetricKey};
+
+        let signature = SCHNORR_SIGNING_PRIVATE_KEY.sign(MESSAGE).unwrap();
+        let base64 = signature.to_base64();
+        assert_eq!(base64, STANDARD.encode(signature.tagged_cbor_data()));
+        assert_eq!(Signature::from_base64(&base64).unwrap(), signature);
+        let base64url = signature.to_base64url();
+        assert_eq!(base64url, URL_SAFE_NO_PAD.encode(signature.tagged_cbor_data()));
+        assert_eq!(Signature::from_base64url(&base64url).unwrap(), signature);
+
+        let message = SymmetricKey::new().encrypt(b"Wolf McNally", Some(b"aad"), None::<Nonce>);
+        assert_eq!(EncryptedMessage::from_base64(&message.to_base64()).unwrap(), message);
+        assert_eq!(EncryptedMessage::from_base64url(&message.to_base64url()).unwrap(), message);
+
+        // The CBOR tag is checked after decoding.
+        assert!(EncryptedMessage::from_base64(&base64).is_err());
+        assert!(EncryptedMessage::from_base64url(&base64url).is_err());
+        assert!(Signature::from_base64(&message.to_base64()).is_err());
+        assert!(Signature::from_base64("not base64!").is_err());
+    }
 }
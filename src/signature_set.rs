@@ -0,0 +1,191 @@
+use alloc::vec::Vec;
+use bc_ur::prelude::*;
+use crate::{ ComponentError, Signature, SigningPublicKey, Verifier };
+use anyhow::{ bail, Error, Result };
+
+/// A collection of signatures over the same message, made by several signers.
+///
+/// The set doesn't record who made each signature: verification is always
+/// against the public keys supplied by the caller. It is encoded in CBOR as
+/// an untagged array of tagged `Signature`s.
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct SignatureSet(Vec<Signature>);
+
+impl SignatureSet {
+    /// Creates an empty `SignatureSet`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a signature to the set if it isn't already present.
+    pub fn add(&mut self, signature: Signature) {
+        if !self.0.contains(&signature) {
+            self.0.push(signature);
+        }
+    }
+
+    /// Returns the signatures in the set, in the order they were added.
+    pub fn signatures(&self) -> &[Signature] {
+        &self.0
+    }
+
+    /// Returns the number of signatures in the set.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the set contains no signatures.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the number of distinct keys among `keys` that made a valid
+    /// signature in this set over `message`.
+    ///
+    /// A key that signed more than once, or that appears more than once in
+    /// `keys`, is counted once. Signatures that no key verifies are ignored.
+    pub fn count_valid(&self, message: impl AsRef<[u8]>, keys: &[SigningPublicKey]) -> usize {
+        let message = message.as_ref();
+        let mut signers: Vec<&SigningPublicKey> = Vec::new();
+        for key in keys {
+            if !signers.contains(&key) && self.0.iter().any(|signature| key.verify(signature, &message)) {
+                signers.push(key);
+            }
+        }
+        signers.len()
+    }
+
+    /// Returns `true` if every key in `keys` made a valid signature over
+    /// `message`, and every signature in the set was made by one of `keys`.
+    ///
+    /// An empty set with no keys is valid.
+    pub fn verify_all(&self, message: impl AsRef<[u8]>, keys: &[SigningPublicKey]) -> bool {
+        let message = message.as_ref();
+        keys.iter().all(|key| self.0.iter().any(|signature| key.verify(signature, &message))) &&
+            self.0.iter().all(|signature| keys.iter().any(|key| key.verify(signature, &message)))
+    }
+
+    /// Returns `true` if at least `threshold` distinct keys among `keys` made
+    /// a valid signature over `message`.
+    pub fn verify_threshold(
+        &self,
+        message: impl AsRef<[u8]>,
+        keys: &[SigningPublicKey],
+        threshold: usize
+    ) -> bool {
+        self.count_valid(message, keys) >= threshold
+    }
+}
+
+impl From<Vec<Signature>> for SignatureSet {
+    fn from(signatures: Vec<Signature>) -> Self {
+        let mut set = Self::new();
+        for signature in signatures {
+            set.add(signature);
+        }
+        set
+    }
+}
+
+impl From<SignatureSet> for CBOR {
+    fn from(value: SignatureSet) -> Self {
+        value.0.into_iter().map(CBOR::from).collect::<Vec<_>>().into()
+    }
+}
+
+impl TryFrom<CBOR> for SignatureSet {
+    type Error = Error;
+
+    fn try_from(cbor: CBOR) -> Result<Self, Self::Error> {
+        let CBORCase::Array(elements) = cbor.into_case() else {
+            bail!(ComponentError::InvalidFormat { component: "SignatureSet" });
+        };
+        let signatures = elements
+            .into_iter()
+            .map(Signature::try_from)
+            .collect::<Result<Vec<_>>>()?;
+        Ok(signatures.into())
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use bc_ur::prelude::*;
+
+    use crate::{ ECPrivateKey, Signer, SigningPrivateKey, SigningPublicKey, SignatureSet };
+
+    const MESSAGE: &[u8] = b"Wolf McNally";
+
+    fn signers() -> Vec<SigningPrivateKey> {
+        (0..3).map(|_| SigningPrivateKey::new_schnorr(ECPrivateKey::new())).collect()
+    }
+
+    fn public_keys(signers: &[SigningPrivateKey]) -> Vec<SigningPublicKey> {
+        signers.iter().map(|signer| signer.public_key()).collect()
+    }
+
+    #[test]
+    fn test_threshold() {
+        let signers = signers();
+        let keys = public_keys(&signers);
+
+        let mut set = SignatureSet::new();
+        set.add(signers[0].sign(&MESSAGE).unwrap());
+        assert!(set.verify_threshold(MESSAGE, &keys, 1));
+        assert!(!set.verify_threshold(MESSAGE, &keys, 2));
+
+        set.add(signers[2].sign(&MESSAGE).unwrap());
+        assert_eq!(set.count_valid(MESSAGE, &keys), 2);
+        assert!(set.verify_threshold(MESSAGE, &keys, 2));
+        assert!(!set.verify_threshold(MESSAGE, &keys, 3));
+        assert!(!set.verify_threshold(b"Other message", &keys, 1));
+        assert!(!set.verify_all(MESSAGE, &keys));
+        assert!(set.verify_all(MESSAGE, &[keys[0].clone(), keys[2].clone()]));
+    }
+
+    #[test]
+    fn test_duplicates_and_strangers() {
+        let signers = signers();
+        let keys = public_keys(&signers);
+
+        // Two different signatures by the same signer count once.
+        let mut set = SignatureSet::new();
+        set.add(signers[0].sign(&MESSAGE).unwrap());
+        set.add(signers[0].sign(&MESSAGE).unwrap());
+        assert_eq!(set.len(), 2);
+        assert_eq!(set.count_valid(MESSAGE, &keys), 1);
+        assert!(!set.verify_threshold(MESSAGE, &keys, 2));
+
+        // Listing a key twice doesn't count it twice.
+        let repeated = [keys[0].clone(), keys[0].clone()];
+        assert!(!set.verify_threshold(MESSAGE, &repeated, 2));
+
+        // A signature from a key that wasn't provided is ignored by the
+        // threshold check but fails verify_all.
+        let stranger = SigningPrivateKey::new_schnorr(ECPrivateKey::new());
+        set.add(stranger.sign(&MESSAGE).unwrap());
+        assert_eq!(set.count_valid(MESSAGE, &keys), 1);
+        assert!(!set.verify_all(MESSAGE, &keys[..1]));
+        assert!(set.verify_all(MESSAGE, &[keys[0].clone(), stranger.public_key()]));
+    }
+
+    #[test]
+    fn test_cbor() {
+        let signers = signers();
+        let signatures: Vec<_> = signers.iter().map(|signer| signer.sign(&MESSAGE).unwrap()).collect();
+        let set = SignatureSet::from(signatures.clone());
+        assert_eq!(set.signatures(), signatures.as_slice());
+
+        let cbor = CBOR::from(set.clone());
+        let CBORCase::Array(elements) = cbor.as_case() else {
+            panic!("expected an array");
+        };
+        assert_eq!(elements.len(), 3);
+        assert_eq!(elements[0], CBOR::from(signatures[0].clone()));
+
+        let decoded = SignatureSet::try_from(CBOR::try_from_data(cbor.to_cbor_data()).unwrap()).unwrap();
+        assert_eq!(decoded, set);
+        assert!(decoded.verify_all(MESSAGE, &public_keys(&signers)));
+        assert!(SignatureSet::try_from(CBOR::from(1)).is_err());
+    }
+}
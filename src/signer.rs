@@ -1,7 +1,18 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 
-use crate::{Signature, SigningOptions};
+use crate::{tagged_sha256, Signature, SignatureAlgorithm, SigningOptions, SymmetricKey};
 
+/// The message actually signed by `sign_in_context`: the BIP-340 tagged
+/// hash of `message` with `context` as the tag.
+fn context_message(message: &[u8], context: &str) -> [u8; 32] {
+    tagged_sha256(message, context.as_bytes())
+}
+
+/// A type that can sign messages.
+///
+/// The message is taken as `&dyn AsRef<[u8]>` rather than a generic
+/// parameter so that the trait is object safe, and signers of different
+/// kinds can be kept together as `Box<dyn Signer>`.
 pub trait Signer {
     fn sign_with_options(
         &self,
@@ -12,8 +23,141 @@ pub trait Signer {
     fn sign(&self, message: &dyn AsRef<[u8]>) -> Result<Signature> {
         self.sign_with_options(message, None)
     }
+
+    /// Signs `message` for use only in the given `context`, such as a
+    /// protocol name, so that the signature can't be replayed in another.
+    ///
+    /// What is signed is `SHA256(SHA256(context) || SHA256(context) ||
+    /// message)`, the BIP-340 tagged hash, for every key type. Verify with
+    /// `Verifier::verify_in_context` and the same context. SSH keys, which
+    /// need signing options, can't sign this way; use an SSH namespace
+    /// instead.
+    fn sign_in_context(&self, message: &dyn AsRef<[u8]>, context: &str) -> Result<Signature> {
+        self.sign(&context_message(message.as_ref(), context))
+    }
 }
 
+/// A type that can verify signatures on messages.
+///
+/// Like `Signer`, this trait is object safe and may be used as
+/// `Box<dyn Verifier>`.
 pub trait Verifier {
     fn verify(&self, signature: &Signature, message: &dyn AsRef<[u8]>) -> bool;
+
+    /// Verifies every `(signature, message)` pair, returning `true` only if
+    /// all of them are valid. An empty list is valid.
+    ///
+    /// This is a convenience for calling `verify` on each pair in turn, and
+    /// stops at the first invalid one. It is not batch verification: each
+    /// signature is still checked on its own.
+    fn verify_all(&self, entries: &[(Signature, &[u8])]) -> bool {
+        entries.iter().all(|(signature, message)| self.verify(signature, message))
+    }
+
+    /// Verifies a signature made by `Signer::sign_in_context` over `message`
+    /// in the given `context`. A signature made in any other context, or
+    /// without one, doesn't verify.
+    fn verify_in_context(
+        &self,
+        signature: &Signature,
+        message: &dyn AsRef<[u8]>,
+        context: &str
+    ) -> bool {
+        self.verify(signature, &context_message(message.as_ref(), context))
+    }
+
+    /// Verifies `signature` over `message` only if it was made with one of
+    /// the `allowed` algorithms.
+    ///
+    /// Returns an error, without verifying, if the signature's algorithm is
+    /// not allowed. Otherwise returns whether the signature is valid.
+    fn verify_with_policy(
+        &self,
+        signature: &Signature,
+        message: &dyn AsRef<[u8]>,
+        allowed: &[SignatureAlgorithm]
+    ) -> Result<bool> {
+        let algorithm = signature.algorithm();
+        if !allowed.contains(&algorithm) {
+            bail!("Signature algorithm {:?} is not allowed", algorithm);
+        }
+        Ok(self.verify(signature, message))
+    }
+}
+
+/// A private key that can agree on a shared `SymmetricKey` with a peer's
+/// public key, such that both parties arrive at the same key.
+pub trait KeyAgreement {
+    /// The type of the peer's public key.
+    type Public;
+
+    /// Returns an error if the peer's key can't be used with this key, such
+    /// as a key on a different curve.
+    fn agree(&self, peer: &Self::Public) -> Result<SymmetricKey>;
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::{ Signer, Verifier };
+    use crate::{ ECPrivateKey, PrivateKeyBase, SignatureAlgorithm, SigningPrivateKey };
+
+    #[test]
+    fn test_trait_objects() {
+        let ecdsa = SigningPrivateKey::new_ecdsa(ECPrivateKey::new());
+        let schnorr = SigningPrivateKey::new_schnorr(ECPrivateKey::new());
+        let base = PrivateKeyBase::new();
+        let verifiers: Vec<Box<dyn Verifier>> = vec![
+            Box::new(ecdsa.public_key()),
+            Box::new(schnorr.public_key()),
+            Box::new(base.schnorr_public_key_base()),
+        ];
+        let signers: Vec<Box<dyn Signer>> = vec![Box::new(ecdsa), Box::new(schnorr), Box::new(base)];
+
+        let message = b"Wolf McNally";
+        for (signer, verifier) in signers.iter().zip(&verifiers) {
+            let signature = signer.sign(message).unwrap();
+            assert!(verifier.verify(&signature, message));
+            assert!(!verifier.verify(&signature, b"Other message"));
+        }
+        let ecdsa_signature = signers[0].sign(message).unwrap();
+        assert!(!verifiers[1].verify(&ecdsa_signature, message));
+    }
+
+    #[test]
+    fn test_verify_with_policy() {
+        let ecdsa = SigningPrivateKey::new_ecdsa(ECPrivateKey::new());
+        let public_key = ecdsa.public_key();
+        let message = b"Wolf McNally";
+        let signature = ecdsa.sign(message).unwrap();
+
+        let err = public_key
+            .verify_with_policy(&signature, message, &[SignatureAlgorithm::Schnorr])
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Signature algorithm ECDSA is not allowed");
+        assert!(public_key.verify_with_policy(&signature, message, &[]).is_err());
+
+        let allowed = [SignatureAlgorithm::Schnorr, SignatureAlgorithm::ECDSA];
+        assert!(public_key.verify_with_policy(&signature, message, &allowed).unwrap());
+        assert!(!public_key.verify_with_policy(&signature, b"Other message", &allowed).unwrap());
+    }
+
+    #[test]
+    fn test_context() {
+        let message = b"Wolf McNally";
+        for signer in [
+            SigningPrivateKey::new_schnorr(ECPrivateKey::new()),
+            SigningPrivateKey::new_ecdsa(ECPrivateKey::new()),
+            SigningPrivateKey::new_ed25519(crate::Ed25519PrivateKey::new()),
+        ] {
+            let public_key = signer.public_key();
+            let signature = signer.sign_in_context(message, "A").unwrap();
+            assert!(public_key.verify_in_context(&signature, message, "A"));
+            assert!(!public_key.verify_in_context(&signature, message, "B"));
+            assert!(!public_key.verify_in_context(&signature, b"Other message", "A"));
+            assert!(!public_key.verify(&signature, message));
+
+            let plain = signer.sign(message).unwrap();
+            assert!(!public_key.verify_in_context(&plain, message, ""));
+        }
+    }
 }
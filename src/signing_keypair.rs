@@ -0,0 +1,79 @@
+use anyhow::Result;
+
+use crate::{ Signature, Signer, SigningOptions, SigningPrivateKey, SigningPublicKey, Verifier };
+
+/// A `SigningPrivateKey` together with its `SigningPublicKey`.
+///
+/// `SigningPrivateKey::public_key` derives the public key afresh on every
+/// call, which for secp256k1 keys is a point multiplication. A keypair
+/// derives it once, when created, and the private key can't be changed
+/// afterwards, so the two always match.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SigningKeypair {
+    private_key: SigningPrivateKey,
+    public_key: SigningPublicKey,
+}
+
+impl SigningKeypair {
+    /// Creates a keypair, deriving the public key from `private_key`.
+    pub fn new(private_key: SigningPrivateKey) -> Self {
+        let public_key = private_key.public_key();
+        Self { private_key, public_key }
+    }
+
+    /// Returns the private key.
+    pub fn private_key(&self) -> &SigningPrivateKey {
+        &self.private_key
+    }
+
+    /// Returns the public key, without deriving it again.
+    pub fn public_key(&self) -> &SigningPublicKey {
+        &self.public_key
+    }
+}
+
+impl From<SigningPrivateKey> for SigningKeypair {
+    fn from(private_key: SigningPrivateKey) -> Self {
+        Self::new(private_key)
+    }
+}
+
+impl Signer for SigningKeypair {
+    fn sign_with_options(
+        &self,
+        message: &dyn AsRef<[u8]>,
+        options: Option<SigningOptions>
+    ) -> Result<Signature> {
+        self.private_key.sign_with_options(message, options)
+    }
+}
+
+impl Verifier for SigningKeypair {
+    fn verify(&self, signature: &Signature, message: &dyn AsRef<[u8]>) -> bool {
+        self.public_key.verify(signature, message)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::SigningKeypair;
+    use crate::{ ECPrivateKey, Ed25519PrivateKey, Signer, SigningPrivateKey, Verifier };
+
+    #[test]
+    fn test_keypair() {
+        for private_key in [
+            SigningPrivateKey::new_schnorr(ECPrivateKey::new()),
+            SigningPrivateKey::new_ecdsa(ECPrivateKey::new()),
+            SigningPrivateKey::new_ed25519(Ed25519PrivateKey::new()),
+        ] {
+            let keypair = SigningKeypair::from(private_key.clone());
+            assert_eq!(keypair.public_key(), &private_key.public_key());
+            assert_eq!(keypair.private_key(), &private_key);
+
+            let signature = keypair.sign(b"Wolf McNally").unwrap();
+            assert!(keypair.verify(&signature, b"Wolf McNally"));
+            assert!(private_key.public_key().verify(&signature, b"Wolf McNally"));
+            assert!(!keypair.verify(&signature, b"Other message"));
+        }
+    }
+}
@@ -1,14 +1,21 @@
-use crate::{tags, ECKeyBase, ECPublicKey, SchnorrPublicKey, Signature, Verifier};
+use alloc::{ string::String, vec, vec::Vec };
+use crate::{
+    bech32_encoding, tags, ComponentError, Digest, DigestProvider, ECKeyBase, ECPublicKey, Ed25519PublicKey,
+    SchnorrPublicKey, Signature, Verifier,
+};
 use anyhow::{bail, Error, Result};
 use bc_ur::prelude::*;
 #[cfg(feature = "ssh")]
-use ssh_key::public::PublicKey as SSHPublicKey;
+use ssh_key::public::{
+    Ed25519PublicKey as SSHEd25519PublicKey, KeyData, PublicKey as SSHPublicKey,
+};
 
-/// A public key that can be used for signing. Supports both ECDSA and Schnorr.
+/// A public key that can be used for signing. Supports ECDSA, Schnorr, and Ed25519.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum SigningPublicKey {
     Schnorr(SchnorrPublicKey),
     ECDSA(ECPublicKey),
+    Ed25519(Ed25519PublicKey),
     #[cfg(feature = "ssh")]
     SSH(SSHPublicKey),
 }
@@ -24,6 +31,11 @@ impl SigningPublicKey {
         Self::ECDSA(key)
     }
 
+    /// Restores a `SigningPublicKey` from an `Ed25519PublicKey`.
+    pub fn from_ed25519(key: Ed25519PublicKey) -> Self {
+        Self::Ed25519(key)
+    }
+
     /// Restores a `SigningPublicKey` from an SSH public key.
     #[cfg(feature = "ssh")]
     pub fn from_ssh(key: SSHPublicKey) -> Self {
@@ -46,6 +58,14 @@ impl SigningPublicKey {
         }
     }
 
+    /// Returns the `Ed25519PublicKey` of this `SigningPublicKey`, if it is an Ed25519 key.
+    pub fn to_ed25519(&self) -> Option<&Ed25519PublicKey> {
+        match self {
+            Self::Ed25519(key) => Some(key),
+            _ => None,
+        }
+    }
+
     /// Returns the SSH public key of this `SigningPublicKey`, if it is an SSH key.
     #[cfg(feature = "ssh")]
     pub fn to_ssh(&self) -> Option<&SSHPublicKey> {
@@ -54,6 +74,93 @@ impl SigningPublicKey {
             _ => None,
         }
     }
+
+    /// Verifies an SSH signature against a message, requiring the signature
+    /// to have been made for the given namespace, as `ssh-keygen -Y verify`
+    /// does.
+    ///
+    /// `verify` accepts an SSH signature made for any namespace, so use this
+    /// instead when a particular namespace is expected. Fails unless this is
+    /// an SSH key and the signature is an SSH signature made with it.
+    #[cfg(feature = "ssh")]
+    pub fn verify_ssh(
+        &self,
+        namespace: &str,
+        signature: &Signature,
+        message: &dyn AsRef<[u8]>,
+    ) -> bool {
+        match (self, signature) {
+            (Self::SSH(key), Signature::SSH(sig)) => {
+                key.verify(namespace, message.as_ref(), sig).is_ok()
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(feature = "ssh")]
+impl SigningPublicKey {
+    /// Imports a public key in OpenSSH format, such as a line of an
+    /// `authorized_keys` file or a `.pub` file written by `ssh-keygen`.
+    ///
+    /// The result is always an SSH key, which verifies SSH signatures.
+    pub fn from_openssh(openssh: &str) -> Result<Self> {
+        Ok(Self::SSH(SSHPublicKey::from_openssh(openssh.trim())?))
+    }
+
+    /// Exports this key in OpenSSH format.
+    ///
+    /// SSH keys are exported as they are, and Ed25519 keys as OpenSSH Ed25519
+    /// keys with no comment. secp256k1 keys have no OpenSSH representation,
+    /// so exporting a Schnorr or ECDSA key is an error.
+    pub fn to_openssh(&self) -> Result<String> {
+        let key = match self {
+            Self::SSH(key) => key.clone(),
+            Self::Ed25519(key) => SSHPublicKey::new(
+                KeyData::Ed25519(SSHEd25519PublicKey(*key.data())),
+                "",
+            ),
+            _ => bail!("Key type has no OpenSSH representation"),
+        };
+        Ok(key.to_openssh()?)
+    }
+}
+
+impl SigningPublicKey {
+    /// Returns a stable identifier for this key: the SHA-256 digest of its
+    /// tagged CBOR encoding, the same as its `DigestProvider` digest.
+    ///
+    /// The encoding is tag 40022 (`signing-public-key`) wrapping either the
+    /// bare 32-byte x-only key (Schnorr) or the array `[1, key]` (ECDSA,
+    /// 33-byte compressed key) or `[2, key]` (Ed25519). An SSH key is encoded
+    /// as its OpenSSH string, comment included, under tag 40801, so keys
+    /// differing only in their comment have different fingerprints.
+    pub fn fingerprint(&self) -> Digest {
+        self.digest().into_owned()
+    }
+
+    /// Returns the first four bytes of `fingerprint` as hex, for display.
+    pub fn fingerprint_short(&self) -> String {
+        self.fingerprint().short_description()
+    }
+
+    /// Encodes this key's tagged CBOR as a bech32m string with the given
+    /// human-readable part, such as `pk`.
+    ///
+    /// Returns an error if the human-readable part is invalid or the encoding
+    /// is too long for bech32m, as for large SSH keys.
+    pub fn bech32(&self, hrp: &str) -> Result<String> {
+        bech32_encoding::encode(hrp, &self.tagged_cbor_data())
+    }
+
+    /// Decodes a key from a bech32m string with the given human-readable
+    /// part.
+    ///
+    /// Returns an error if the checksum does not match, the human-readable
+    /// part differs, or the data is not a tagged `SigningPublicKey`.
+    pub fn from_bech32(bech32: &str, hrp: &str) -> Result<Self> {
+        Self::from_tagged_cbor_data(bech32_encoding::decode(bech32, hrp)?)
+    }
 }
 
 impl Verifier for SigningPublicKey {
@@ -69,7 +176,13 @@ impl Verifier for SigningPublicKey {
                 _ => false,
             },
             SigningPublicKey::ECDSA(key) => match signature {
-                Signature::ECDSA(sig) => key.verify(sig, message),
+                Signature::ECDSA(sig) | Signature::ECDSARecoverable { sig, .. } => {
+                    key.verify(sig, message)
+                }
+                _ => false,
+            },
+            SigningPublicKey::Ed25519(key) => match signature {
+                Signature::Ed25519(sig) => key.verify(sig, message),
                 _ => false,
             },
             #[cfg(feature = "ssh")]
@@ -79,6 +192,28 @@ impl Verifier for SigningPublicKey {
             },
         }
     }
+
+    /// Verifies each signature against its message, as `verify` would.
+    ///
+    /// Any entry whose signature type doesn't match this key fails the whole
+    /// list, exactly as it would fail `verify`.
+    fn verify_all(&self, entries: &[(Signature, &[u8])]) -> bool {
+        match self {
+            SigningPublicKey::Schnorr(key) => {
+                let mut schnorr_entries = Vec::with_capacity(entries.len());
+                for (signature, message) in entries {
+                    match signature {
+                        Signature::Schnorr { sig, tag } => {
+                            schnorr_entries.push((sig, *message, tag.as_slice()))
+                        }
+                        _ => return false,
+                    }
+                }
+                key.schnorr_verify_all(schnorr_entries)
+            }
+            _ => entries.iter().all(|(signature, message)| self.verify(signature, message)),
+        }
+    }
 }
 
 impl AsRef<SigningPublicKey> for SigningPublicKey {
@@ -106,6 +241,9 @@ impl CBORTaggedEncodable for SigningPublicKey {
             SigningPublicKey::ECDSA(key) => {
                 vec![(1).into(), CBOR::to_byte_string(key.data())].into()
             }
+            SigningPublicKey::Ed25519(key) => {
+                vec![(2).into(), CBOR::to_byte_string(key.data())].into()
+            }
             #[cfg(feature = "ssh")]
             SigningPublicKey::SSH(key) => {
                 let string = key.to_openssh().unwrap();
@@ -124,6 +262,10 @@ impl TryFrom<CBOR> for SigningPublicKey {
 }
 
 impl CBORTaggedDecodable for SigningPublicKey {
+    fn from_tagged_cbor(cbor: CBOR) -> Result<Self> {
+        crate::component_error::decode_tagged(cbor, "signing public key")
+    }
+
     fn from_untagged_cbor(untagged_cbor: CBOR) -> Result<Self> {
         match untagged_cbor.into_case() {
             CBORCase::ByteString(data) => Ok(Self::Schnorr(SchnorrPublicKey::from_data_ref(data)?)),
@@ -132,24 +274,111 @@ impl CBORTaggedDecodable for SigningPublicKey {
                     let mut drain = elements.drain(0..);
                     let ele_0 = drain.next().unwrap().into_case();
                     let ele_1 = drain.next().unwrap().into_case();
-                    if let CBORCase::Unsigned(1) = ele_0 {
-                        if let CBORCase::ByteString(data) = ele_1 {
+                    match (ele_0, ele_1) {
+                        (CBORCase::Unsigned(1), CBORCase::ByteString(data)) => {
                             return Ok(Self::ECDSA(ECPublicKey::from_data_ref(data)?));
                         }
+                        (CBORCase::Unsigned(2), CBORCase::ByteString(data)) => {
+                            return Ok(Self::Ed25519(Ed25519PublicKey::from_data_ref(data)?));
+                        }
+                        (CBORCase::Unsigned(value), CBORCase::ByteString(_)) => {
+                            bail!(ComponentError::UnknownAlgorithm { component: "signing public key", value });
+                        }
+                        _ => (),
                     }
                 }
-                bail!("invalid signing public key");
+                bail!(ComponentError::InvalidFormat { component: "signing public key" });
             }
             #[cfg(feature = "ssh")]
             CBORCase::Tagged(tag, item) => {
                 if tag == tags::SSH_TEXT_PUBLIC_KEY {
                     let string = item.try_into_text()?;
-                    let key = SSHPublicKey::from_openssh(&string)?;
+                    let Ok(key) = SSHPublicKey::from_openssh(&string) else {
+                        bail!(ComponentError::MalformedBytes { component: "SSH public key" });
+                    };
                     return Ok(Self::SSH(key));
                 }
-                bail!("invalid signing public key");
+                bail!(ComponentError::WrongTag { component: "signing public key", tag: tag.value() });
             }
-            _ => bail!("invalid signing public key"),
+            _ => bail!(ComponentError::InvalidFormat { component: "signing public key" }),
         }
     }
 }
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use bc_ur::prelude::*;
+    use hex_literal::hex;
+
+    use crate::{
+        Digest, ECPrivateKey, Ed25519PrivateKey, Signer, SigningPrivateKey, SigningPublicKey, Verifier,
+    };
+
+    #[test]
+    fn test_fingerprint() {
+        let private_key = ECPrivateKey::from_data(
+            hex!("322b5c1dd5a17c3481c2297990c85c232ed3c17b52ce9905c6ec5193ad132c36")
+        );
+        let schnorr = SigningPrivateKey::new_schnorr(private_key.clone()).public_key();
+        let ecdsa = SigningPrivateKey::new_ecdsa(private_key).public_key();
+        let ed25519 = SigningPrivateKey::new_ed25519(Ed25519PrivateKey::new()).public_key();
+
+        assert_eq!(schnorr.fingerprint(), Digest::from_image(schnorr.to_cbor_data()));
+        assert_eq!(schnorr.fingerprint().hex(), "d40e0602674df1b732f5e025d04c45f2e74ed1652c5ae1740f6a5502dbbdcd47");
+        assert_eq!(schnorr.fingerprint_short(), "d40e0602");
+        assert_eq!(ecdsa.fingerprint_short(), "863a331a");
+
+        // The same secret gives different fingerprints for different key types.
+        assert_ne!(schnorr.fingerprint(), ecdsa.fingerprint());
+        assert_ne!(ecdsa.fingerprint(), ed25519.fingerprint());
+        assert_eq!(ed25519.fingerprint(), ed25519.clone().fingerprint());
+    }
+
+    #[test]
+    fn test_bech32() {
+        let private_key = ECPrivateKey::from_data(
+            hex!("322b5c1dd5a17c3481c2297990c85c232ed3c17b52ce9905c6ec5193ad132c36")
+        );
+        let schnorr = SigningPrivateKey::new_schnorr(private_key.clone()).public_key();
+        let encoded = schnorr.bech32("pk").unwrap();
+        assert_eq!(encoded, "pk1mxw9vkpqaqj3msap0c8jcpuxtmgezyu7e0xae0wswrkplaja752gclh5qpdqvvy4qd");
+        assert_eq!(SigningPublicKey::from_bech32(&encoded, "pk").unwrap(), schnorr);
+
+        let ecdsa = SigningPrivateKey::new_ecdsa(private_key).public_key();
+        let encoded = ecdsa.bech32("pk").unwrap();
+        assert_eq!(SigningPublicKey::from_bech32(&encoded, "pk").unwrap(), ecdsa);
+
+        let mut corrupted: Vec<char> = encoded.chars().collect();
+        corrupted[10] = if corrupted[10] == 'q' { 'p' } else { 'q' };
+        let corrupted: String = corrupted.into_iter().collect();
+        assert!(SigningPublicKey::from_bech32(&corrupted, "pk").is_err());
+    }
+
+    #[test]
+    fn test_cbor() {
+        let private_key = ECPrivateKey::from_data(
+            hex!("322b5c1dd5a17c3481c2297990c85c232ed3c17b52ce9905c6ec5193ad132c36")
+        );
+        let schnorr = SigningPrivateKey::new_schnorr(private_key.clone());
+        let ecdsa = SigningPrivateKey::new_ecdsa(private_key);
+
+        // The encoding is pinned: Schnorr keys are a bare byte string, ECDSA
+        // keys are `[1, key]`, and Ed25519 keys are `[2, key]`.
+        let schnorr_public = schnorr.public_key();
+        assert_eq!(hex::encode(schnorr_public.to_cbor_data()), "d99c565820e8251dc3a17e0f2c07865ed191139ecbcddcbdd070ec1ff65df5148c7ef4005a");
+        let ecdsa_public = ecdsa.public_key();
+        assert_eq!(hex::encode(ecdsa_public.to_cbor_data()), "d99c568201582102e8251dc3a17e0f2c07865ed191139ecbcddcbdd070ec1ff65df5148c7ef4005a");
+
+        for (signer, public_key) in [(schnorr, schnorr_public), (ecdsa, ecdsa_public)] {
+            let decoded = SigningPublicKey::from_tagged_cbor_data(public_key.to_cbor_data()).unwrap();
+            assert_eq!(decoded, public_key);
+            let signature = signer.sign(&b"Wolf McNally").unwrap();
+            assert!(decoded.verify(&signature, &b"Wolf McNally"));
+        }
+
+        let ed25519 = SigningPrivateKey::new_ed25519(Ed25519PrivateKey::new()).public_key();
+        let elements = ed25519.untagged_cbor().try_into_array().unwrap();
+        assert_eq!(elements[0], CBOR::from(2));
+        assert_eq!(SigningPublicKey::from_tagged_cbor_data(ed25519.to_cbor_data()).unwrap(), ed25519);
+    }
+}
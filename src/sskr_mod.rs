@@ -1,4 +1,5 @@
-use bc_rand::{ RandomNumberGenerator, SecureRandomNumberGenerator };
+use alloc::{ string::String, vec, vec::Vec };
+use bc_rand::RandomNumberGenerator;
 use bc_ur::prelude::*;
 use sskr::SSKRError;
 use anyhow::{ Result, Error };
@@ -98,6 +99,10 @@ impl TryFrom<CBOR> for SSKRShare {
 }
 
 impl CBORTaggedDecodable for SSKRShare {
+    fn from_tagged_cbor(cbor: CBOR) -> Result<Self> {
+        crate::component_error::decode_tagged(cbor, "SSKR share")
+    }
+
     fn from_untagged_cbor(cbor: CBOR) -> Result<Self> {
         let data = CBOR::try_into_byte_string(cbor)?;
         let instance = Self::from_data(data);
@@ -111,11 +116,12 @@ impl CBORTaggedDecodable for SSKRShare {
 ///
 /// * `spec` - The `Spec` instance that defines the group and member thresholds.
 /// * `master_secret` - The `Secret` instance to be split into shares.
+#[cfg(feature = "std")]
 pub fn sskr_generate(
     spec: &SSKRSpec,
     master_secret: &SSKRSecret
 ) -> Result<Vec<Vec<SSKRShare>>, SSKRError> {
-    let mut rng = SecureRandomNumberGenerator;
+    let mut rng = bc_rand::SecureRandomNumberGenerator;
     sskr_generate_using(spec, master_secret, &mut rng)
 }
 
@@ -1,16 +1,48 @@
-use crate::{ EncryptedMessage, Nonce, tags, Digest };
-use bc_crypto::{ aead_chacha20_poly1305_encrypt_with_aad, aead_chacha20_poly1305_decrypt_with_aad };
+use alloc::{ string::String, vec, vec::Vec };
+use crate::{
+    AeadAlgorithm,
+    AuthenticationTag,
+    ComponentError,
+    EncryptedMessage,
+    KdfParams,
+    Nonce,
+    NonceTracker,
+    Salt,
+    tags,
+    sskr_combine,
+    sskr_generate_using,
+    SSKRSecret,
+    SSKRShare,
+    SSKRSpec,
+};
+use crate::EncryptBuilder;
+#[cfg(feature = "std")]
+use crate::Digest;
+use aes_gcm::{ aead::AeadInPlace, Aes256Gcm, KeyInit };
+use aes_gcm_siv::Aes256GcmSiv;
+use bc_crypto::{
+    aead_chacha20_poly1305_encrypt_with_aad,
+    aead_chacha20_poly1305_decrypt_with_aad,
+    hash::hmac_sha256,
+};
+use hkdf::Hkdf;
+use sha2::Sha256;
 use bc_ur::prelude::*;
 use anyhow::{ bail, Result, Error };
+use subtle::ConstantTimeEq;
+use zeroize::{ Zeroize, ZeroizeOnDrop };
 
 /// A symmetric encryption key.
-#[derive(Clone, PartialEq, Eq, Hash)]
+///
+/// The key material is zeroized when the key is dropped.
+#[derive(Clone, PartialEq, Eq, Hash, Zeroize, ZeroizeOnDrop)]
 pub struct SymmetricKey([u8; Self::SYMMETRIC_KEY_SIZE]);
 
 impl SymmetricKey {
     pub const SYMMETRIC_KEY_SIZE: usize = 32;
 
     /// Create a new random symmetric key.
+    #[cfg(feature = "std")]
     pub fn new() -> Self {
         let mut rng = bc_rand::SecureRandomNumberGenerator;
         Self::new_using(&mut rng)
@@ -23,31 +55,49 @@ impl SymmetricKey {
         Self::from_data(key)
     }
 
-    /// Create a new symmetric key from data.
+    /// Deterministically derive a symmetric key from a seed, for golden tests.
+    ///
+    /// The same seed always gives the same key. This is only available with
+    /// the `testing` feature and must not be used to make production keys.
+    #[cfg(feature = "testing")]
+    pub fn from_seed(seed: &[u8]) -> Self {
+        Self::from_data(hkdf_sha256(seed, Some(b"testing"), b"symmetric-key"))
+    }
+
+    /// Create a new symmetric key from data, such as a key derived or stored
+    /// elsewhere.
     pub const fn from_data(data: [u8; Self::SYMMETRIC_KEY_SIZE]) -> Self {
         Self(data)
     }
 
-    /// Create a new symmetric key from data.
+    /// Create a new symmetric key from data, such as a key derived or stored
+    /// elsewhere.
+    ///
+    /// Returns an error unless the data is exactly 32 bytes.
     pub fn from_data_ref(data: impl AsRef<[u8]>) -> Result<Self> {
         let data = data.as_ref();
         if data.len() != Self::SYMMETRIC_KEY_SIZE {
-            bail!("Invalid symmetric key size");
+            bail!(ComponentError::InvalidLength { component: "symmetric key" });
         }
         let mut arr = [0u8; Self::SYMMETRIC_KEY_SIZE];
         arr.copy_from_slice(data);
         Ok(Self::from_data(arr))
     }
 
-    /// Get the data of the symmetric key.
+    /// Get the data of the symmetric key, for export, such as to an HSM.
+    ///
+    /// This is the secret key material itself. `from_data` and
+    /// `from_data_ref` restore the key from it.
     pub fn data(&self) -> &[u8; Self::SYMMETRIC_KEY_SIZE] {
         self.into()
     }
 
     /// Create a new symmetric key from the given hexadecimal string.
     ///
+    /// Returns an error if the string is not exactly 64 hexadecimal digits.
+    ///
     /// # Panics
-    /// Panics if the string is not exactly 24 hexadecimal digits.
+    /// Panics if the string is not valid hexadecimal.
     pub fn from_hex(hex: impl AsRef<str>) -> Result<Self> {
         Self::from_data_ref(hex::decode(hex.as_ref()).unwrap())
     }
@@ -57,26 +107,290 @@ impl SymmetricKey {
         hex::encode(self.data())
     }
 
+    /// Compare two keys in constant time.
+    ///
+    /// Unlike `==`, this does not short-circuit on the first differing byte.
+    pub fn ct_eq(&self, other: &SymmetricKey) -> bool {
+        self.0.ct_eq(&other.0).into()
+    }
+
+    /// Derive a new symmetric key from this one using HKDF-SHA256.
+    ///
+    /// Different `info` values yield independent keys, so one master key can
+    /// serve several purposes with domain separation. The derivation is
+    /// deterministic for the same key, salt, and info.
+    pub fn derive(&self, salt: Option<&[u8]>, info: &[u8]) -> SymmetricKey {
+        Self::from_data(hkdf_sha256(self.data(), salt, info))
+    }
+
+    /// Split this key into two shares, both of which are needed to
+    /// reconstruct it with `combine2`.
+    ///
+    /// The first share is a random key and the second is its XOR with this
+    /// key, so either share alone is a uniformly random key that reveals
+    /// nothing about this one. For k-of-n sharing, use SSKR.
+    #[cfg(feature = "std")]
+    pub fn split2(&self) -> (SymmetricKey, SymmetricKey) {
+        let mut rng = bc_rand::SecureRandomNumberGenerator;
+        self.split2_using(&mut rng)
+    }
+
+    /// Split this key into two shares like `split2`, using the given random
+    /// number generator.
+    pub fn split2_using(
+        &self,
+        rng: &mut impl bc_rand::RandomNumberGenerator
+    ) -> (SymmetricKey, SymmetricKey) {
+        let a = Self::new_using(rng);
+        let b = Self::combine2(self, &a);
+        (a, b)
+    }
+
+    /// Reconstruct a key from the two shares made by `split2`, in either
+    /// order.
+    pub fn combine2(a: &SymmetricKey, b: &SymmetricKey) -> SymmetricKey {
+        let mut data = [0u8; Self::SYMMETRIC_KEY_SIZE];
+        for (byte, (x, y)) in data.iter_mut().zip(a.0.iter().zip(b.0.iter())) {
+            *byte = x ^ y;
+        }
+        let key = Self::from_data(data);
+        data.zeroize();
+        key
+    }
+
+    /// Split this key into SSKR shares, grouped as described by `spec`.
+    ///
+    /// Each share is UR-encodable as `ur:sskr`, and `from_sskr_shares`
+    /// recovers the key from any set of shares that meets the group and
+    /// member thresholds of `spec`.
+    #[cfg(feature = "std")]
+    pub fn to_sskr_shares(&self, spec: &SSKRSpec) -> Result<Vec<Vec<SSKRShare>>> {
+        let mut rng = bc_rand::SecureRandomNumberGenerator;
+        self.to_sskr_shares_using(spec, &mut rng)
+    }
+
+    /// Split this key into SSKR shares like `to_sskr_shares`, using the given
+    /// random number generator.
+    pub fn to_sskr_shares_using(
+        &self,
+        spec: &SSKRSpec,
+        rng: &mut impl bc_rand::RandomNumberGenerator
+    ) -> Result<Vec<Vec<SSKRShare>>> {
+        let secret = SSKRSecret::new(self.data())?;
+        Ok(sskr_generate_using(spec, &secret, rng)?)
+    }
+
+    /// Recover a key split by `to_sskr_shares`.
+    ///
+    /// Returns an error if the shares don't meet the thresholds of the split,
+    /// or don't reconstruct a secret of the size of a symmetric key.
+    pub fn from_sskr_shares(shares: &[SSKRShare]) -> Result<Self> {
+        let secret = sskr_combine(shares)?;
+        Self::from_data_ref(secret.data())
+    }
+
+    /// Derive a symmetric key from a password, using the given salt and
+    /// key derivation parameters.
+    ///
+    /// The same password, salt, and parameters always reproduce the same key.
+    /// The salt and parameters are not secret and can be stored (both are
+    /// CBOR-encodable) alongside the ciphertext.
+    ///
+    /// Returns an error if the parameters are out of range for the algorithm.
+    pub fn from_password(
+        password: impl AsRef<[u8]>,
+        salt: &Salt,
+        params: &KdfParams
+    ) -> Result<Self> {
+        let mut key = [0u8; Self::SYMMETRIC_KEY_SIZE];
+        params.derive_into(password.as_ref(), salt.as_ref(), &mut key)?;
+        Ok(Self::from_data(key))
+    }
+
     /// Encrypt the given plaintext with this key, and the given additional authenticated data and nonce.
+    #[cfg(feature = "std")]
     pub fn encrypt(
         &self,
         plaintext: impl Into<Vec<u8>>,
         aad: Option<impl Into<Vec<u8>>>,
         nonce: Option<impl AsRef<Nonce>>
+    ) -> EncryptedMessage {
+        self.encrypt_using(plaintext, aad, nonce, &mut bc_rand::SecureRandomNumberGenerator)
+    }
+
+    /// Encrypt the given plaintext with this key, and the given additional
+    /// authenticated data and nonce, drawing a missing nonce from the given
+    /// random number generator.
+    pub fn encrypt_using(
+        &self,
+        plaintext: impl Into<Vec<u8>>,
+        aad: Option<impl Into<Vec<u8>>>,
+        nonce: Option<impl AsRef<Nonce>>,
+        rng: &mut impl bc_rand::RandomNumberGenerator
+    ) -> EncryptedMessage {
+        self.encrypt_with_algorithm_using(plaintext, aad, nonce, AeadAlgorithm::default(), rng)
+    }
+
+    /// Encrypt the given plaintext with this key and a random nonce, binding the
+    /// given additional authenticated data (AAD) to the message.
+    ///
+    /// The AAD is carried unencrypted in the resulting `EncryptedMessage` and is
+    /// covered by its authentication tag, so `decrypt` needs only the message.
+    #[cfg(feature = "std")]
+    pub fn encrypt_with_aad(
+        &self,
+        plaintext: impl Into<Vec<u8>>,
+        aad: impl Into<Vec<u8>>
+    ) -> EncryptedMessage {
+        self.encrypt(plaintext, Some(aad), None::<Nonce>)
+    }
+
+    /// Encrypt the given plaintext with this key and an explicit nonce, binding
+    /// the given additional authenticated data (AAD) to the message.
+    ///
+    /// The same key, nonce, plaintext, and AAD always give the same message,
+    /// which makes test vectors reproducible. Reusing a nonce with the same
+    /// key for different plaintexts destroys confidentiality, so callers that
+    /// supply their own nonces should pass a `NonceTracker` for the key. The
+    /// nonce is then recorded, and an error is returned if it was already
+    /// recorded.
+    ///
+    /// An all-zero nonce is always rejected with an error, as it is the
+    /// likeliest to be reused by accident. `NonceSequence` never produces it.
+    pub fn encrypt_with_nonce(
+        &self,
+        plaintext: impl Into<Vec<u8>>,
+        nonce: &Nonce,
+        aad: impl Into<Vec<u8>>,
+        tracker: Option<&mut NonceTracker>
+    ) -> Result<EncryptedMessage> {
+        if nonce.is_zero() {
+            bail!(ComponentError::ZeroNonce);
+        }
+        if let Some(tracker) = tracker {
+            tracker.record(nonce)?;
+        }
+        Ok(self.encrypt_with_resolved_nonce(plaintext.into(), aad.into(), nonce.clone(), AeadAlgorithm::default()))
+    }
+
+    /// Encrypt the given plaintext with this key using the given AEAD algorithm,
+    /// and the given additional authenticated data and nonce.
+    ///
+    /// If no nonce is given, a random one is used, except with
+    /// `AeadAlgorithm::Aes256GcmSiv`, which derives it from this key, the
+    /// AAD, and the plaintext so that encryption is deterministic.
+    #[cfg(feature = "std")]
+    pub fn encrypt_with_algorithm(
+        &self,
+        plaintext: impl Into<Vec<u8>>,
+        aad: Option<impl Into<Vec<u8>>>,
+        nonce: Option<impl AsRef<Nonce>>,
+        algorithm: AeadAlgorithm
+    ) -> EncryptedMessage {
+        self.encrypt_with_algorithm_using(
+            plaintext,
+            aad,
+            nonce,
+            algorithm,
+            &mut bc_rand::SecureRandomNumberGenerator
+        )
+    }
+
+    /// Encrypt the given plaintext with this key using the given AEAD algorithm,
+    /// and the given additional authenticated data and nonce, drawing a missing
+    /// nonce from the given random number generator.
+    ///
+    /// As with `encrypt_with_algorithm`, `AeadAlgorithm::Aes256GcmSiv` derives
+    /// a missing nonce instead, and doesn't use the generator.
+    pub fn encrypt_with_algorithm_using(
+        &self,
+        plaintext: impl Into<Vec<u8>>,
+        aad: Option<impl Into<Vec<u8>>>,
+        nonce: Option<impl AsRef<Nonce>>,
+        algorithm: AeadAlgorithm,
+        rng: &mut impl bc_rand::RandomNumberGenerator
     ) -> EncryptedMessage {
         let aad: Vec<u8> = aad.map(|a| a.into()).unwrap_or_default();
-        let nonce: Nonce = nonce.map(|n| n.as_ref().clone()).unwrap_or_default();
         let plaintext = plaintext.into();
-        let (ciphertext, auth) = aead_chacha20_poly1305_encrypt_with_aad(
+        let nonce: Nonce = match nonce {
+            Some(nonce) => nonce.as_ref().clone(),
+            None if algorithm == AeadAlgorithm::Aes256GcmSiv => self.synthetic_nonce(&plaintext, &aad),
+            None => Nonce::new_using(rng),
+        };
+        self.encrypt_with_resolved_nonce(plaintext, aad, nonce, algorithm)
+    }
+
+    fn encrypt_with_resolved_nonce(
+        &self,
+        plaintext: Vec<u8>,
+        aad: Vec<u8>,
+        nonce: Nonce,
+        algorithm: AeadAlgorithm
+    ) -> EncryptedMessage {
+        let (ciphertext, auth) = match algorithm {
+            AeadAlgorithm::ChaCha20Poly1305 => {
+                let (ciphertext, auth) = aead_chacha20_poly1305_encrypt_with_aad(
+                    plaintext,
+                    self.into(),
+                    (&nonce).into(),
+                    &aad
+                );
+                (ciphertext, auth.into())
+            }
+            AeadAlgorithm::Aes256Gcm => self.aes_256_gcm_encrypt(plaintext, &nonce, &aad),
+            AeadAlgorithm::Aes256GcmSiv => self.aes_256_gcm_siv_encrypt(plaintext, &nonce, &aad),
+        };
+        EncryptedMessage::new_with_algorithm(algorithm, ciphertext, aad, nonce, auth)
+    }
+
+    /// Encrypt the given plaintext with this key using the given AEAD algorithm,
+    /// and the given additional authenticated data and nonce, committing the
+    /// message to this key.
+    ///
+    /// The message carries an HMAC-SHA-256 of its nonce under this key, which
+    /// `decrypt` checks before decrypting, so the message can't be decrypted
+    /// under any other key even if the ciphertext was crafted to authenticate
+    /// under one.
+    #[cfg(feature = "std")]
+    pub fn encrypt_committing(
+        &self,
+        plaintext: impl Into<Vec<u8>>,
+        aad: Option<impl Into<Vec<u8>>>,
+        nonce: Option<impl AsRef<Nonce>>,
+        algorithm: AeadAlgorithm
+    ) -> EncryptedMessage {
+        self.encrypt_committing_using(
             plaintext,
-            self.into(),
-            (&nonce).into(),
-            &aad
-        );
-        EncryptedMessage::new(ciphertext, aad, nonce, auth.into())
+            aad,
+            nonce,
+            algorithm,
+            &mut bc_rand::SecureRandomNumberGenerator
+        )
+    }
+
+    /// Encrypt the given plaintext like `encrypt_committing`, drawing a missing
+    /// nonce from the given random number generator.
+    pub fn encrypt_committing_using(
+        &self,
+        plaintext: impl Into<Vec<u8>>,
+        aad: Option<impl Into<Vec<u8>>>,
+        nonce: Option<impl AsRef<Nonce>>,
+        algorithm: AeadAlgorithm,
+        rng: &mut impl bc_rand::RandomNumberGenerator
+    ) -> EncryptedMessage {
+        let message = self.encrypt_with_algorithm_using(plaintext, aad, nonce, algorithm, rng);
+        let key_commitment = self.key_commitment(message.nonce());
+        message.with_key_commitment(key_commitment)
+    }
+
+    /// Returns a builder for encrypting a message under this key with a
+    /// combination of AAD, nonce, algorithm, and key commitment.
+    pub fn encrypt_builder(&self) -> EncryptBuilder<'_> {
+        EncryptBuilder::new(self)
     }
 
     /// Encrypt the given plaintext with this key, and the given digest of the plaintext, and nonce.
+    #[cfg(feature = "std")]
     pub fn encrypt_with_digest(
         &self,
         plaintext: impl Into<Vec<u8>>,
@@ -88,24 +402,224 @@ impl SymmetricKey {
         self.encrypt(plaintext, Some(data), nonce)
     }
 
+    /// Wrap (encrypt) another key, or any tagged CBOR value, under this key.
+    ///
+    /// The target is serialized as tagged CBOR and encrypted with a random
+    /// nonce, so wrapping the same target twice gives different messages.
+    #[cfg(feature = "std")]
+    pub fn wrap_key(&self, target: &impl CBORTaggedEncodable) -> EncryptedMessage {
+        self.wrap_key_using(target, &mut bc_rand::SecureRandomNumberGenerator)
+    }
+
+    /// Wrap (encrypt) another key, or any tagged CBOR value, under this key,
+    /// drawing the nonce from the given random number generator.
+    pub fn wrap_key_using(
+        &self,
+        target: &impl CBORTaggedEncodable,
+        rng: &mut impl bc_rand::RandomNumberGenerator
+    ) -> EncryptedMessage {
+        self.encrypt_using(target.tagged_cbor().to_cbor_data(), None::<Vec<u8>>, None::<Nonce>, rng)
+    }
+
+    /// Unwrap (decrypt) a key, or any tagged CBOR value, wrapped by `wrap_key`.
+    ///
+    /// Returns an error if this is not the key the target was wrapped under,
+    /// the message has been tampered with, or the plaintext is not the
+    /// expected type.
+    pub fn unwrap_key<T: CBORTaggedDecodable>(&self, message: &EncryptedMessage) -> Result<T> {
+        let plaintext = self.decrypt(message)?;
+        T::from_tagged_cbor_data(plaintext)
+    }
+
     /// Decrypt the given encrypted message with this key.
+    ///
+    /// The AEAD algorithm is taken from the message.
+    ///
+    /// If the message carries a key commitment, it must be a commitment to
+    /// this key.
     pub fn decrypt(&self, message: &EncryptedMessage) -> Result<Vec<u8>, bc_crypto::Error> {
-        aead_chacha20_poly1305_decrypt_with_aad(
-            message.ciphertext(),
-            self.into(),
-            message.nonce().into(),
-            message.aad(),
-            message.authentication_tag().into()
-        )
+        if let Some(key_commitment) = message.key_commitment() {
+            if !bool::from(self.key_commitment(message.nonce()).ct_eq(key_commitment)) {
+                return Err(bc_crypto::Error::DecryptFailed);
+            }
+        }
+        match message.algorithm() {
+            AeadAlgorithm::ChaCha20Poly1305 => aead_chacha20_poly1305_decrypt_with_aad(
+                message.ciphertext(),
+                self.into(),
+                message.nonce().into(),
+                message.aad(),
+                message.authentication_tag().into()
+            ),
+            AeadAlgorithm::Aes256Gcm => self.aes_256_gcm_decrypt(message),
+            AeadAlgorithm::Aes256GcmSiv => self.aes_256_gcm_siv_decrypt(message),
+        }
+    }
+
+    /// Decrypt the given encrypted message with this key, requiring it to
+    /// carry a commitment to this key.
+    ///
+    /// Use this instead of `decrypt` when trying several keys on messages
+    /// that are all expected to be key-committing, so that a message with its
+    /// commitment stripped is rejected.
+    pub fn decrypt_committing(&self, message: &EncryptedMessage) -> Result<Vec<u8>, bc_crypto::Error> {
+        if !message.is_key_committing() {
+            return Err(bc_crypto::Error::DecryptFailed);
+        }
+        self.decrypt(message)
+    }
+
+    /// Encrypt the given plaintext with this key, the given nonce, and the
+    /// given additional authenticated data, returning the ciphertext and the
+    /// authentication tag separately.
+    ///
+    /// This is for protocols that carry the tag in a different field from the
+    /// ciphertext. The result is the same as the ciphertext and tag of the
+    /// `EncryptedMessage` returned by `encrypt` with the same inputs, using
+    /// the default AEAD algorithm.
+    ///
+    /// As with `encrypt_with_nonce`, an all-zero nonce is rejected with an
+    /// error.
+    pub fn encrypt_detached(
+        &self,
+        plaintext: impl Into<Vec<u8>>,
+        nonce: &Nonce,
+        aad: impl Into<Vec<u8>>
+    ) -> Result<(Vec<u8>, [u8; AuthenticationTag::AUTHENTICATION_TAG_SIZE])> {
+        let message = self.encrypt_with_nonce(plaintext, nonce, aad, None)?;
+        Ok((message.ciphertext().clone(), *message.authentication_tag().data()))
+    }
+
+    /// Decrypt a ciphertext and authentication tag produced by
+    /// `encrypt_detached` with this key, the same nonce, and the same
+    /// additional authenticated data.
+    pub fn decrypt_detached(
+        &self,
+        ciphertext: impl Into<Vec<u8>>,
+        tag: &[u8; AuthenticationTag::AUTHENTICATION_TAG_SIZE],
+        nonce: &Nonce,
+        aad: impl Into<Vec<u8>>
+    ) -> Result<Vec<u8>, bc_crypto::Error> {
+        let message = EncryptedMessage::new(
+            ciphertext,
+            aad,
+            nonce.clone(),
+            AuthenticationTag::from_data(*tag)
+        );
+        self.decrypt(&message)
+    }
+
+    fn key_commitment(&self, nonce: &Nonce) -> [u8; EncryptedMessage::KEY_COMMITMENT_SIZE] {
+        hmac_sha256(self.data(), [b"key-commitment".as_slice(), nonce.data()].concat())
+    }
+
+    /// The first 12 bytes of the HMAC-SHA-256 under this key of
+    /// `"synthetic-nonce" || len(aad) || aad || plaintext`, where `len(aad)`
+    /// is an 8-byte big-endian integer.
+    fn synthetic_nonce(&self, plaintext: &[u8], aad: &[u8]) -> Nonce {
+        let mac = hmac_sha256(
+            self.data(),
+            [b"synthetic-nonce".as_slice(), &(aad.len() as u64).to_be_bytes(), aad, plaintext].concat()
+        );
+        Nonce::from_data_ref(&mac[..Nonce::NONCE_SIZE]).unwrap()
+    }
+
+    fn aes_256_gcm_encrypt(
+        &self,
+        plaintext: Vec<u8>,
+        nonce: &Nonce,
+        aad: &[u8]
+    ) -> (Vec<u8>, AuthenticationTag) {
+        let cipher = Aes256Gcm::new(self.data().into());
+        let mut buffer = plaintext;
+        let tag = cipher
+            .encrypt_in_place_detached(nonce.data().into(), aad, &mut buffer)
+            .expect("AES-256-GCM encryption failed");
+        (buffer, AuthenticationTag::from_data(tag.into()))
+    }
+
+    fn aes_256_gcm_decrypt(&self, message: &EncryptedMessage) -> Result<Vec<u8>, bc_crypto::Error> {
+        let cipher = Aes256Gcm::new(self.data().into());
+        let mut buffer = message.ciphertext().clone();
+        cipher
+            .decrypt_in_place_detached(
+                message.nonce().data().into(),
+                message.aad(),
+                &mut buffer,
+                message.authentication_tag().data().into()
+            )
+            .map_err(|_| bc_crypto::Error::DecryptFailed)?;
+        Ok(buffer)
+    }
+
+    fn aes_256_gcm_siv_encrypt(
+        &self,
+        plaintext: Vec<u8>,
+        nonce: &Nonce,
+        aad: &[u8]
+    ) -> (Vec<u8>, AuthenticationTag) {
+        let cipher = Aes256GcmSiv::new(self.data().into());
+        let mut buffer = plaintext;
+        let tag = cipher
+            .encrypt_in_place_detached(nonce.data().into(), aad, &mut buffer)
+            .expect("AES-256-GCM-SIV encryption failed");
+        (buffer, AuthenticationTag::from_data(tag.into()))
+    }
+
+    fn aes_256_gcm_siv_decrypt(&self, message: &EncryptedMessage) -> Result<Vec<u8>, bc_crypto::Error> {
+        let cipher = Aes256GcmSiv::new(self.data().into());
+        let mut buffer = message.ciphertext().clone();
+        cipher
+            .decrypt_in_place_detached(
+                message.nonce().data().into(),
+                message.aad(),
+                &mut buffer,
+                message.authentication_tag().data().into()
+            )
+            .map_err(|_| bc_crypto::Error::DecryptFailed)?;
+        Ok(buffer)
     }
 }
 
+fn hkdf_sha256(
+    key_material: &[u8],
+    salt: Option<&[u8]>,
+    info: &[u8]
+) -> [u8; SymmetricKey::SYMMETRIC_KEY_SIZE] {
+    let mut okm = [0u8; SymmetricKey::SYMMETRIC_KEY_SIZE];
+    Hkdf::<Sha256>::new(salt, key_material)
+        .expand(info, &mut okm)
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    okm
+}
+
+#[cfg(feature = "std")]
 impl Default for SymmetricKey {
     fn default() -> Self {
         Self::new()
     }
 }
 
+impl AsRef<[u8]> for SymmetricKey {
+    fn as_ref(&self) -> &[u8] {
+        self.data()
+    }
+}
+
+impl TryFrom<&[u8]> for SymmetricKey {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_data_ref(data)
+    }
+}
+
+impl From<[u8; Self::SYMMETRIC_KEY_SIZE]> for SymmetricKey {
+    fn from(data: [u8; Self::SYMMETRIC_KEY_SIZE]) -> Self {
+        Self::from_data(data)
+    }
+}
+
 impl AsRef<SymmetricKey> for SymmetricKey {
     fn as_ref(&self) -> &SymmetricKey {
         self
@@ -139,8 +653,8 @@ impl From<&SymmetricKey> for Vec<u8> {
     }
 }
 
-impl std::fmt::Debug for SymmetricKey {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Debug for SymmetricKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "SymmetricKey({})", self.hex())
     }
 }
@@ -172,9 +686,340 @@ impl TryFrom<CBOR> for SymmetricKey {
 }
 
 impl CBORTaggedDecodable for SymmetricKey {
+    fn from_tagged_cbor(cbor: CBOR) -> Result<Self> {
+        crate::component_error::decode_tagged(cbor, "symmetric key")
+    }
+
     fn from_untagged_cbor(cbor: CBOR) -> Result<Self> {
         let bytes = CBOR::try_into_byte_string(cbor)?;
         let instance = Self::from_data_ref(bytes)?;
         Ok(instance)
     }
 }
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use dcbor::prelude::*;
+    use hex_literal::hex;
+    use zeroize::Zeroize;
+
+    use super::hkdf_sha256;
+    use crate::{
+        AeadAlgorithm, AgreementPrivateKey, ComponentError, ECKeyBase, ECPrivateKey, Ed25519PrivateKey,
+        EncryptedMessage, KdfParams, Nonce, NonceSequence, NonceTracker, Salt, SigningPrivateKey, SSKRGroupSpec,
+        SSKRSecret, SSKRShare, SSKRSpec, SymmetricKey,
+    };
+    use bc_ur::{ URDecodable, UREncodable };
+
+    #[test]
+    fn test_hkdf_rfc5869() {
+        // RFC 5869 A.1, truncated to 32 bytes.
+        let okm = hkdf_sha256(
+            &hex!("0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b"),
+            Some(&hex!("000102030405060708090a0b0c")),
+            &hex!("f0f1f2f3f4f5f6f7f8f9")
+        );
+        assert_eq!(okm, hex!("3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf"));
+
+        // RFC 5869 A.3 (no salt, no info), truncated to 32 bytes.
+        let okm = hkdf_sha256(&hex!("0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b"), None, &[]);
+        assert_eq!(okm, hex!("8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d"));
+    }
+
+    #[test]
+    fn test_from_password() {
+        let salt = Salt::from_data(hex!("0102030405060708090a0b0c0d0e0f10"));
+        let password = b"correct horse battery staple";
+        // Deliberately weak parameters to keep the test fast.
+        let argon2id = KdfParams::Argon2id { memory_kib: 64, iterations: 1, parallelism: 1 };
+        let scrypt = KdfParams::Scrypt { log_n: 4, r: 8, p: 1 };
+
+        let key = SymmetricKey::from_password(password, &salt, &argon2id).unwrap();
+        assert_eq!(key, SymmetricKey::from_password(password, &salt, &argon2id).unwrap());
+        assert_ne!(key, SymmetricKey::from_password(b"wrong password", &salt, &argon2id).unwrap());
+        let other_salt = Salt::from_data(hex!("1112131415161718"));
+        assert_ne!(key, SymmetricKey::from_password(password, &other_salt, &argon2id).unwrap());
+
+        let scrypt_key = SymmetricKey::from_password(password, &salt, &scrypt).unwrap();
+        assert_eq!(scrypt_key, SymmetricKey::from_password(password, &salt, &scrypt).unwrap());
+        assert_ne!(key, scrypt_key);
+
+        // The parameters round-trip through CBOR and re-derive the same key.
+        for params in [argon2id, scrypt] {
+            let decoded = KdfParams::from_tagged_cbor_data(params.to_cbor_data()).unwrap();
+            assert_eq!(decoded, params);
+            assert_eq!(
+                SymmetricKey::from_password(password, &salt, &decoded).unwrap(),
+                SymmetricKey::from_password(password, &salt, &params).unwrap()
+            );
+        }
+
+        let bad = KdfParams::Argon2id { memory_kib: 1, iterations: 1, parallelism: 1 };
+        assert!(SymmetricKey::from_password(password, &salt, &bad).is_err());
+    }
+
+    #[test]
+    fn test_wrap_key() {
+        let kek = SymmetricKey::new();
+        let target = SymmetricKey::new();
+        let wrapped = kek.wrap_key(&target);
+        let unwrapped: SymmetricKey = kek.unwrap_key(&wrapped).unwrap();
+        assert_eq!(unwrapped, target);
+
+        // Only the random nonce keeps two wrappings of the same key apart.
+        let wrapped_again = kek.wrap_key(&target);
+        assert_ne!(wrapped, wrapped_again);
+        assert_eq!(kek.unwrap_key::<SymmetricKey>(&wrapped_again).unwrap(), target);
+
+        let signing_key = SigningPrivateKey::new_schnorr(ECPrivateKey::new());
+        let wrapped = kek.wrap_key(&signing_key);
+        let unwrapped: SigningPrivateKey = kek.unwrap_key(&wrapped).unwrap();
+        assert_eq!(unwrapped, signing_key);
+
+        // Wrong key-encryption key.
+        let error = SymmetricKey::new().unwrap_key::<SigningPrivateKey>(&wrapped).unwrap_err();
+        assert!(matches!(error.downcast_ref::<bc_crypto::Error>(), Some(bc_crypto::Error::DecryptFailed)));
+
+        // Right key, wrong type.
+        assert!(kek.unwrap_key::<SymmetricKey>(&wrapped).is_err());
+    }
+
+    #[test]
+    fn test_derive() {
+        let master = SymmetricKey::from_data(
+            hex!("808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9f")
+        );
+        let encryption_key = master.derive(None, b"encryption");
+        let mac_key = master.derive(None, b"mac");
+        assert_ne!(encryption_key, mac_key);
+        assert_ne!(encryption_key, master);
+        assert_eq!(encryption_key, master.derive(None, b"encryption"));
+        assert_ne!(encryption_key, master.derive(Some(b"salt"), b"encryption"));
+        assert_eq!(encryption_key.data(), &hkdf_sha256(master.data(), None, b"encryption"));
+    }
+
+    #[test]
+    fn test_zeroize() {
+        let mut key = SymmetricKey::new();
+        let copy = key.clone();
+        key.zeroize();
+        assert_eq!(key.data(), &[0u8; SymmetricKey::SYMMETRIC_KEY_SIZE]);
+        assert_ne!(copy.data(), &[0u8; SymmetricKey::SYMMETRIC_KEY_SIZE]);
+
+        let mut key = AgreementPrivateKey::new();
+        let copy = key.clone();
+        key.zeroize();
+        assert_eq!(key.data(), &[0u8; AgreementPrivateKey::KEY_SIZE]);
+        assert_ne!(copy.data(), &[0u8; AgreementPrivateKey::KEY_SIZE]);
+
+        let ec_key = ECPrivateKey::new();
+        let mut key = SigningPrivateKey::new_schnorr(ec_key.clone());
+        key.zeroize();
+        assert_eq!(key.to_schnorr().unwrap().data(), &[0u8; ECPrivateKey::KEY_SIZE]);
+        assert_ne!(ec_key.data(), &[0u8; ECPrivateKey::KEY_SIZE]);
+    }
+
+    #[test]
+    fn test_encrypt_with_nonce() {
+        let key = SymmetricKey::from_data(
+            hex!("808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9f")
+        );
+        let nonce = Nonce::from_data(hex!("070000004041424344454647"));
+        let a = key.encrypt_with_nonce(b"plaintext", &nonce, b"aad", None).unwrap();
+        let b = key.encrypt_with_nonce(b"plaintext", &nonce, b"aad", None).unwrap();
+        assert_eq!(a, b);
+        assert_eq!(a.nonce(), &nonce);
+        assert_eq!(key.decrypt(&a).unwrap(), b"plaintext");
+
+        let mut tracker = NonceTracker::new();
+        key.encrypt_with_nonce(b"first", &nonce, b"", Some(&mut tracker)).unwrap();
+        let err = key.encrypt_with_nonce(b"second", &nonce, b"", Some(&mut tracker)).unwrap_err();
+        assert_eq!(err.to_string(), "Nonce reuse detected");
+        assert_eq!(err.downcast_ref(), Some(&ComponentError::NonceReuse));
+        let other = Nonce::new();
+        key.encrypt_with_nonce(b"second", &other, b"", Some(&mut tracker)).unwrap();
+
+        let zero = Nonce::from_data([0u8; Nonce::NONCE_SIZE]);
+        let err = key.encrypt_with_nonce(b"plaintext", &zero, b"", Some(&mut tracker)).unwrap_err();
+        assert_eq!(err.to_string(), "All-zero nonce rejected");
+        assert_eq!(err.downcast_ref(), Some(&ComponentError::ZeroNonce));
+        assert!(!tracker.contains(&zero));
+
+        // The first nonce of a zero-prefix sequence is accepted.
+        let mut sequence = NonceSequence::new();
+        let first = sequence.next().unwrap();
+        let message = key.encrypt_with_nonce(b"first", &first, b"", Some(&mut tracker)).unwrap();
+        assert_eq!(key.decrypt(&message).unwrap(), b"first");
+    }
+
+    #[test]
+    fn test_detached() {
+        let key = SymmetricKey::from_data(
+            hex!("808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9f")
+        );
+        let nonce = Nonce::from_data(hex!("070000004041424344454647"));
+        let (ciphertext, tag) = key.encrypt_detached(b"plaintext", &nonce, b"aad").unwrap();
+
+        // Recombined, the detached parts are the combined message.
+        let message = key.encrypt(b"plaintext", Some(b"aad"), Some(&nonce));
+        let recombined = EncryptedMessage::new(ciphertext.clone(), b"aad", nonce.clone(), tag.into());
+        assert_eq!(recombined.tagged_cbor_data(), message.tagged_cbor_data());
+
+        assert_eq!(key.decrypt_detached(ciphertext.clone(), &tag, &nonce, b"aad").unwrap(), b"plaintext");
+
+        let mut corrupted = tag;
+        corrupted[0] ^= 1;
+        assert!(key.decrypt_detached(ciphertext.clone(), &corrupted, &nonce, b"aad").is_err());
+        assert!(key.decrypt_detached(ciphertext, &tag, &nonce, b"other").is_err());
+
+        let zero = Nonce::from_data([0u8; Nonce::NONCE_SIZE]);
+        let err = key.encrypt_detached(b"plaintext", &zero, b"aad").unwrap_err();
+        assert_eq!(err.to_string(), "All-zero nonce rejected");
+    }
+
+    #[test]
+    fn test_split2() {
+        let key = SymmetricKey::new();
+        let (a, b) = key.split2();
+        assert_eq!(SymmetricKey::combine2(&a, &b), key);
+        assert_eq!(SymmetricKey::combine2(&b, &a), key);
+        assert_ne!(a, key);
+        assert_ne!(b, key);
+        assert_ne!(a, b);
+
+        // Splitting again gives unrelated shares of the same key.
+        let (c, d) = key.split2();
+        assert_ne!(c, a);
+        assert_eq!(SymmetricKey::combine2(&c, &d), key);
+        assert_ne!(SymmetricKey::combine2(&a, &d), key);
+
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        let (a, b) = key.split2_using(&mut rng);
+        let mut rng = bc_rand::make_fake_random_number_generator();
+        assert_eq!(key.split2_using(&mut rng), (a, b));
+    }
+
+    #[test]
+    fn test_sskr() {
+        let key = SymmetricKey::new();
+        let spec = SSKRSpec::new(1, vec![SSKRGroupSpec::new(2, 3).unwrap()]).unwrap();
+        let groups = key.to_sskr_shares(&spec).unwrap();
+        assert_eq!(groups.len(), 1);
+        let shares = &groups[0];
+        assert_eq!(shares.len(), 3);
+
+        let recovered = SymmetricKey::from_sskr_shares(&[shares[0].clone(), shares[2].clone()]).unwrap();
+        assert_eq!(recovered, key);
+        assert!(SymmetricKey::from_sskr_shares(&shares[..1]).is_err());
+
+        // Shares travel as URs.
+        let ur = shares[1].ur_string();
+        assert!(ur.starts_with("ur:sskr/"));
+        let decoded = SSKRShare::from_ur_string(ur).unwrap();
+        assert_eq!(SymmetricKey::from_sskr_shares(&[decoded, shares[2].clone()]).unwrap(), key);
+
+        // A secret of another size isn't a symmetric key.
+        let secret = SSKRSecret::new([7u8; 16]).unwrap();
+        let shares = crate::sskr_generate(&spec, &secret).unwrap();
+        assert!(SymmetricKey::from_sskr_shares(&shares[0][..2]).is_err());
+    }
+
+    #[test]
+    fn test_ct_eq() {
+        let a = SymmetricKey::new();
+        let b = SymmetricKey::new();
+        assert!(a.ct_eq(&a.clone()));
+        assert_eq!(a.ct_eq(&b), a == b);
+        assert!(!a.ct_eq(&b));
+
+        let a = AgreementPrivateKey::new();
+        let b = AgreementPrivateKey::new();
+        assert!(a.ct_eq(&a.clone()));
+        assert!(!a.ct_eq(&b));
+
+        let a = ECPrivateKey::new();
+        let b = ECPrivateKey::new();
+        assert!(a.ct_eq(&a.clone()));
+        assert!(!a.ct_eq(&b));
+
+        let a = Ed25519PrivateKey::new();
+        let b = Ed25519PrivateKey::new();
+        assert!(a.ct_eq(&a.clone()));
+        assert!(!a.ct_eq(&b));
+    }
+
+    #[test]
+    fn test_key_commitment() {
+        let a = SymmetricKey::new();
+        let b = SymmetricKey::new();
+        let plaintext = b"Wolf McNally";
+
+        let committed = a.encrypt_committing(plaintext, Some(b"aad"), None::<Nonce>, AeadAlgorithm::default());
+        assert!(committed.is_key_committing());
+        assert_eq!(a.decrypt(&committed).unwrap(), plaintext);
+        assert_eq!(a.decrypt_committing(&committed).unwrap(), plaintext);
+        assert!(b.decrypt(&committed).is_err());
+        assert_eq!(committed.decrypt_with_any(&[b.clone(), a.clone()]), Some((1, plaintext.to_vec())));
+
+        let decoded = EncryptedMessage::from_tagged_cbor_data(committed.to_cbor_data()).unwrap();
+        assert_eq!(decoded, committed);
+        let empty_aad = a.encrypt_committing(plaintext, None::<Vec<u8>>, None::<Nonce>, AeadAlgorithm::Aes256Gcm);
+        let decoded = EncryptedMessage::from_tagged_cbor_data(empty_aad.to_cbor_data()).unwrap();
+        assert_eq!(a.decrypt(&decoded).unwrap(), plaintext);
+
+        // Stand in for a ciphertext crafted to authenticate under both keys:
+        // it authenticates under `b` but carries a commitment to `a`, so it
+        // is rejected by both.
+        let uncommitted = b.encrypt(plaintext, None::<Vec<u8>>, None::<Nonce>);
+        assert!(b.decrypt(&uncommitted).is_ok());
+        assert!(b.decrypt_committing(&uncommitted).is_err());
+        let forged = uncommitted.clone().with_key_commitment(a.key_commitment(uncommitted.nonce()));
+        assert!(b.decrypt(&forged).is_err());
+        assert!(a.decrypt(&forged).is_err());
+    }
+
+    #[test]
+    fn test_siv_determinism() {
+        let key = SymmetricKey::new();
+        let plaintext = b"Wolf McNally";
+        let a = key.encrypt_with_algorithm(plaintext, Some(b"aad"), None::<Nonce>, AeadAlgorithm::Aes256GcmSiv);
+        let b = key.encrypt_with_algorithm(plaintext, Some(b"aad"), None::<Nonce>, AeadAlgorithm::Aes256GcmSiv);
+        assert_eq!(a, b);
+        assert_eq!(a.to_cbor_data(), b.to_cbor_data());
+        assert_eq!(key.decrypt(&a).unwrap(), plaintext);
+
+        let decoded = EncryptedMessage::from_tagged_cbor_data(a.tagged_cbor_data()).unwrap();
+        assert_eq!(decoded.algorithm(), AeadAlgorithm::Aes256GcmSiv);
+        assert_eq!(key.decrypt(&decoded).unwrap(), plaintext);
+
+        // A different AAD, plaintext, or key gives a different message.
+        let c = key.encrypt_with_algorithm(plaintext, Some(b"other"), None::<Nonce>, AeadAlgorithm::Aes256GcmSiv);
+        assert_ne!(a.nonce(), c.nonce());
+        let d = key.encrypt_with_algorithm(b"Other", Some(b"aad"), None::<Nonce>, AeadAlgorithm::Aes256GcmSiv);
+        assert_ne!(a.nonce(), d.nonce());
+        let other_key = SymmetricKey::new();
+        let e = other_key.encrypt_with_algorithm(plaintext, Some(b"aad"), None::<Nonce>, AeadAlgorithm::Aes256GcmSiv);
+        assert_ne!(a.ciphertext(), e.ciphertext());
+        assert!(other_key.decrypt(&a).is_err());
+
+        // The other algorithms still use a random nonce.
+        for algorithm in [AeadAlgorithm::ChaCha20Poly1305, AeadAlgorithm::Aes256Gcm] {
+            let a = key.encrypt_with_algorithm(plaintext, Some(b"aad"), None::<Nonce>, algorithm);
+            let b = key.encrypt_with_algorithm(plaintext, Some(b"aad"), None::<Nonce>, algorithm);
+            assert_ne!(a, b);
+        }
+    }
+
+    #[test]
+    fn test_raw_data_round_trip() {
+        let data = hex!("808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9f");
+        let key = SymmetricKey::from_data(data);
+        assert_eq!(key.data(), &data);
+        assert_eq!(SymmetricKey::from_data_ref(key.data()).unwrap(), key);
+        assert_eq!(SymmetricKey::from_data_ref(Vec::from(&key)).unwrap(), key);
+
+        let err = SymmetricKey::from_data_ref(&data[..31]).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid symmetric key size");
+        assert!(SymmetricKey::from_data_ref([0u8; 33]).is_err());
+    }
+}
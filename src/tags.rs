@@ -1,4 +1,5 @@
 use paste::paste;
+#[cfg(feature = "std")]
 use std::sync::{ Once, Mutex };
 use dcbor::prelude::*;
 
@@ -23,6 +24,7 @@ macro_rules! tag_constant {
     ($const_name:ident, $value:expr, $name:expr) => {
         paste! {
             pub const [<$const_name _VALUE>]: u64 = $value;
+            pub const [<$const_name _NAME>]: &str = $name;
         }
         pub const $const_name: Tag = Tag::new_with_static_name($value, $name);
     };
@@ -87,6 +89,7 @@ tag_constant!(SIGNATURE, 40020, "signature");
 tag_constant!(SIGNING_PRIVATE_KEY, 40021, "signing-private-key");
 tag_constant!(SIGNING_PUBLIC_KEY, 40022, "signing-public-key");
 tag_constant!(SYMMETRIC_KEY, 40023, "crypto-key");
+tag_constant!(KDF_PARAMS, 40024, "kdf-params");
 
 // Bitcoin-related
 
@@ -125,89 +128,148 @@ tag_constant!(OUTPUT_RAW_SCRIPT, 408, "output-raw-script"); // Fixed
 tag_constant!(OUTPUT_TAPROOT, 409, "output-taproot"); // Fixed
 tag_constant!(OUTPUT_COSIGNER, 410, "output-cosigner"); // Fixed
 
+macro_rules! known_tags {
+    ($($const_name:ident),* $(,)?) => {
+        /// The tags registered in `GLOBAL_TAGS`: every tag defined here except the
+        /// IANA-assigned `DATE`, `URI`, `UUID`, and `ENCODED_CBOR`.
+        pub const KNOWN_TAGS: &[Tag] = &[$($const_name),*];
+
+        /// The values and names of `KNOWN_TAGS`, sorted by value.
+        const KNOWN_TAG_NAMES: &[(u64, &str)] =
+            &sorted_by_value([$(paste! { ([<$const_name _VALUE>], [<$const_name _NAME>]) }),*]);
+    };
+}
+
+known_tags!(
+    ACCOUNT_DESCRIPTOR,
+    ACCOUNT_V1,
+    ADDRESS,
+    ADDRESS_V1,
+    AGREEMENT_PRIVATE_KEY,
+    AGREEMENT_PUBLIC_KEY,
+    ARID,
+    COMPRESSED,
+    DERIVATION_PATH,
+    DERIVATION_PATH_V1,
+    DIGEST,
+    EC_KEY,
+    EC_KEY_V1,
+    ENCRYPTED,
+    ENVELOPE,
+    FUNCTION,
+    HDKEY,
+    HDKEY_V1,
+    KDF_PARAMS,
+    KNOWN_VALUE,
+    LEAF,
+    NONCE,
+    OUTPUT_COMBO,
+    OUTPUT_COSIGNER,
+    OUTPUT_DESCRIPTOR,
+    OUTPUT_DESCRIPTOR_V1,
+    OUTPUT_MULTISIG,
+    OUTPUT_PUBLIC_KEY,
+    OUTPUT_PUBLIC_KEY_HASH,
+    OUTPUT_RAW_SCRIPT,
+    OUTPUT_SCRIPT_HASH,
+    OUTPUT_SORTED_MULTISIG,
+    OUTPUT_TAPROOT,
+    OUTPUT_WITNESS_PUBLIC_KEY_HASH,
+    OUTPUT_WITNESS_SCRIPT_HASH,
+    PARAMETER,
+    PASSWORD,
+    PLACEHOLDER,
+    PRIVATE_KEY_BASE,
+    PSBT,
+    PSBT_V1,
+    PUBLIC_KEY_BASE,
+    REPLACEMENT,
+    REQUEST,
+    RESPONSE,
+    SALT,
+    SEALED_MESSAGE,
+    SEED,
+    SEED_V1,
+    SIGNATURE,
+    SIGNING_PRIVATE_KEY,
+    SIGNING_PUBLIC_KEY,
+    SSH_TEXT_PRIVATE_KEY,
+    SSH_TEXT_PUBLIC_KEY,
+    SSH_TEXT_SIGNATURE,
+    SSH_TEXT_CERTIFICATE,
+    SSKR_SHARE,
+    SSKR_SHARE_V1,
+    SYMMETRIC_KEY,
+    USE_INFO,
+    USE_INFO_V1,
+);
+
+const fn sorted_by_value<const N: usize>(
+    mut entries: [(u64, &'static str); N]
+) -> [(u64, &'static str); N] {
+    // A stable insertion sort, as iterators aren't available in const fns.
+    let mut i = 1;
+    while i < N {
+        let mut j = i;
+        while j > 0 && entries[j - 1].0 > entries[j].0 {
+            let entry = entries[j];
+            entries[j] = entries[j - 1];
+            entries[j - 1] = entry;
+            j -= 1;
+        }
+        i += 1;
+    }
+    entries
+}
+
+/// Returns the name of the tag in `KNOWN_TAGS` with the given value, if any.
+///
+/// This is a binary search over a table sorted at compile time, so it is
+/// cheap enough to call for every tag while rendering diagnostics. Where two
+/// known tags share a value, the name returned is the one `GLOBAL_TAGS` uses.
+pub fn known_tag_name(value: u64) -> Option<&'static str> {
+    let end = KNOWN_TAG_NAMES.partition_point(|(v, _)| *v <= value);
+    match end.checked_sub(1).map(|i| KNOWN_TAG_NAMES[i]) {
+        Some((v, name)) if v == value => Some(name),
+        _ => None,
+    }
+}
+
+/// Returns a new `TagsStore` seeded with `KNOWN_TAGS`.
+///
+/// Application-specific tags can be added to the returned store with
+/// `TagsStore::insert` and looked up with the methods of `TagsStoreTrait`,
+/// without affecting `GLOBAL_TAGS`.
+pub fn known_tags_store() -> TagsStore {
+    TagsStore::new(KNOWN_TAGS.iter().cloned())
+}
+
+#[cfg(feature = "std")]
 #[derive(Debug)]
 pub struct LazyTagsStore {
     init: Once,
     data: Mutex<Option<TagsStore>>,
 }
 
+#[cfg(feature = "std")]
 impl LazyTagsStore {
     pub fn get(&self) -> std::sync::MutexGuard<'_, Option<TagsStore>> {
         self.init.call_once(|| {
-            let m = TagsStore::new([
-                ACCOUNT_DESCRIPTOR,
-                ACCOUNT_V1,
-                ADDRESS,
-                ADDRESS_V1,
-                AGREEMENT_PRIVATE_KEY,
-                AGREEMENT_PUBLIC_KEY,
-                ARID,
-                COMPRESSED,
-                DERIVATION_PATH,
-                DERIVATION_PATH_V1,
-                DIGEST,
-                EC_KEY,
-                EC_KEY_V1,
-                ENCRYPTED,
-                ENVELOPE,
-                FUNCTION,
-                HDKEY,
-                HDKEY_V1,
-                KNOWN_VALUE,
-                LEAF,
-                NONCE,
-                OUTPUT_COMBO,
-                OUTPUT_COSIGNER,
-                OUTPUT_DESCRIPTOR,
-                OUTPUT_DESCRIPTOR_V1,
-                OUTPUT_MULTISIG,
-                OUTPUT_PUBLIC_KEY,
-                OUTPUT_PUBLIC_KEY_HASH,
-                OUTPUT_RAW_SCRIPT,
-                OUTPUT_SCRIPT_HASH,
-                OUTPUT_SORTED_MULTISIG,
-                OUTPUT_TAPROOT,
-                OUTPUT_WITNESS_PUBLIC_KEY_HASH,
-                OUTPUT_WITNESS_SCRIPT_HASH,
-                PARAMETER,
-                PASSWORD,
-                PLACEHOLDER,
-                PRIVATE_KEY_BASE,
-                PSBT,
-                PSBT_V1,
-                PUBLIC_KEY_BASE,
-                REPLACEMENT,
-                REQUEST,
-                RESPONSE,
-                SALT,
-                SEALED_MESSAGE,
-                SEED,
-                SEED_V1,
-                SIGNATURE,
-                SIGNING_PRIVATE_KEY,
-                SIGNING_PUBLIC_KEY,
-                SSH_TEXT_PRIVATE_KEY,
-                SSH_TEXT_PUBLIC_KEY,
-                SSH_TEXT_SIGNATURE,
-                SSH_TEXT_CERTIFICATE,
-                SSKR_SHARE,
-                SSKR_SHARE_V1,
-                SYMMETRIC_KEY,
-                USE_INFO,
-                USE_INFO_V1,
-            ]);
+            let m = known_tags_store();
             *self.data.lock().unwrap() = Some(m);
         });
         self.data.lock().unwrap()
     }
 }
 
+#[cfg(feature = "std")]
 pub static GLOBAL_TAGS: LazyTagsStore = LazyTagsStore {
     init: Once::new(),
     data: Mutex::new(None),
 };
 
 /// A macro for accessing the global tags store.
+#[cfg(feature = "std")]
 #[macro_export]
 macro_rules! with_tags {
     ($action:expr) => {
@@ -220,10 +282,8 @@ macro_rules! with_tags {
     };
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests {
-    use crate::with_tags;
-
     #[test]
     fn test_1() {
         use crate::*;
@@ -233,4 +293,46 @@ mod tests {
             assert_eq!(tags.name_for_tag(&tags::LEAF), "leaf");
         });
     }
+
+    #[test]
+    fn test_known_tags_store() {
+        use dcbor::prelude::*;
+        use crate::tags::{ self, known_tags_store, KNOWN_TAGS };
+
+        let mut store = known_tags_store();
+        assert_eq!(store.tag_for_name("leaf"), Some(tags::LEAF));
+        assert_eq!(store.name_for_tag(&tags::ARID), "arid");
+        assert!(store.tag_for_value(90000).is_none());
+        assert!(store.tag_for_name("my-app-type").is_none());
+
+        store.insert(Tag::new_with_static_name(90000, "my-app-type"));
+        assert_eq!(store.tag_for_name("my-app-type").unwrap().value(), 90000);
+        assert_eq!(store.name_for_tag(&Tag::new(90000)), "my-app-type");
+        assert_eq!(store.assigned_name_for_tag(&Tag::new(90001)), None);
+
+        // The store is independent of the global one.
+        assert!(crate::GLOBAL_TAGS.get().as_ref().unwrap().tag_for_value(90000).is_none());
+        for tag in KNOWN_TAGS {
+            assert_eq!(known_tags_store().tag_for_value(tag.value()).as_ref(), Some(tag));
+        }
+    }
+
+    #[test]
+    fn test_known_tag_name() {
+        use dcbor::prelude::*;
+        use crate::tags::{ self, known_tag_name, KNOWN_TAGS, KNOWN_TAG_NAMES };
+
+        assert_eq!(known_tag_name(tags::LEAF_VALUE), Some("leaf"));
+        assert_eq!(known_tag_name(tags::SSH_TEXT_CERTIFICATE_VALUE), Some("ssh-certificate"));
+        assert_eq!(known_tag_name(0), None);
+        assert_eq!(known_tag_name(40013), None);
+        assert_eq!(known_tag_name(u64::MAX), None);
+
+        assert!(KNOWN_TAG_NAMES.windows(2).all(|w| w[0].0 <= w[1].0));
+        let store = crate::GLOBAL_TAGS.get();
+        let store = store.as_ref().unwrap();
+        for tag in KNOWN_TAGS {
+            assert_eq!(known_tag_name(tag.value()).map(String::from), store.assigned_name_for_tag(tag));
+        }
+    }
 }
@@ -1,4 +1,5 @@
-use std::str::FromStr;
+use core::str::FromStr;
+use base64::{ engine::general_purpose::STANDARD, Engine as _ };
 use dcbor::prelude::*;
 use url::Url;
 use anyhow::{ bail, Result, Error };
@@ -6,20 +7,136 @@ use anyhow::{ bail, Result, Error };
 use crate::tags;
 
 /// A URI.
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct URI(String);
+///
+/// The string is kept exactly as given. The parsed form is cached so the
+/// component accessors don't need to re-parse it.
+#[derive(Clone)]
+pub struct URI {
+    string: String,
+    url: Url,
+}
 
 impl URI {
     /// Creates a new `URI` from a string.
     ///
-    /// No validation is performed on the string.
+    /// Returns an error if the string is not a valid absolute URI.
     pub fn new(uri: impl Into<String>) -> Result<Self> {
-        let uri = uri.into();
-        if Url::parse(&uri).is_ok() {
-            Ok(Self(uri))
-        } else {
-            bail!("Invalid URI")
+        let string = uri.into();
+        match Url::parse(&string) {
+            Ok(url) => Ok(Self { string, url }),
+            Err(_) => bail!("Invalid URI"),
+        }
+    }
+
+    /// Returns the scheme, lowercased, without the trailing `:`.
+    pub fn scheme(&self) -> &str {
+        self.url.scheme()
+    }
+
+    /// Returns the host, if any.
+    pub fn host(&self) -> Option<&str> {
+        self.url.host_str()
+    }
+
+    /// Returns the path. May be empty for URIs that cannot be a base, such as
+    /// `mailto:`.
+    pub fn path(&self) -> &str {
+        self.url.path()
+    }
+
+    /// Returns the query, without the leading `?`, if any.
+    pub fn query(&self) -> Option<&str> {
+        self.url.query()
+    }
+
+    /// Returns the fragment, without the leading `#`, if any.
+    pub fn fragment(&self) -> Option<&str> {
+        self.url.fragment()
+    }
+
+    /// Returns the canonical form of this URI, as serialized by the `url`
+    /// crate, for comparing URIs that differ only in their spelling.
+    ///
+    /// The scheme and host are lowercased, dot-segments are resolved,
+    /// default ports are removed, and characters that must be
+    /// percent-encoded are, so `http://Example.COM:80/a/../b` becomes
+    /// `http://example.com/b`. An empty path becomes `/`. Existing
+    /// percent-encodings are kept as they are.
+    pub fn normalized(&self) -> URI {
+        Self { string: self.url.to_string(), url: self.url.clone() }
+    }
+
+    /// Creates a `data:` URI carrying `data` as Base64, in the form
+    /// `data:<mime>;base64,<data>`, as described in
+    /// [IETF RFC 2397](https://www.ietf.org/rfc/rfc2397.txt).
+    pub fn data_uri(mime: &str, data: &[u8]) -> URI {
+        Self::new(format!("data:{};base64,{}", mime, STANDARD.encode(data)))
+            .expect("a data URI is a valid URI")
+    }
+
+    /// Returns the media type and decoded payload of a Base64 `data:` URI.
+    ///
+    /// The media type is returned as written, including any parameters such
+    /// as `charset`, and is empty if the URI gives none. Returns `None` if
+    /// this is not a `data:` URI, or if its payload is not Base64 encoded.
+    pub fn data_uri_payload(&self) -> Option<(String, Vec<u8>)> {
+        if self.scheme() != "data" {
+            return None;
+        }
+        let (header, data) = self.string["data:".len()..].split_once(',')?;
+        let split = header.len().checked_sub(";base64".len())?;
+        if !header.is_char_boundary(split) || !header[split..].eq_ignore_ascii_case(";base64") {
+            return None;
         }
+        let mime = &header[..split];
+        let data = STANDARD.decode(data).ok()?;
+        Some((mime.to_string(), data))
+    }
+
+    /// Resolves a relative reference against this URI, as a browser would
+    /// resolve a link on the page this URI names.
+    pub fn join(&self, relative: &str) -> Result<URI> {
+        let url = self.url.join(relative)?;
+        Ok(Self { string: url.to_string(), url })
+    }
+}
+
+impl PartialEq for URI {
+    fn eq(&self, other: &Self) -> bool {
+        self.string == other.string
+    }
+}
+
+impl Eq for URI {}
+
+// Compare a URI to a string, exactly as it was given.
+impl PartialEq<str> for URI {
+    fn eq(&self, other: &str) -> bool {
+        self.string == other
+    }
+}
+
+impl PartialEq<&str> for URI {
+    fn eq(&self, other: &&str) -> bool {
+        self.string == *other
+    }
+}
+
+impl PartialEq<URI> for str {
+    fn eq(&self, other: &URI) -> bool {
+        other == self
+    }
+}
+
+impl PartialEq<URI> for &str {
+    fn eq(&self, other: &URI) -> bool {
+        other == self
+    }
+}
+
+impl core::fmt::Debug for URI {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("URI").field(&self.string).finish()
     }
 }
 
@@ -33,13 +150,13 @@ impl FromStr for URI {
 
 impl AsRef<str> for URI {
     fn as_ref(&self) -> &str {
-        &self.0
+        &self.string
     }
 }
 
 impl AsRef<String> for URI {
     fn as_ref(&self) -> &String {
-        &self.0
+        &self.string
     }
 }
 
@@ -63,7 +180,7 @@ impl From<URI> for CBOR {
 
 impl CBORTaggedEncodable for URI {
     fn untagged_cbor(&self) -> CBOR {
-        self.0.clone().into()
+        self.string.clone().into()
     }
 }
 
@@ -76,42 +193,137 @@ impl TryFrom<CBOR> for URI {
 }
 
 impl CBORTaggedDecodable for URI {
+    fn from_tagged_cbor(cbor: CBOR) -> Result<Self> {
+        crate::component_error::decode_tagged(cbor, "URI")
+    }
+
     fn from_untagged_cbor(cbor: CBOR) -> Result<Self> {
         let uri: String = cbor.try_into()?;
         Self::new(uri)
     }
 }
 
-impl std::fmt::Display for URI {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0)
+impl core::fmt::Display for URI {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.string)
     }
 }
 
 // Convert from a string to a URI.
-impl From<&str> for URI {
-    fn from(uri: &str) -> Self {
-        Self::new(uri).unwrap()
+impl TryFrom<&str> for URI {
+    type Error = Error;
+
+    fn try_from(uri: &str) -> Result<Self, Self::Error> {
+        Self::new(uri)
     }
 }
 
 // Convert from a string to a URI.
-impl From<String> for URI {
-    fn from(uri: String) -> Self {
-        Self::new(uri).unwrap()
+impl TryFrom<String> for URI {
+    type Error = Error;
+
+    fn try_from(uri: String) -> Result<Self, Self::Error> {
+        Self::new(uri)
     }
 }
 
 // Convert from a URI to a string.
 impl From<URI> for String {
     fn from(uri: URI) -> Self {
-        uri.0
+        uri.string
     }
 }
 
 // Convert from a URI to a string.
 impl From<&URI> for String {
     fn from(uri: &URI) -> Self {
-        uri.0.clone()
+        uri.string.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::URI;
+
+    #[test]
+    fn test_accessors() {
+        let uri = URI::new("https://Example.com:8443/a/b/c.html?x=1&y=2#section").unwrap();
+        assert_eq!(uri.scheme(), "https");
+        assert_eq!(uri.host(), Some("example.com"));
+        assert_eq!(uri.path(), "/a/b/c.html");
+        assert_eq!(uri.query(), Some("x=1&y=2"));
+        assert_eq!(uri.fragment(), Some("section"));
+        // The original string is preserved.
+        assert_eq!(uri.to_string(), "https://Example.com:8443/a/b/c.html?x=1&y=2#section");
+
+        let uri = URI::new("mailto:someone@example.com").unwrap();
+        assert_eq!(uri.scheme(), "mailto");
+        assert_eq!(uri.host(), None);
+        assert_eq!(uri.query(), None);
+    }
+
+    #[test]
+    fn test_join() {
+        let base = URI::new("https://example.com/api/v1/").unwrap();
+        assert_eq!(base.join("users/42").unwrap().to_string(), "https://example.com/api/v1/users/42");
+        assert_eq!(base.join("../v2/").unwrap().path(), "/api/v2/");
+        assert_eq!(base.join("/root?q=1").unwrap().to_string(), "https://example.com/root?q=1");
+        assert!(URI::new("mailto:someone@example.com").unwrap().join("other").is_err());
+    }
+
+    #[test]
+    fn test_try_from() {
+        let uri = URI::try_from("https://example.com").unwrap();
+        assert_eq!(URI::try_from(String::from("https://example.com")).unwrap(), uri);
+        assert_eq!("https://example.com".parse::<URI>().unwrap(), uri);
+
+        assert!(URI::try_from("not a uri").is_err());
+        assert!(URI::try_from(String::from("/relative/path")).is_err());
+        assert!("".parse::<URI>().is_err());
+    }
+
+    #[test]
+    fn test_eq_str() {
+        let uri = URI::new("https://Example.com/").unwrap();
+        assert!(uri == "https://Example.com/");
+        assert!("https://Example.com/" == uri);
+        assert!(uri == *"https://Example.com/");
+        assert!(*"https://Example.com/" == uri);
+        // The comparison is on the string as given, not its normalized form.
+        assert!(uri != "https://example.com/");
+        assert!("https://example.com" != uri);
+    }
+
+    #[test]
+    fn test_data_uri() {
+        let png = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0xff];
+        let uri = URI::data_uri("image/png", &png);
+        assert_eq!(uri, "data:image/png;base64,iVBORw0KGgoA/w==");
+        assert_eq!(uri.scheme(), "data");
+        assert_eq!(uri.data_uri_payload(), Some(("image/png".to_string(), png.to_vec())));
+
+        let uri = URI::data_uri("text/plain;charset=utf-8", b"");
+        assert_eq!(uri.data_uri_payload(), Some(("text/plain;charset=utf-8".to_string(), vec![])));
+
+        let uri = URI::new("DATA:text/plain;BASE64,SGk=").unwrap();
+        assert_eq!(uri.data_uri_payload(), Some(("text/plain".to_string(), b"Hi".to_vec())));
+
+        assert_eq!(URI::new("https://example.com/").unwrap().data_uri_payload(), None);
+        assert_eq!(URI::new("data:,Hello%2C%20World").unwrap().data_uri_payload(), None);
+        assert_eq!(URI::new("data:text/plain;base64,!!").unwrap().data_uri_payload(), None);
+    }
+
+    #[test]
+    fn test_normalized() {
+        let uri = URI::new("http://Example.COM/a/../b").unwrap();
+        assert_eq!(uri.normalized(), "http://example.com/b");
+        assert_eq!(uri, "http://Example.COM/a/../b");
+        assert_eq!(URI::new("HTTPS://example.com:443").unwrap().normalized(), "https://example.com/");
+        assert_eq!(URI::new("https://example.com/a b").unwrap().normalized(), "https://example.com/a%20b");
+
+        for canonical in ["https://example.com/b?x=1#y", "mailto:someone@example.com"] {
+            let uri = URI::new(canonical).unwrap();
+            assert_eq!(uri.normalized(), uri);
+        }
     }
 }
@@ -1,8 +1,12 @@
-use std::str::FromStr;
+use alloc::{ format, string::{ String, ToString }, vec, vec::Vec };
+use core::str::FromStr;
+#[cfg(feature = "std")]
+use std::time::{ Duration, SystemTime, UNIX_EPOCH };
 
-use anyhow::{ bail, Result, Error };
+use anyhow::{ anyhow, bail, Result, Error };
 use dcbor::prelude::*;
-use crate::tags;
+use crate::{ tags, ComponentError };
+use bc_rand::RandomNumberGenerator;
 
 /// A UUID.
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -12,14 +16,70 @@ impl UUID {
     pub const UUID_SIZE: usize = 16;
 
     /// Creates a new type 4 (random) UUID.
+    #[cfg(feature = "std")]
     pub fn new() -> Self {
+        let mut rng = bc_rand::SecureRandomNumberGenerator;
+        Self::new_using(&mut rng)
+    }
+
+    /// Creates a new type 4 (random) UUID using the given random number
+    /// generator.
+    pub fn new_using(rng: &mut impl RandomNumberGenerator) -> Self {
         let mut uuid = [0u8; Self::UUID_SIZE];
-        bc_rand::fill_random_data(&mut uuid);
+        rng.fill_random_data(&mut uuid);
         uuid[6] = (uuid[6] & 0x0f) | 0x40; // set version to 4
         uuid[8] = (uuid[8] & 0x3f) | 0x80; // set variant to 2
         Self(uuid)
     }
 
+    /// Creates a new type 7 (time-ordered) UUID for the current time.
+    ///
+    /// The first 48 bits are the number of milliseconds since the Unix epoch,
+    /// so UUIDs created at least a millisecond apart sort in creation order
+    /// by their bytes. The remaining bits, other than the version and
+    /// variant, are random.
+    #[cfg(feature = "std")]
+    pub fn new_v7() -> Self {
+        let mut rng = bc_rand::SecureRandomNumberGenerator;
+        Self::new_v7_using(SystemTime::now(), &mut rng)
+    }
+
+    /// Creates a new type 7 (time-ordered) UUID for the given time using the
+    /// given random number generator.
+    ///
+    /// Times before the Unix epoch are clamped to the epoch.
+    #[cfg(feature = "std")]
+    pub fn new_v7_using(time: SystemTime, rng: &mut impl RandomNumberGenerator) -> Self {
+        let millis = time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        let mut uuid = [0u8; Self::UUID_SIZE];
+        rng.fill_random_data(&mut uuid[6..]);
+        uuid[0..6].copy_from_slice(&millis.to_be_bytes()[2..8]);
+        uuid[6] = (uuid[6] & 0x0f) | 0x70; // set version to 7
+        uuid[8] = (uuid[8] & 0x3f) | 0x80; // set variant to 2
+        Self(uuid)
+    }
+
+    /// Returns the version number of the UUID.
+    pub fn version(&self) -> u8 {
+        self.0[6] >> 4
+    }
+
+    /// Returns the time embedded in a type 7 UUID, with millisecond precision.
+    ///
+    /// Returns `None` for other versions.
+    #[cfg(feature = "std")]
+    pub fn timestamp(&self) -> Option<SystemTime> {
+        if self.version() != 7 {
+            return None;
+        }
+        let mut millis = [0u8; 8];
+        millis[2..8].copy_from_slice(&self.0[0..6]);
+        Some(UNIX_EPOCH + Duration::from_millis(u64::from_be_bytes(millis)))
+    }
+
     /// Restores a UUID from data.
     pub fn from_data(data: [u8; Self::UUID_SIZE]) -> Self {
         Self(data)
@@ -42,6 +102,7 @@ impl UUID {
     }
 }
 
+#[cfg(feature = "std")]
 impl Default for UUID {
     fn default() -> Self {
         Self::new()
@@ -93,10 +154,14 @@ impl TryFrom<CBOR> for UUID {
 }
 
 impl CBORTaggedDecodable for UUID {
+    fn from_tagged_cbor(cbor: CBOR) -> Result<Self> {
+        crate::component_error::decode_tagged(cbor, "UUID")
+    }
+
     fn from_untagged_cbor(cbor: CBOR) -> Result<Self> {
         let bytes = CBOR::try_into_byte_string(cbor)?;
         if bytes.len() != Self::UUID_SIZE {
-            bail!("invalid UUID size");
+            bail!(ComponentError::InvalidLength { component: "UUID" });
         }
         let mut uuid = [0u8; Self::UUID_SIZE];
         uuid.copy_from_slice(&bytes);
@@ -104,8 +169,8 @@ impl CBORTaggedDecodable for UUID {
     }
 }
 
-impl std::fmt::Display for UUID {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for UUID {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", String::from(self))
     }
 }
@@ -125,15 +190,94 @@ impl From<&UUID> for String {
     }
 }
 
+/// Parses either the canonical hyphenated `8-4-4-4-12` form or 32 bare
+/// hexadecimal digits, in either case.
 impl FromStr for UUID {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let s = s.trim();
-        let s = s.replace('-', "");
-        let bytes = hex::decode(s).unwrap();
+        let hex = match s.len() {
+            32 => s.to_string(),
+            36 => {
+                let bytes = s.as_bytes();
+                if [8, 13, 18, 23].iter().any(|&i| bytes[i] != b'-') {
+                    bail!("invalid UUID format: {}", s);
+                }
+                s.replace('-', "")
+            }
+            _ => bail!("invalid UUID length: {}", s),
+        };
+        let bytes = hex::decode(&hex).map_err(|e| anyhow!("invalid UUID hex: {}", e))?;
         let mut uuid = [0u8; Self::UUID_SIZE];
         uuid.copy_from_slice(&bytes);
         Ok(Self::from_data(uuid))
     }
 }
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use std::{ str::FromStr, time::{ Duration, SystemTime, UNIX_EPOCH } };
+    use bc_rand::make_fake_random_number_generator;
+    use hex_literal::hex;
+
+    use super::UUID;
+
+    #[test]
+    fn test_version() {
+        assert_eq!(UUID::new().version(), 4);
+        assert_eq!(UUID::new().timestamp(), None);
+        let uuid = UUID::new_v7();
+        assert_eq!(uuid.version(), 7);
+        assert_eq!(uuid.data()[8] & 0xc0, 0x80);
+    }
+
+    #[test]
+    fn test_v7_layout() {
+        let time = UNIX_EPOCH + Duration::from_millis(0x0123_4567_89ab);
+        let mut rng = make_fake_random_number_generator();
+        let uuid = UUID::new_v7_using(time, &mut rng);
+        assert_eq!(&uuid.data()[0..6], &[0x01, 0x23, 0x45, 0x67, 0x89, 0xab]);
+        assert_eq!(uuid.timestamp(), Some(time));
+        assert!(uuid.to_string().starts_with("01234567-89ab-7"));
+    }
+
+    #[test]
+    fn test_v7_ordering() {
+        let mut rng = make_fake_random_number_generator();
+        let start = SystemTime::now();
+        let mut uuids: Vec<UUID> = (0..10)
+            .map(|i| UUID::new_v7_using(start + Duration::from_millis(i * 3), &mut rng))
+            .collect();
+        let created = uuids.clone();
+        uuids.sort_by(|a, b| a.data().cmp(b.data()));
+        assert_eq!(uuids, created);
+
+        let first = UUID::new_v7();
+        std::thread::sleep(Duration::from_millis(2));
+        let second = UUID::new_v7();
+        assert!(first.data() < second.data());
+        assert!(first.timestamp() < second.timestamp());
+    }
+
+    #[test]
+    fn test_from_str() {
+        let expected = UUID::from_data(hex!("f81d4fae7dec11d0a76500a0c91e6bf6"));
+        assert_eq!(UUID::from_str("f81d4fae-7dec-11d0-a765-00a0c91e6bf6").unwrap(), expected);
+        assert_eq!(UUID::from_str("F81D4FAE-7DEC-11D0-A765-00A0C91E6BF6").unwrap(), expected);
+        assert_eq!(UUID::from_str("f81d4fae7dec11d0a76500a0c91e6bf6").unwrap(), expected);
+        assert_eq!(expected.to_string(), "f81d4fae-7dec-11d0-a765-00a0c91e6bf6");
+
+        let uuid = UUID::new();
+        assert_eq!(UUID::from_str(&uuid.to_string()).unwrap(), uuid);
+    }
+
+    #[test]
+    fn test_from_str_errors() {
+        assert!(UUID::from_str("").is_err());
+        assert!(UUID::from_str("f81d4fae-7dec-11d0-a765-00a0c91e6bf").is_err());
+        assert!(UUID::from_str("f81d4fae7-dec-11d0-a765-00a0c91e6bf6").is_err());
+        assert!(UUID::from_str("g81d4fae-7dec-11d0-a765-00a0c91e6bf6").is_err());
+        assert!(UUID::from_str("f81d4fae7dec11d0a76500a0c91e6bf6ff").is_err());
+    }
+}
@@ -0,0 +1,77 @@
+use crate::{ DigestHasher, Signature, SigningPublicKey, Verifier };
+
+/// Verifies a signature over a message that is fed in incrementally, so a
+/// large message can be checked while it is read once, without buffering it.
+///
+/// This is prehashed verification: the message is hashed with SHA-256 as it
+/// arrives, and the signature is checked over the 32-byte `Digest` of the
+/// whole message, not over the message itself. The signer must therefore have
+/// signed `Digest::from_image(message)` (for example, the result of
+/// `Digest::from_reader`), and a signature made directly over the message will
+/// not verify here.
+#[derive(Clone, Default, Debug)]
+pub struct VerificationContext(DigestHasher);
+
+impl VerificationContext {
+    /// Creates a new context with no message data.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feeds the next chunk of the message into the context.
+    pub fn update(&mut self, chunk: &[u8]) {
+        self.0.update(chunk);
+    }
+
+    /// Consumes the context and returns `true` if `signature` was made by
+    /// `key` over the digest of all the message data fed to it.
+    pub fn verify(self, signature: &Signature, key: &SigningPublicKey) -> bool {
+        key.verify(signature, &self.0.finalize())
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use crate::{
+        Digest, ECPrivateKey, Ed25519PrivateKey, Signer, SigningPrivateKey, Verifier,
+        VerificationContext,
+    };
+
+    fn make_data(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[test]
+    fn test_chunked_matches_one_shot() {
+        let message = make_data(100_000);
+        let digest = Digest::from_image(&message);
+        let signers = [
+            SigningPrivateKey::new_schnorr(ECPrivateKey::new()),
+            SigningPrivateKey::new_ecdsa(ECPrivateKey::new()),
+            SigningPrivateKey::new_ed25519(Ed25519PrivateKey::new()),
+        ];
+        for signer in signers {
+            let key = signer.public_key();
+            let signature = signer.sign(&digest).unwrap();
+            assert!(key.verify(&signature, &digest));
+
+            let mut context = VerificationContext::new();
+            for chunk in message.chunks(4096) {
+                context.update(chunk);
+            }
+            assert!(context.verify(&signature, &key));
+
+            // A different message doesn't verify.
+            let mut context = VerificationContext::new();
+            context.update(&message[1..]);
+            assert!(!context.verify(&signature, &key));
+
+            // Nor does a signature over the message itself rather than its
+            // digest.
+            let direct = signer.sign(&message).unwrap();
+            let mut context = VerificationContext::new();
+            context.update(&message);
+            assert!(!context.verify(&direct, &key));
+        }
+    }
+}